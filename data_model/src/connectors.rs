@@ -1,5 +1,6 @@
 use std::any::Any;
-use std::sync::mpsc::{SyncSender, Receiver};
+use std::sync::mpsc::{SyncSender, Receiver, RecvTimeoutError};
+use std::time::Duration;
 use crate::memory_manager::DataHeader;
 use crate::streaming_data::StreamErrCode;
 
@@ -7,6 +8,16 @@ pub trait ConnectorTrait: Send {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn get_header(&self) -> &DataHeader;
+    /// Moves one value from this connector to `output`. Only an `Input<T>`
+    /// can forward, and only to an `Output<T>` of the same type.
+    fn forward(&mut self, _output: &dyn ConnectorTrait) -> Result<(), StreamErrCode> {
+        Err(StreamErrCode::InvalidOperation)
+    }
+    /// Connects this connector to `input`. Only an `Output<T>` can be
+    /// connected, and only to an `Input<T>` of the same type.
+    fn connect_input(&mut self, _input: &dyn ConnectorTrait) -> Result<(), StreamErrCode> {
+        Err(StreamErrCode::InvalidOperation)
+    }
 }
 
 pub struct Input<T: 'static + Send + Any + Clone> {
@@ -21,7 +32,7 @@ where T: 'static + Send + Any + Clone
     pub fn new(name: &'static str) -> Self{
         let (sender, receiver) = std::sync::mpsc::sync_channel(50);
         Self {
-            header: DataHeader{name},
+            header: DataHeader{name, type_name: std::any::type_name::<T>()},
             sender,
             receiver,
         }
@@ -42,11 +53,32 @@ where T: 'static + Send + Any + Clone
             Err(StreamErrCode::ReceiveDataError)
         }
     }
+    /// Waits at most `timeout` for a value, None if none arrived in time.
+    pub fn recv_timeout(&mut self, timeout: Duration) -> Result<Option<T>, StreamErrCode> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(data) => Ok(Some(data)),
+            Err(RecvTimeoutError::Timeout) => Ok(None),
+            Err(RecvTimeoutError::Disconnected) => Err(StreamErrCode::ReceiveDataError),
+        }
+    }
+    /// Drains the pending values without blocking and returns the last one.
+    pub fn recv_latest(&mut self) -> Option<T> {
+        self.receiver.try_iter().last()
+    }
 }
 impl<T: 'static + Send + Any + Clone> ConnectorTrait for Input<T> {
     fn as_any(&self) -> &dyn Any {self}
     fn as_any_mut(&mut self) -> &mut dyn Any {self}
     fn get_header(&self) -> &DataHeader {&self.header}
+    fn forward(&mut self, output: &dyn ConnectorTrait) -> Result<(), StreamErrCode> {
+        match output.as_any().downcast_ref::<Output<T>>() {
+            Some(output) => {
+                let data = self.recv()?;
+                output.send(data)
+            }
+            None => Err(StreamErrCode::WrongType),
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -58,7 +90,7 @@ pub struct Output<T: 'static + Send + Clone> {
 impl<T: 'static + Send + Any + Clone> Output<T> {
     pub fn new(name: &'static str) -> Self {
         Self {
-            header: DataHeader{name},
+            header: DataHeader{name, type_name: std::any::type_name::<T>()},
             senders: Vec::new(),
         }
     }
@@ -81,6 +113,15 @@ impl<T: 'static + Send + Any + Clone> ConnectorTrait for Output<T> {
     fn as_any(&self) -> &dyn Any {self}
     fn as_any_mut(&mut self) -> &mut dyn Any {self}
     fn get_header(&self) -> &DataHeader {&self.header}
+    fn connect_input(&mut self, input: &dyn ConnectorTrait) -> Result<(), StreamErrCode> {
+        match input.as_any().downcast_ref::<Input<T>>() {
+            Some(input) => {
+                self.connect(input.sender.clone());
+                Ok(())
+            }
+            None => Err(StreamErrCode::WrongType),
+        }
+    }
 }
 
 
@@ -117,4 +158,27 @@ mod test {
         assert_eq!(recv, 2);
 
     }
+    #[test]
+    fn test_forward() {
+        let mut test_input = Input::<u32>::new("test_input");
+        let mut test_output = Output::<u32>::new("test_output");
+        let mut dest_input = Input::<u32>::new("dest_input");
+        test_output.connect(dest_input.sender.clone());
+        test_input.send(4).unwrap();
+        test_input.forward(&test_output).unwrap();
+        assert_eq!(dest_input.recv().unwrap(), 4);
+        let wrong_output = Output::<f32>::new("wrong_output");
+        assert_eq!(test_input.forward(&wrong_output), Err(StreamErrCode::WrongType));
+        assert_eq!(test_output.clone().forward(&wrong_output), Err(StreamErrCode::InvalidOperation));
+    }
+    #[test]
+    fn test_connect_input() {
+        let mut test_output = Output::<u32>::new("test_output");
+        let mut dest_input = Input::<u32>::new("dest_input");
+        test_output.connect_input(&dest_input).unwrap();
+        test_output.send(7).unwrap();
+        assert_eq!(dest_input.recv().unwrap(), 7);
+        assert_eq!(test_output.connect_input(&Input::<f32>::new("wrong_input")), Err(StreamErrCode::WrongType));
+        assert_eq!(dest_input.connect_input(&Input::<u32>::new("other_input")), Err(StreamErrCode::InvalidOperation));
+    }
 }
\ No newline at end of file
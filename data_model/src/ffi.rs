@@ -1,4 +1,6 @@
-use std::{ffi::*, mem};
+use std::collections::HashMap;
+use std::ffi::*;
+use std::sync::Arc;
 use crate::{modules::{ModuleStruct,ModuleStructFFI}, streaming_data::StreamErrCode};
 use libloading::{Library, Symbol};
 
@@ -10,27 +12,33 @@ pub struct FfiStrSlice {
     pub len: usize,
 }
 
-#[repr(C)]
+/// A loaded module. The library stays loaded as long as any clone of its
+/// handle, or of `lib`, is alive. The constructor is only reachable through
+/// `create_processor`, so it cannot outlive the library.
 #[derive(Clone)]
-pub struct ModuleHandle<'a> {
+pub struct ModuleHandle {
     pub module: ModuleStruct,
-    pub lib: &'a Library,
-    pub get_processor_modules: Symbol<'static, unsafe extern "C" fn(*const u8, usize, *const u8, usize) -> TraitObjectRepr>,
+    pub path: String,
+    pub lib: Arc<Library>,
+    create: CreateProcessorFn,
 }
 
-impl ModuleHandle<'static> {
+/// Constructor a module exports as `create_processor`. It takes the
+/// NUL-terminated name of a processor and returns null when the module does
+/// not provide it. Otherwise it returns a thin pointer to a heap-allocated
+/// `Box<dyn StreamProcessor>`, whose ownership moves to the caller; see
+/// `processor_engine::ffi` for both ends of the exchange.
+pub type CreateProcessorFn = unsafe extern "C" fn(name: *const c_char) -> *mut c_void;
+
+impl ModuleHandle {
     pub fn new(library_path: String) -> Result<Self, StreamErrCode> {
-        let library: &'static Library;
-        match unsafe { Library::new(library_path)} {
-            Ok(lib) => {
-                let box_library = Box::leak(Box::new(lib));
-                library = box_library;
-            }
+        let library = match unsafe { Library::new(&library_path)} {
+            Ok(lib) => lib,
             Err(_) => {
                 eprintln!("Unable to find");
                 return Err(StreamErrCode::FileNotFound);
             }
-        }
+        };
         let module_info: Symbol<*mut ModuleStructFFI>;
         match unsafe { library.get(b"MODULE\0") } {
             Ok(module) => {module_info = module;}
@@ -40,63 +48,192 @@ impl ModuleHandle<'static> {
             }
         }
         let module = unsafe{**module_info}.into();
-        let funct_ptr: Symbol<unsafe extern "C" fn(*const u8, usize, *const u8, usize) -> TraitObjectRepr>;
-        match unsafe { library.get(b"get_processor_modules")} {
-            Ok(func) => {funct_ptr = func;}
+        let funct_ptr: CreateProcessorFn;
+        match unsafe { library.get::<CreateProcessorFn>(b"create_processor\0")} {
+            Ok(func) => {funct_ptr = *func;}
             Err(_) => {
                 eprintln!("Unable to find");
                 return Err(StreamErrCode::FileNotFound);
             }
         }
         let handle = Self {
-            lib: library,
+            lib: Arc::new(library),
+            path: library_path,
             module: module,
-            get_processor_modules: funct_ptr,
+            create: funct_ptr,
         };
         Ok(handle)
     }
+    /// Builds a handle on an already loaded library whose constructor is
+    /// `create`.
+    ///
+    /// # Safety
+    /// `create` must be a function of `lib`, or of the host itself.
+    pub unsafe fn from_parts(module: ModuleStruct, path: String, lib: Arc<Library>, create: CreateProcessorFn) -> Self {
+        Self { module, path, lib, create }
+    }
+    /// Calls the constructor of the module for the processor `name`; see
+    /// `CreateProcessorFn` for the returned pointer. The processor must not
+    /// outlive `lib`.
+    pub fn create_processor(&self, name: &CStr) -> *mut c_void {
+        unsafe { (self.create)(name.as_ptr()) }
+    }
 }
 
-// Representazione C-compatible of trait object
-#[repr(C)]
-pub struct TraitObjectRepr {
-    pub data: *mut c_void,
-    pub vtable: *mut c_void,
-}
-pub fn get_error_return(code: i32) -> TraitObjectRepr {
-    TraitObjectRepr {
-        data: code as *mut c_void,
-        vtable: std::ptr::null_mut(),
+/// Order in which the `new` modules can be loaded so that each one comes after
+/// its dependencies, which may also be satisfied by the `loaded` modules.
+/// Fails with `FileNotFound` on a missing dependency, `WrongType` on an
+/// incompatible version and `InvalidOperation` on a dependency cycle.
+pub fn dependency_order(loaded: &[&ModuleStruct], new: &[&ModuleStruct]) -> Result<Vec<usize>, StreamErrCode> {
+    let mut edges: Vec<Vec<usize>> = Vec::with_capacity(new.len());
+    for module in new {
+        let mut providers = Vec::new();
+        for dependency in &module.dependencies {
+            let (provider, index) = match loaded.iter().find(|m| m.provides(&dependency.dep_name)) {
+                Some(provider) => (*provider, None),
+                None => match new.iter().position(|m| m.provides(&dependency.dep_name)) {
+                    Some(index) => (new[index], Some(index)),
+                    None => {
+                        eprintln!("Module {} requires {}, which is not loaded", module.name, dependency);
+                        return Err(StreamErrCode::FileNotFound);
+                    }
+                },
+            };
+            if !provider.version.is_compatible_with(&dependency.version) {
+                eprintln!("Module {} requires {}, found {}.{}.{}", module.name, dependency,
+                          provider.version.major, provider.version.minor, provider.version.build);
+                return Err(StreamErrCode::WrongType);
+            }
+            providers.extend(index);
+        }
+        edges.push(providers);
     }
-}
-// Funzione FFI per creare e restituire l'oggetto
-/*pub fn export_stream_processor(proc: Box<dyn StreamProcessor>) -> TraitObjectRepr {
-    
-    let ptr_fat: *mut dyn StreamProcessor = Box::into_raw(proc);
-    
-    unsafe {
-        mem::transmute(ptr_fat)
+    let mut placed = vec![false; new.len()];
+    let mut order = Vec::with_capacity(new.len());
+    while order.len() < new.len() {
+        let next = (0..new.len())
+            .find(|&i| !placed[i] && edges[i].iter().all(|&p| placed[p] || p == i));
+        match next {
+            Some(i) => {
+                placed[i] = true;
+                order.push(i);
+            }
+            None => {
+                eprintln!("Dependency cycle between modules");
+                return Err(StreamErrCode::InvalidOperation);
+            }
+        }
     }
+    Ok(order)
 }
 
-// Funzione FFI per usare l'oggetto
-pub fn import_stream_processor(repr: TraitObjectRepr) -> Box<dyn StreamProcessor> {
-    // SAFETY: Dobbiamo essere in un blocco unsafe per riconvertire la repr 
-    // nel puntatore a trait object originale tramite transmute.
-    unsafe {
-        let trait_heap_pointer: *mut dyn StreamProcessor = mem::transmute(repr);
-        Box::from_raw(trait_heap_pointer) 
-        
+/// Loaded modules by library path, each one accepted only once its
+/// dependencies are loaded at a compatible version.
+#[derive(Default)]
+pub struct ModuleRegistry {
+    modules: HashMap<String, ModuleHandle>,
+}
+
+impl ModuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Loads the libraries at `library_paths` together, so that they may
+    /// depend on each other. Nothing is registered if any dependency fails.
+    pub fn load(&mut self, library_paths: Vec<String>) -> Result<(), StreamErrCode> {
+        let handles = library_paths.into_iter()
+            .map(ModuleHandle::new)
+            .collect::<Result<Vec<_>, _>>()?;
+        self.register(handles)
+    }
+    fn register(&mut self, handles: Vec<ModuleHandle>) -> Result<(), StreamErrCode> {
+        for handle in &handles {
+            if self.modules.contains_key(&handle.path) || self.get(&handle.module.name).is_some() {
+                return Err(StreamErrCode::AlreadyDefined);
+            }
+        }
+        let loaded: Vec<&ModuleStruct> = self.modules.values().map(|h| &h.module).collect();
+        let new: Vec<&ModuleStruct> = handles.iter().map(|h| &h.module).collect();
+        dependency_order(&loaded, &new)?;
+        for handle in handles {
+            self.modules.insert(handle.path.clone(), handle);
+        }
+        Ok(())
+    }
+    pub fn get(&self, name: &str) -> Option<&ModuleHandle> {
+        self.modules.values().find(|h| h.module.name == name)
+    }
+    pub fn module_names(&self) -> Vec<&str> {
+        self.modules.values().map(|h| h.module.name.as_str()).collect()
+    }
+    /// Checks that module `name` is loaded and that no other loaded module
+    /// depends on it.
+    pub fn can_unload(&self, name: &str) -> Result<(), StreamErrCode> {
+        let module = &self.get(name).ok_or(StreamErrCode::FileNotFound)?.module;
+        for other in self.modules.values().filter(|h| h.module.name != name) {
+            if other.module.dependencies.iter().any(|d| module.provides(&d.dep_name)) {
+                eprintln!("Module {} is required by {}", name, other.module.name);
+                return Err(StreamErrCode::InvalidOperation);
+            }
+        }
+        Ok(())
+    }
+    /// Unloads module `name`. The library itself is closed once the last
+    /// clone of its handle is dropped and the processors created through
+    /// `processor_engine::ffi::create_module_processor`, which hold it as
+    /// their code and vtables live there, are gone.
+    pub fn unload(&mut self, name: &str) -> Result<(), StreamErrCode> {
+        self.can_unload(name)?;
+        self.modules.retain(|_, h| h.module.name != name);
+        Ok(())
     }
 }
 
-pub fn free_object(repr: TraitObjectRepr) {
-    // SAFETY: il chiamante deve garantire che la repr sia valida e non ancora liberata.
-    unsafe {
-        // Riconvertire la repr in un puntatore a trait object originale
-        let trait_heap_pointer: *mut dyn StreamProcessor = mem::transmute(repr);
-        
-        // Box::from_raw riprende la proprietà del Box originale e lo dealloca
-        let _boxed_trait: Box<dyn StreamProcessor> = Box::from_raw(trait_heap_pointer);
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::modules::{Dependency, Version};
+
+    fn module(name: &str, major: u32, minor: u32, dependencies: &[(&str, u32, u32)]) -> ModuleStruct {
+        ModuleStruct {
+            name: name.to_string(),
+            description: String::new(),
+            authors: String::new(),
+            release_date: String::new(),
+            version: Version { major, minor, build: 0 },
+            dependencies: dependencies.iter().map(|(dep_name, major, minor)| Dependency {
+                dep_name: dep_name.to_string(),
+                version: Version { major: *major, minor: *minor, build: 0 },
+            }).collect(),
+            provides: vec![format!("{}_blocks", name)],
+        }
     }
-}*/
\ No newline at end of file
+
+    #[test]
+    fn test_dependency_order() {
+        let core = module("core", 1, 2, &[]);
+        let filters = module("filters", 2, 0, &[("core", 1, 1)]);
+        let radar = module("radar", 1, 0, &[("filters_blocks", 2, 0), ("core", 1, 0)]);
+        assert_eq!(dependency_order(&[], &[&radar, &filters, &core]), Ok(vec![2, 1, 0]));
+        // Dependencies already loaded need not be loaded again.
+        assert_eq!(dependency_order(&[&core], &[&radar, &filters]), Ok(vec![1, 0]));
+
+        assert_eq!(dependency_order(&[], &[&radar, &core]), Err(StreamErrCode::FileNotFound));
+        let newer_core = module("filters", 2, 0, &[("core", 1, 3)]);
+        assert_eq!(dependency_order(&[&core], &[&newer_core]), Err(StreamErrCode::WrongType));
+        let next_major = module("filters", 2, 0, &[("core", 2, 0)]);
+        assert_eq!(dependency_order(&[&core], &[&next_major]), Err(StreamErrCode::WrongType));
+
+        let a = module("a", 1, 0, &[("b", 1, 0)]);
+        let b = module("b", 1, 0, &[("a", 1, 0)]);
+        assert_eq!(dependency_order(&[], &[&a, &b]), Err(StreamErrCode::InvalidOperation));
+    }
+
+    #[test]
+    fn test_registry_missing_library() {
+        let mut registry = ModuleRegistry::new();
+        assert_eq!(registry.load(vec!["/nonexistent/libmodule.so".to_string()]), Err(StreamErrCode::FileNotFound));
+        assert!(registry.module_names().is_empty());
+        assert_eq!(registry.unload("missing"), Err(StreamErrCode::FileNotFound));
+    }
+}
@@ -1,57 +1,79 @@
 use std::collections::HashMap;
 use std::any::Any;
-use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
 use std::fmt::Debug;
 
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use memory_var_macro::MemoryVarMacro;
-use crate::streaming_data::StreamErrCode;
+use crate::streaming_data::{StreamErrCode, StreamingError};
 
 // General traits for Statics, States and Parameters
 #[derive(Debug, Clone, Copy)]
 pub struct DataHeader {
     pub name: &'static str,
+    /// Name of the carried type, as given by `std::any::type_name`.
+    pub type_name: &'static str,
 }
 
 pub trait DataTrait : Send + Sync {
     fn as_any(&self) -> &dyn Any;
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn get_header(&self) -> &DataHeader;
+    /// Returns the current value as a JSON value.
+    fn to_json(&self) -> serde_json::Value;
+    /// Sets the value from a JSON value, honouring the limits if any.
+    fn load_json(&mut self, value: &serde_json::Value) -> Result<(), StreamErrCode>;
     fn serialize(&self) -> String;
 }
 
 pub trait StaticsTrait : Send + Sync + DataTrait {
     fn is_settable(&self) -> bool;
+    /// Allows the value to be set once more.
+    fn unlock(&mut self);
+    /// Restores the value given at construction and makes it settable again.
+    fn reset_to_default(&mut self) -> Result<(), StreamErrCode>;
 }
 
 #[derive(MemoryVarMacro, Clone)]
 pub struct Statics<T: 'static + Sync + Send + Debug> {
     pub header: DataHeader,
     value: T,
+    default: T,
     limits: Option<[T; 2]>,
     settable: bool,
     lock: Arc<Mutex<()>>,
 }
 
 impl<T> Statics<T> 
-where T: 'static + Sync + Send + PartialOrd + PartialEq + Debug + Clone
+where T: 'static + Sync + Send + PartialOrd + PartialEq + Debug + Clone + Serialize + DeserializeOwned
 {
-    pub fn new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
-        let mm= MemoryManager::get_memory_manager();
-        let res = Self {
-            header: DataHeader{name},
+    fn create(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
+        Self {
+            header: DataHeader{name, type_name: std::any::type_name::<T>()},
+            default: value.clone(),
             value,
             limits,
             settable: true,
             lock: Arc::new(Mutex::new(())),
-        };
-        match mm {
-            Ok(mut mgr) => {
-                let _ = mgr.get_memory_current_mode().unwrap().register_statics(name, Box::new(res.clone()));
-            }
-            Err(_) => {}
         }
-        res
+    }
+    /// Creates the statics and registers it in the current memory mode.
+    pub fn try_new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Result<Self, StreamErrCode> {
+        let res = Self::create(name, value, limits);
+        if let Some(mode) = MemoryManager::get_memory_manager()?.get_memory_current_mode() {
+            mode.register_statics(name, Box::new(res.clone()))?;
+        }
+        Ok(res)
+    }
+    /// Same as `try_new`, logging a failed registration.
+    pub fn new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
+        Self::try_new(name, value.clone(), limits.clone()).unwrap_or_else(|e| {
+            eprintln!("Unable to register statics {}: {}", name, e);
+            Self::create(name, value, limits)
+        })
     }
     pub fn set_value(&mut self, value: T) -> Result<(), StreamErrCode> {
         let _locked = self.lock.lock().unwrap();
@@ -60,7 +82,9 @@ where T: 'static + Sync + Send + PartialOrd + PartialEq + Debug + Clone
             let mm= MemoryManager::get_memory_manager();
             match mm {
                 Ok(mut mgr) => {
-                    mgr.get_memory_current_mode().unwrap().update_statics(self.header.name, Box::new(self.clone()));
+                    if let Some(mode) = mgr.get_memory_current_mode() {
+                        mode.update_statics(self.header.name, Box::new(self.clone()));
+                    }
                 }
                 Err(e) => {
                     return Err(e);
@@ -78,11 +102,25 @@ where T: 'static + Sync + Send + PartialOrd + PartialEq + Debug + Clone
 }
 
 impl<T> StaticsTrait for Statics<T> 
-where T: 'static + Sync + Send + Debug
+where T: 'static + Sync + Send + Debug + Clone + PartialOrd + Serialize + DeserializeOwned
 {
     fn is_settable(&self) -> bool {
         self.settable
     }
+    fn unlock(&mut self) {
+        let _locked = self.lock.lock().unwrap();
+        self.settable = true;
+    }
+    fn reset_to_default(&mut self) -> Result<(), StreamErrCode> {
+        let _locked = self.lock.lock().unwrap();
+        self.value = self.default.clone();
+        self.settable = true;
+        let mut mgr = MemoryManager::get_memory_manager()?;
+        if let Some(mode) = mgr.get_memory_current_mode() {
+            mode.update_statics(self.header.name, Box::new(self.clone()));
+        }
+        Ok(())
+    }
 }
 
 #[derive(MemoryVarMacro)]
@@ -90,26 +128,36 @@ pub struct State<T: 'static +Send + Sync + Debug> {
     pub header: DataHeader,
     value: T,
     senders: Vec<SyncSender<T>>,
+    /// Updates lost because a connected channel was full.
+    dropped: Arc<AtomicU64>,
     lock: Arc<Mutex<()>>,
 }
 
-impl<T> State<T> where T: 'static + Send + Sync + Clone + PartialOrd + PartialEq + Debug
+impl<T> State<T> where T: 'static + Send + Sync + Clone + PartialOrd + PartialEq + Debug + Serialize + DeserializeOwned
 {
-    pub fn new(name: &'static str, value: T) -> Self {
-        let mm= MemoryManager::get_memory_manager();
-        let res = Self {
-            header: DataHeader{name},
+    fn create(name: &'static str, value: T) -> Self {
+        Self {
+            header: DataHeader{name, type_name: std::any::type_name::<T>()},
             value,
             senders: Vec::new(),
+            dropped: Arc::new(AtomicU64::new(0)),
             lock: Arc::new(Mutex::new(())),
-        };
-        match mm {
-            Ok(mut mgr) => {
-                mgr.get_memory_current_mode().unwrap().register_state(name, Box::new(res.clone())).unwrap();
-            }
-            Err(_) => {}
         }
-        res
+    }
+    /// Creates the state and registers it in the current memory mode.
+    pub fn try_new(name: &'static str, value: T) -> Result<Self, StreamErrCode> {
+        let res = Self::create(name, value);
+        if let Some(mode) = MemoryManager::get_memory_manager()?.get_memory_current_mode() {
+            mode.register_state(name, Box::new(res.clone()))?;
+        }
+        Ok(res)
+    }
+    /// Same as `try_new`, logging a failed registration.
+    pub fn new(name: &'static str, value: T) -> Self {
+        Self::try_new(name, value.clone()).unwrap_or_else(|e| {
+            eprintln!("Unable to register state {}: {}", name, e);
+            Self::create(name, value)
+        })
     }
     pub fn set_value(& mut self, value: T) -> Result<(), StreamErrCode> {
         let _locked = self.lock.lock().unwrap();
@@ -117,7 +165,9 @@ impl<T> State<T> where T: 'static + Send + Sync + Clone + PartialOrd + PartialEq
         let mm= MemoryManager::get_memory_manager();
         match mm {
             Ok(mut mgr) => {
-                mgr.get_memory_current_mode().unwrap().update_state(self.header.name, Box::new(self.clone()));
+                if let Some(mode) = mgr.get_memory_current_mode() {
+                    mode.update_state(self.header.name, Box::new(self.clone()));
+                }
             }
             Err(e) => {
                 return Err(e);
@@ -132,18 +182,26 @@ impl<T> State<T> where T: 'static + Send + Sync + Clone + PartialOrd + PartialEq
     pub fn connect(&mut self, sender: SyncSender<T>) {
         self.senders.push(sender);
     }
+    /// Sends the value to the connected channels without blocking: a full
+    /// channel loses the update, which is counted in `dropped_count`.
     pub fn send(&self) {
         for s in &self.senders {
-            let _ = s.send(self.value.clone());
+            if let Err(TrySendError::Full(_)) = s.try_send(self.value.clone()) {
+                self.dropped.fetch_add(1, Ordering::Relaxed);
+            }
         }
     }
+    pub fn dropped_count(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
 }
 impl<T> Clone for State<T> where T: 'static + Send + Sync + Clone + Debug{
     fn clone(&self) -> Self {
         Self {
-            header: DataHeader{name: self.header.name},
+            header: self.header,
             value: self.value.clone(),
             senders: self.senders.clone(),
+            dropped: self.dropped.clone(),
             lock: self.lock.clone(),
         }
     }
@@ -158,24 +216,31 @@ pub struct Parameter<T:'static + Send + Sync + Clone + Debug> {
     lock: Arc<Mutex<()>>,
 }
 
-impl<T> Parameter<T> where T:'static +  Send + Sync + Clone + PartialOrd + Debug{
-    pub fn new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
+impl<T> Parameter<T> where T:'static +  Send + Sync + Clone + PartialOrd + Debug + Serialize + DeserializeOwned{
+    fn create(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
         let default = value.clone();
-        let res = Self {
-            header: DataHeader{name},
+        Self {
+            header: DataHeader{name, type_name: std::any::type_name::<T>()},
             value: value,
             default: default,
             limits: limits,
             lock: Arc::new(Mutex::new(())),
-        };
-        let mm= MemoryManager::get_memory_manager();
-        match mm {
-            Ok(mut mgr) => {
-                mgr.get_memory_current_mode().unwrap().register_parameters(name, Box::new(res.clone())).unwrap();
-            }
-            Err(_) => {}
         }
-        res
+    }
+    /// Creates the parameter and registers it in the current memory mode.
+    pub fn try_new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Result<Self, StreamErrCode> {
+        let res = Self::create(name, value, limits);
+        if let Some(mode) = MemoryManager::get_memory_manager()?.get_memory_current_mode() {
+            mode.register_parameters(name, Box::new(res.clone()))?;
+        }
+        Ok(res)
+    }
+    /// Same as `try_new`, logging a failed registration.
+    pub fn new(name: &'static str, value: T, limits: Option<[T; 2]>) -> Self {
+        Self::try_new(name, value.clone(), limits.clone()).unwrap_or_else(|e| {
+            eprintln!("Unable to register parameter {}: {}", name, e);
+            Self::create(name, value, limits)
+        })
     }
 
     pub fn get_value(&self) -> T {
@@ -183,10 +248,16 @@ impl<T> Parameter<T> where T:'static +  Send + Sync + Clone + PartialOrd + Debug
         self.value.clone()
 
     }
-    pub fn set_value(&mut self, value: T) -> Result<(), StreamErrCode> {
+    /// Sets the value, telling which bound is exceeded if out of the limits.
+    pub fn set_value(&mut self, value: T) -> Result<(), StreamingError> {
         if let Some(limits) = &self.limits {
-            if value < limits[0] || value > limits[1] {
-                return Err(StreamErrCode::OutOfRange);
+            if value < limits[0] {
+                return Err(StreamingError::new(StreamErrCode::OutOfRange,
+                    &format!("value {:?} is below min {:?} for key {}", value, limits[0], self.header.name)));
+            }
+            if value > limits[1] {
+                return Err(StreamingError::new(StreamErrCode::OutOfRange,
+                    &format!("value {:?} exceeds max {:?} for key {}", value, limits[1], self.header.name)));
             }
         }
         let _locked = self.lock.lock().unwrap();
@@ -194,10 +265,12 @@ impl<T> Parameter<T> where T:'static +  Send + Sync + Clone + PartialOrd + Debug
         let mm= MemoryManager::get_memory_manager();
         match mm {
             Ok(mut mgr) => {
-                mgr.get_memory_current_mode().unwrap().update_parameters(self.header.name, Box::new(self.clone()));
+                if let Some(mode) = mgr.get_memory_current_mode() {
+                    mode.update_parameters(self.header.name, Box::new(self.clone()));
+                }
             }
             Err(e) => {
-                return Err(e);
+                return Err(StreamingError::new(e, "Memory manager not available"));
             }
         }
         Ok(())
@@ -207,7 +280,7 @@ impl<T> Parameter<T> where T:'static +  Send + Sync + Clone + PartialOrd + Debug
 impl<T> Clone for Parameter<T> where T: Send + Sync + Clone + Debug {
     fn clone(&self) -> Self {
         Self {
-            header: DataHeader{name: self.header.name},
+            header: self.header,
             value: self.value.clone(),
             default: self.default.clone(),
             limits: self.limits.clone(),
@@ -218,7 +291,8 @@ impl<T> Clone for Parameter<T> where T: Send + Sync + Clone + Debug {
 pub struct MemoryMode {
     mapped_state:       HashMap<&'static str, Box<dyn DataTrait>>,
     mapped_statics:     HashMap<&'static str, Box<dyn DataTrait>>,
-    mapped_parameters:  HashMap<&'static str, Box<dyn DataTrait>>
+    mapped_parameters:  HashMap<&'static str, Box<dyn DataTrait>>,
+    subscribers:        HashMap<&'static str, Vec<SyncSender<String>>>,
 }
 
 impl MemoryMode {
@@ -227,6 +301,7 @@ impl MemoryMode {
             mapped_state: HashMap::new(),
             mapped_statics: HashMap::new(),
             mapped_parameters: HashMap::new(),
+            subscribers: HashMap::new(),
         }
     }
     pub fn register_state(&mut self, key: &'static str, state: Box<dyn DataTrait>) -> Result<(), StreamErrCode> {
@@ -250,36 +325,82 @@ impl MemoryMode {
         self.mapped_parameters.insert(key, param);
         Ok(())
     }
+    /// Removes a parameter no longer defined by its block.
+    pub fn remove_parameters(&mut self, key: &str) -> Option<Box<dyn DataTrait>> {
+        self.mapped_parameters.remove(key)
+    }
+    /// Returns a receiver getting the JSON serialized value of `key` each
+    /// time the variable is updated.
+    pub fn subscribe(&mut self, key: &'static str) -> Receiver<String> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(50);
+        self.subscribers.entry(key).or_default().push(sender);
+        receiver
+    }
+    fn notify(&mut self, key: &'static str, value: &dyn DataTrait) {
+        if let Some(senders) = self.subscribers.get_mut(key) {
+            let serialized = value.to_json().to_string();
+            // Slow subscribers lose updates rather than blocking the writer; closed ones are removed.
+            senders.retain(|s| !matches!(s.try_send(serialized.clone()), Err(TrySendError::Disconnected(_))));
+        }
+    }
     pub fn update_state(&mut self, key: &'static str, state: Box<dyn DataTrait>) {
+        self.notify(key, state.as_ref());
         self.mapped_state.insert(key, state);
     }
     pub fn update_statics(&mut self, key: &'static str, statics: Box<dyn DataTrait>) {
+        self.notify(key, statics.as_ref());
         self.mapped_statics.insert(key, statics);
     }
     pub fn update_parameters(&mut self, key: &'static str, param: Box<dyn DataTrait>) {
+        self.notify(key, param.as_ref());
         self.mapped_parameters.insert(key, param);
     }
-    pub fn serialize_all(&self) -> String {
-        let mut result = String::new();
-        result.push_str("{\"memory_mapped\":");
-        result.push_str("{\"state\": {");
-        for (_, val) in &self.mapped_state {
-            result.push_str(&format!("{}", val.serialize()));
+    fn map_to_json(map: &HashMap<&'static str, Box<dyn DataTrait>>) -> serde_json::Value {
+        map.iter()
+            .map(|(key, val)| (key.to_string(), val.to_json()))
+            .collect::<serde_json::Map<String, serde_json::Value>>()
+            .into()
+    }
+    /// Restores the values exported by `serialize_all`.
+    pub fn load_from_json(&mut self, json: &str) -> Result<(), StreamErrCode> {
+        let document: serde_json::Value = serde_json::from_str(json)
+            .map_err(|_| StreamErrCode::InvalidInput)?;
+        let mapped = &document["memory_mapped"];
+        if !mapped.is_object() {
+            return Err(StreamErrCode::InvalidInput);
         }
-
-        result.push_str("}");
-        result.push_str(",{\"statics\": {");
-        for (_, val) in &self.mapped_statics {
-            result.push_str(&format!("{}", val.serialize()));
+        // Variables not registered in this mode are skipped.
+        for (name, value) in mapped["state"].as_object().into_iter().flatten() {
+            if let Some((key, mut state)) = self.mapped_state.remove_entry(name.as_str()) {
+                let res = state.load_json(value);
+                self.update_state(key, state);
+                res?;
+            }
         }
-        result.push_str("}");
-        result.push_str(",{\"parameters\": {");
-        for (_, val) in &self.mapped_parameters {
-            result.push_str(&format!("{}", val.serialize()));
+        for (name, value) in mapped["statics"].as_object().into_iter().flatten() {
+            if let Some((key, mut statics)) = self.mapped_statics.remove_entry(name.as_str()) {
+                let res = statics.load_json(value);
+                self.update_statics(key, statics);
+                res?;
+            }
+        }
+        for (name, value) in mapped["parameters"].as_object().into_iter().flatten() {
+            if let Some((key, mut param)) = self.mapped_parameters.remove_entry(name.as_str()) {
+                let res = param.load_json(value);
+                self.update_parameters(key, param);
+                res?;
+            }
         }
-        result.push_str("}}");
-        result
-        
+        Ok(())
+    }
+    pub fn serialize_all(&self) -> String {
+        serde_json::json!({
+            "memory_mapped": {
+                "state": Self::map_to_json(&self.mapped_state),
+                "statics": Self::map_to_json(&self.mapped_statics),
+                "parameters": Self::map_to_json(&self.mapped_parameters),
+            }
+        }).to_string()
     }
 }
 
@@ -317,6 +438,13 @@ impl MemoryManager {
     pub fn get_memory_mode(&mut self, index: usize) -> Option<&mut MemoryMode> {
         self.memory_modes.get_mut(&index)
     }
+    /// Restores the values of a mode from a document produced by `serialize_all`.
+    pub fn restore_mode(&mut self, index: usize, json: &str) -> Result<(), StreamErrCode> {
+        match self.memory_modes.get_mut(&index) {
+            Some(mode) => mode.load_from_json(json),
+            None => Err(StreamErrCode::InvalidInput),
+        }
+    }
 }
 
 pub static MEMORY_MANAGER: OnceLock<Mutex<MemoryManager>> = OnceLock::new();
@@ -336,7 +464,7 @@ mod tests {
     }
     #[test]
     fn test_static_variable() {
-        let mut statics = Statics::new("test_statics", 10);
+        let mut statics = Statics::new("test_statics", 10, None);
         assert_eq!(statics.get_value(), 10);
         statics.set_value(20).unwrap();
         assert_eq!(statics.get_value(), 20);
@@ -344,6 +472,21 @@ mod tests {
         assert!(result.is_err());
     }
     #[test]
+    fn test_statics_unlock() {
+        let mut statics = Statics::new("test_statics_unlock", 10, None);
+        statics.set_value(20).unwrap();
+        assert!(statics.set_value(30).is_err());
+        statics.unlock();
+        assert!(statics.is_settable());
+        statics.set_value(30).unwrap();
+        assert_eq!(statics.get_value(), 30);
+        assert!(!statics.is_settable());
+        statics.reset_to_default().unwrap();
+        assert_eq!(statics.get_value(), 10);
+        statics.set_value(40).unwrap();
+        assert_eq!(statics.get_value(), 40);
+    }
+    #[test]
     fn test_state_variable() {
         let mut state = State::new("test_state", 10);
         assert_eq!(state.get_value(), 10);
@@ -353,26 +496,66 @@ mod tests {
         assert!(result.is_ok());
     }
     #[test]
+    fn test_state_send_full() {
+        let mut state = State::new("test_state_send", 1);
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<i32>(1);
+        state.connect(sender);
+        state.send();
+        // The channel is full: the second update is dropped instead of blocking.
+        state.set_value(2).unwrap();
+        state.send();
+        assert_eq!(state.dropped_count(), 1);
+        assert_eq!(receiver.try_iter().collect::<Vec<i32>>(), vec![1]);
+    }
+    #[test]
     fn test_parameter_variable() {
         let mut param = Parameter::new("test_param", 10, Some([10, 20]));
         assert_eq!(param.get_value(), 10);
         param.set_value(20).unwrap();
         assert_eq!(param.get_value(), 20);
         let result = param.set_value(30);
-        assert!(result.is_err());
+        assert_eq!(result.unwrap_err().message, "value 30 exceeds max 20 for key test_param");
+        assert_eq!(param.set_value(5).unwrap_err().message, "value 5 is below min 10 for key test_param");
         assert_eq!(param.get_value(), 20);
     }
     #[test]
+    fn test_register_duplicate() {
+        {
+            let mut mm = MemoryManager::get_memory_manager().unwrap();
+            if mm.get_memory_mode(0).is_none() {
+                mm.add_mode(0);
+            }
+            mm.set_mode(0);
+        }
+        assert!(State::try_new("test_state_dup", 1).is_ok());
+        assert_eq!(State::try_new("test_state_dup", 2).err(), Some(StreamErrCode::AlreadyDefined));
+        assert!(Parameter::try_new("test_param_dup", 1, None).is_ok());
+        assert_eq!(Parameter::try_new("test_param_dup", 2, None).err(), Some(StreamErrCode::AlreadyDefined));
+        assert!(Statics::try_new("test_statics_dup", 1, None).is_ok());
+        assert_eq!(Statics::try_new("test_statics_dup", 2, None).err(), Some(StreamErrCode::AlreadyDefined));
+        // The registered value is kept.
+        let mut mm = MemoryManager::get_memory_manager().unwrap();
+        let json: serde_json::Value = serde_json::from_str(&mm.get_memory_current_mode().unwrap().serialize_all()).unwrap();
+        assert_eq!(json["memory_mapped"]["state"]["test_state_dup"], 1);
+    }
+    #[test]
     fn test_memory_manager_serialization() {
         use std::io::Write;
         use std::fs;
         use std::path::Path;
 
-        let _ = Statics::new("test_statics_reg", 10);
+        {
+            let mut mm = MemoryManager::get_memory_manager().unwrap();
+            if mm.get_memory_mode(0).is_none() {
+                mm.add_mode(0);
+            }
+            mm.set_mode(0);
+        }
+        let _ = Statics::new("test_statics_reg", 10, None);
         let _ = State::new("test_state_reg", 20);
         let _ = Parameter::new("test_param_reg", 15, Some([10, 20]));
-        let mm = MemoryManager::get_memory_manager().unwrap();
-        let serialized = mm.serialize_all();
+        let mut mm = MemoryManager::get_memory_manager().unwrap();
+        let serialized = mm.get_memory_current_mode().unwrap().serialize_all();
         assert!(serialized.contains("\"test_statics_reg\""));
         assert!(serialized.contains("\"test_state_reg\""));
         assert!(serialized.contains("\"test_param_reg\""));
@@ -380,6 +563,61 @@ mod tests {
         let mut file = fs::File::create(&path).unwrap();
         file.write_all(serialized.as_bytes()).unwrap();
         let json_result = serde_json::from_str::<serde_json::Value>(&serialized);
-        //assert!(json_result.is_ok());
+        assert!(json_result.is_ok());
+        let json = json_result.unwrap();
+        assert_eq!(json["memory_mapped"]["statics"]["test_statics_reg"], 10);
+        assert_eq!(json["memory_mapped"]["state"]["test_state_reg"], 20);
+        assert_eq!(json["memory_mapped"]["parameters"]["test_param_reg"], 15);
+        fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn test_memory_mode_subscribe() {
+        let receiver = {
+            let mut mm = MemoryManager::get_memory_manager().unwrap();
+            if mm.get_memory_mode(0).is_none() {
+                mm.add_mode(0);
+            }
+            mm.set_mode(0);
+            mm.get_memory_current_mode().unwrap().subscribe("test_param_sub")
+        };
+        let mut param = Parameter::new("test_param_sub", 15, Some([10, 20]));
+        param.set_value(12).unwrap();
+        assert_eq!(receiver.try_recv().unwrap(), "12");
+        assert!(param.set_value(30).is_err());
+        assert!(receiver.try_recv().is_err());
+        drop(receiver);
+        param.set_value(13).unwrap();
+        let mut mm = MemoryManager::get_memory_manager().unwrap();
+        assert!(mm.get_memory_current_mode().unwrap().subscribers["test_param_sub"].is_empty());
+    }
+    #[test]
+    fn test_memory_mode_restore() {
+        let mut mode = MemoryMode::new();
+        mode.register_state("restore_state", Box::new(State::new("restore_state", 1.5))).unwrap();
+        mode.register_statics("restore_statics", Box::new(Statics::new("restore_statics", "a".to_string(), None))).unwrap();
+        mode.register_parameters("restore_param", Box::new(Parameter::new("restore_param", 15, Some([10, 20])))).unwrap();
+        mode.register_parameters("restore_path", Box::new(Parameter::new("restore_path", "./log".to_string(), None))).unwrap();
+        let json = r#"{"memory_mapped": {"state": {"restore_state": 2.5, "unknown": 1},
+            "statics": {"restore_statics": "b"}, "parameters": {"restore_param": 12, "restore_path": "./restored"}}}"#;
+        mode.load_from_json(json).unwrap();
+        let restored: serde_json::Value = serde_json::from_str(&mode.serialize_all()).unwrap();
+        assert_eq!(restored["memory_mapped"]["state"]["restore_state"], 2.5);
+        assert_eq!(restored["memory_mapped"]["statics"]["restore_statics"], "b");
+        assert_eq!(restored["memory_mapped"]["parameters"]["restore_param"], 12);
+        assert_eq!(restored["memory_mapped"]["parameters"]["restore_path"], "./restored");
+        // Statics are set once, from JSON as well.
+        let statics_again = r#"{"memory_mapped": {"statics": {"restore_statics": "c"}}}"#;
+        assert_eq!(mode.load_from_json(statics_again), Err(StreamErrCode::InvalidOperation));
+
+        let out_of_range = r#"{"memory_mapped": {"parameters": {"restore_param": 30}}}"#;
+        assert_eq!(mode.load_from_json(out_of_range), Err(StreamErrCode::OutOfRange));
+        let wrong_type = r#"{"memory_mapped": {"parameters": {"restore_param": "x"}}}"#;
+        assert_eq!(mode.load_from_json(wrong_type), Err(StreamErrCode::WrongType));
+        let restored: serde_json::Value = serde_json::from_str(&mode.serialize_all()).unwrap();
+        assert_eq!(restored["memory_mapped"]["parameters"]["restore_param"], 12);
+        assert_eq!(mode.load_from_json("not json"), Err(StreamErrCode::InvalidInput));
+
+        let mut mm = MemoryManager::get_memory_manager().unwrap();
+        assert_eq!(mm.restore_mode(usize::MAX, json), Err(StreamErrCode::InvalidInput));
     }
 }
\ No newline at end of file
@@ -7,9 +7,9 @@ pub fn connector_macro_derive(input: TokenStream) -> TokenStream {
     let ast = parse_macro_input!(input as DeriveInput);
     let name = &ast.ident; 
     let generics = &ast.generics;
-    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
     
-    let _ = generics.params.iter().next().map(|param| {
+    let value_type = generics.params.iter().next().map(|param| {
         match param {
             syn::GenericParam::Type(type_param) => type_param.ident.clone(),
             _ => panic!("Traits object type shall be generic."),
@@ -17,6 +17,8 @@ pub fn connector_macro_derive(input: TokenStream) -> TokenStream {
     }).unwrap_or_else(|| panic!("Traits object type shall be generic on a type."));
     
     let mut has_header_field = false;
+    let mut has_limits_field = false;
+    let mut has_settable_field = false;
     let fields_names =match &ast.data {
         syn::Data::Struct(data_struct) => {
             for field in &data_struct.fields {
@@ -24,6 +26,12 @@ pub fn connector_macro_derive(input: TokenStream) -> TokenStream {
                     if ident == "header" {
                         has_header_field = true;
                     }
+                    if ident == "limits" {
+                        has_limits_field = true;
+                    }
+                    if ident == "settable" {
+                        has_settable_field = true;
+                    }
                 }
             }
             has_header_field
@@ -39,18 +47,57 @@ pub fn connector_macro_derive(input: TokenStream) -> TokenStream {
         .into();
     }
 
+    // The value must be serializable to be exported by the MemoryManager.
+    let mut where_clause = generics.clone().make_where_clause().clone();
+    where_clause.predicates.push(syn::parse_quote!(#value_type: serde::Serialize + serde::de::DeserializeOwned));
+    let limits_check = if has_limits_field {
+        where_clause.predicates.push(syn::parse_quote!(#value_type: PartialOrd));
+        quote! {
+            if let Some(limits) = &self.limits {
+                if value < limits[0] || value > limits[1] {
+                    return Err(StreamErrCode::OutOfRange);
+                }
+            }
+        }
+    } else {
+        quote! {}
+    };
+
+    // Statics can be set once only, from JSON as well.
+    let (settable_check, settable_lock) = if has_settable_field {
+        (quote! {
+            if !self.settable {
+                return Err(StreamErrCode::InvalidOperation);
+            }
+        }, quote! {
+            self.settable = false;
+        })
+    } else {
+        (quote! {}, quote! {})
+    };
+
     let code_gen = quote! {
         //#fields_types
 
-        impl #impl_generics DataTrait for #name #ty_generics where #where_clause {
+        impl #impl_generics DataTrait for #name #ty_generics #where_clause {
             fn as_any(&self) -> &dyn Any {self}
             fn as_any_mut(&mut self) -> &mut dyn Any {self}
             fn get_header(&self) -> &DataHeader {&self.header}
+            fn to_json(&self) -> serde_json::Value {
+                serde_json::to_value(&self.value).unwrap_or(serde_json::Value::Null)
+            }
+            fn load_json(&mut self, value: &serde_json::Value) -> Result<(), StreamErrCode> {
+                let value: #value_type = serde_json::from_value(value.clone())
+                    .map_err(|_| StreamErrCode::WrongType)?;
+                #limits_check
+                let _locked = self.lock.lock().unwrap();
+                #settable_check
+                self.value = value;
+                #settable_lock
+                Ok(())
+            }
             fn serialize(&self) -> String {
-                let mut result = String::new();
-                result.push_str(&format!("\"name\" : \"{}\",", self.header.name));
-                result.push_str(&format!("\"value\" : \"{:#?}\"}}", self.value));
-                result
+                serde_json::json!({"name": self.header.name, "value": self.to_json()}).to_string()
             }
         }
     };
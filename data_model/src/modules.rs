@@ -30,6 +30,14 @@ pub struct Version {
     pub build: u32,
 }
 
+impl Version {
+    /// Whether a module at this version can stand in for `required`: same
+    /// major version and no older minor/build.
+    pub fn is_compatible_with(&self, required: &Version) -> bool {
+        self.major == required.major && (self.minor, self.build) >= (required.minor, required.build)
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone)]
 pub struct DependencyFFI {
@@ -84,6 +92,14 @@ pub struct ModuleStruct {
     pub provides: Vec<String>,
 }
 
+impl ModuleStruct {
+    /// Whether this module satisfies dependencies on `name`, either its own
+    /// name or one of the names it provides.
+    pub fn provides(&self, name: &str) -> bool {
+        self.name == name || self.provides.iter().any(|p| p == name)
+    }
+}
+
 impl From<ModuleStructFFI> for ModuleStruct {
     fn from(ffi_struct: ModuleStructFFI) -> Self {
         let name = c_char_to_string(ffi_struct.name).unwrap_or_default();
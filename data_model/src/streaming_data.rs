@@ -3,6 +3,7 @@ pub enum StreamingState {
     Null,
     Initial,
     Running,
+    Paused,
     Stopped,
 }
 impl std::fmt::Display for StreamingState {
@@ -41,6 +42,8 @@ impl std::fmt::Display for StreamErrCode {
         write!(f, "{:?}", self)
     }
 }
+/// Error code with a message for the operator, empty if the code says it all.
+#[derive(Debug, PartialEq)]
 pub struct StreamingError {
     pub code: StreamErrCode,
     pub message: String,
@@ -53,8 +56,25 @@ impl StreamingError {
         }
     }
 }
+impl From<StreamErrCode> for StreamingError {
+    fn from(code: StreamErrCode) -> Self {
+        StreamingError {
+            code,
+            message: String::new(),
+        }
+    }
+}
+impl From<StreamingError> for StreamErrCode {
+    fn from(error: StreamingError) -> Self {
+        error.code
+    }
+}
 impl std::fmt::Display for StreamingError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "Error {:?}: {}", self.code, self.message)
+        if self.message.is_empty() {
+            write!(f, "Error {:?}", self.code)
+        } else {
+            write!(f, "Error {:?}: {}", self.code, self.message)
+        }
     }
 }
\ No newline at end of file
@@ -0,0 +1,273 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use processor_engine::engine::ProcessorEngine;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(1);
+const MAX_BODY_SIZE: usize = 65536;
+
+#[derive(Deserialize)]
+struct CommandRequest {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+}
+
+struct HttpRequest {
+    method: String,
+    path: String,
+    body: Vec<u8>,
+}
+
+fn read_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line).ok()?;
+    let mut request_line = line.split_whitespace();
+    let method = request_line.next()?.to_string();
+    let path = request_line.next()?.to_string();
+    let mut content_length = 0;
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).ok()? == 0 {
+            return None;
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':')
+            && name.trim().eq_ignore_ascii_case("content-length") {
+            content_length = value.trim().parse().ok()?;
+        }
+    }
+    if content_length > MAX_BODY_SIZE {
+        return None;
+    }
+    let mut body = vec![0u8; content_length];
+    reader.read_exact(&mut body).ok()?;
+    Some(HttpRequest { method, path, body })
+}
+
+fn write_response(stream: &mut TcpStream, status: &str, body: &Value) -> std::io::Result<()> {
+    let body = body.to_string();
+    write!(stream, "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status, body.len(), body)
+}
+
+fn error_status(error: StreamingError) -> (&'static str, Value) {
+    let status = match error.code {
+        StreamErrCode::InvalidProcessorBlock => "404 Not Found",
+        _ => "400 Bad Request",
+    };
+    (status, json!({"error": error.to_string()}))
+}
+
+/// Serves a request on the processors of the ProcessorEngine:
+/// - `GET /processors`: names and commands of the processors;
+/// - `GET /processors/{name}/params`: parameter values of a processor;
+/// - `POST /processors/{name}/command`: runs `{"command": ..., "args": [...]}`.
+fn route(request: &HttpRequest) -> (&'static str, Value) {
+    let segments: Vec<&str> = request.path.trim_matches('/').split('/').collect();
+    let mut engine = ProcessorEngine::get().lock().unwrap();
+    if let ["processors", name, _] = segments.as_slice()
+        && !engine.processor_names().contains(name) {
+        return error_status(StreamingError::new(StreamErrCode::InvalidProcessorBlock,
+            &format!("Processor {} not found", name)));
+    }
+    match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["processors"]) => {
+            let processors: Vec<Value> = engine.processor_names().into_iter().map(|name| {
+                let commands: Vec<Value> = engine.list_commands(name).unwrap_or_default().into_iter()
+                    .map(|(command, help)| json!({"name": command, "help": help}))
+                    .collect();
+                json!({"name": name, "commands": commands})
+            }).collect();
+            ("200 OK", Value::from(processors))
+        }
+        ("GET", ["processors", name, "params"]) => match engine.get_parameters(name) {
+            Ok(params) => ("200 OK", params),
+            Err(e) => error_status(StreamingError::new(e, &format!("Unable to read the parameters of {}", name))),
+        },
+        ("POST", ["processors", name, "command"]) => {
+            let command: CommandRequest = match serde_json::from_slice(&request.body) {
+                Ok(command) => command,
+                Err(_) => return ("400 Bad Request", json!({"error": "Invalid command body"})),
+            };
+            let args: Vec<&str> = command.args.iter().map(String::as_str).collect();
+            match engine.execute_command(name, &command.command, args) {
+                Ok(result) => ("200 OK", json!({"result": result})),
+                Err(e) => error_status(StreamingError::new(e, &format!("Command {} failed on {}", command.command, name))),
+            }
+        }
+        (_, ["processors"]) | (_, ["processors", _, "params"]) | (_, ["processors", _, "command"]) =>
+            ("405 Method Not Allowed", json!({"error": "Method not allowed"})),
+        _ => ("404 Not Found", json!({"error": "Not found"})),
+    }
+}
+
+/// HTTP and JSON control plane of the ProcessorEngine, so that processors can
+/// be inspected and tuned at run time. Each connection carries one request.
+#[derive(StreamBlockMacro)]
+pub struct ControlServer {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    listener:   Option<TcpListener>,
+}
+
+impl ControlServer {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            listener: None,
+        };
+        ret.new_statics::<u16>("port", 50090, None).unwrap();
+        ret.new_statics::<String>("address", "127.0.0.1".to_string(), None).unwrap();
+        ret
+    }
+    fn serve(mut stream: TcpStream) {
+        let ready = stream.set_nonblocking(false).is_ok()
+            && stream.set_read_timeout(Some(CLIENT_TIMEOUT)).is_ok()
+            && stream.set_write_timeout(Some(CLIENT_TIMEOUT)).is_ok();
+        if !ready {
+            return;
+        }
+        let (status, body) = match read_request(&mut stream) {
+            Some(request) => route(&request),
+            None => ("400 Bad Request", json!({"error": "Malformed request"})),
+        };
+        let _ = write_response(&mut stream, status, &body);
+    }
+}
+
+impl StreamProcessor for ControlServer {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        let port = self.get_statics_value::<u16>("port")?;
+        let address = self.get_statics_value::<String>("address")?;
+        let listener = TcpListener::bind(format!("{}:{}", address, port))
+            .map_err(|_| StreamErrCode::CreateError)?;
+        // Non blocking, so that process returns and a stop request is seen.
+        listener.set_nonblocking(true).map_err(|_| StreamErrCode::CreateError)?;
+        self.listener = Some(listener);
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let listener = self.listener.as_ref().ok_or(StreamErrCode::InvalidOperation)?;
+        match listener.accept() {
+            Ok((stream, _)) => {
+                let _lock = self.lock.lock().unwrap();
+                Self::serve(stream);
+                Ok(())
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                thread::sleep(Duration::from_millis(10));
+                Ok(())
+            }
+            Err(_) => Err(StreamErrCode::ReceiveDataError),
+        }
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        self.listener = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use processor_engine::test::TestBlock;
+
+    fn http_request(port: u16, request: &str) -> (String, Value) {
+        let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        client.write_all(request.as_bytes()).unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        (head.lines().next().unwrap().to_string(), serde_json::from_str(body).unwrap())
+    }
+
+    fn post(path: &str, body: &str) -> String {
+        format!("POST {} HTTP/1.1\r\nHost: localhost\r\nContent-Length: {}\r\n\r\n{}", path, body.len(), body)
+    }
+
+    #[test]
+    fn test_control_server() {
+        let port: u16 = 50391;
+        ProcessorEngine::get().lock().unwrap()
+            .register_processor("control_test_block", Box::new(TestBlock::new("control_test_block"))).unwrap();
+        let mut server = ControlServer::new("control_server_test");
+        server.set_statics_value::<u16>("port", port).unwrap();
+        server.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        assert!(server.init().is_ok());
+        let state = server.get_state_handle();
+        let handle = thread::spawn(move || server.run());
+
+        let (status, body) = http_request(port, "GET /processors HTTP/1.1\r\nHost: localhost\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        let block = body.as_array().unwrap().iter().find(|p| p["name"] == "control_test_block").unwrap();
+        assert!(block["commands"].as_array().unwrap().iter().any(|c| c["name"] == "set_parameter"));
+
+        let (status, body) = http_request(port, &post("/processors/control_test_block/command",
+            r#"{"command": "set_parameter", "args": ["change_sign", "true"]}"#));
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body, json!({"result": ""}));
+        let (status, body) = http_request(port, "GET /processors/control_test_block/params HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 200 OK");
+        assert_eq!(body, json!({"control_test_block.change_sign": true, "control_test_block.bypass": false}));
+
+        let (status, body) = http_request(port, &post("/processors/control_test_block/command", r#"{"command": "explode"}"#));
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        assert_eq!(body["error"], "Error InvalidOperation: Command explode failed on control_test_block");
+        let (status, body) = http_request(port, &post("/processors/control_test_block/command",
+            r#"{"command": "set_parameter", "args": ["change_sign"]}"#));
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        assert_eq!(body["error"], "Error InvalidInput: Command set_parameter failed on control_test_block");
+        let (status, _) = http_request(port, &post("/processors/control_test_block/command", "not json"));
+        assert_eq!(status, "HTTP/1.1 400 Bad Request");
+        let (status, body) = http_request(port, "GET /processors/missing/params HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        assert_eq!(body["error"], "Error InvalidProcessorBlock: Processor missing not found");
+        let (status, _) = http_request(port, &post("/processors/missing/command", r#"{"command": "pause"}"#));
+        assert_eq!(status, "HTTP/1.1 404 Not Found");
+        let (status, _) = http_request(port, "DELETE /processors HTTP/1.1\r\n\r\n");
+        assert_eq!(status, "HTTP/1.1 405 Method Not Allowed");
+
+        *state.lock().unwrap() = StreamingState::Stopped;
+        assert!(handle.join().unwrap().is_ok());
+    }
+}
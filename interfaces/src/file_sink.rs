@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum FileFormat {
+    /// Little-endian `f64`, the format read by `FileSourceProcess`.
+    Binary,
+    /// One sample per line.
+    Csv,
+}
+
+/// Appends the received samples to the file at `path`, in the given `format`.
+#[derive(StreamBlockMacro)]
+pub struct FileSinkProcess {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    writer:     Option<BufWriter<File>>,
+}
+
+impl FileSinkProcess {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            writer: None,
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_parameter::<String>("path", "./samples.bin".to_string(), None).unwrap();
+        ret.new_parameter::<FileFormat>("format", FileFormat::Binary, None).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for FileSinkProcess {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        let path = self.get_parameter_value::<String>("path")?;
+        let file = OpenOptions::new().create(true).append(true).open(path)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        self.writer = Some(BufWriter::new(file));
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let samples = self.recv_input::<Vec<f64>>("input")?;
+        let format = self.get_parameter_value::<FileFormat>("format")?;
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => {return Err(StreamErrCode::FileNotFound);}
+        };
+        for sample in samples {
+            match format {
+                FileFormat::Binary => writer.write_all(&sample.to_le_bytes()),
+                FileFormat::Csv => writeln!(writer, "{}", sample),
+            }.map_err(|_| StreamErrCode::WriteError)?;
+        }
+        Ok(())
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        if let Some(mut writer) = self.writer.take() {
+            writer.flush().map_err(|_| StreamErrCode::WriteError)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn write_samples(name: &'static str, path: &str, format: FileFormat, chunks: &[Vec<f64>]) {
+        let mut sink = FileSinkProcess::new(name);
+        sink.set_parameter_value::<String>("path", path.to_string()).unwrap();
+        sink.set_parameter_value::<FileFormat>("format", format).unwrap();
+        assert!(sink.init().is_ok());
+        let input = sink.get_input_channel::<Vec<f64>>("input").unwrap();
+        for chunk in chunks {
+            input.send(chunk.clone()).unwrap();
+            sink.process().unwrap();
+        }
+        sink.stop().unwrap();
+    }
+
+    #[test]
+    fn test_file_sink() {
+        let path = std::env::temp_dir().join("kappa_test_file_sink.csv");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        write_samples("test_file_sink_csv", path, FileFormat::Csv, &[vec![0.5, -1.0], vec![2.25]]);
+        // Reopening appends.
+        write_samples("test_file_sink_csv_append", path, FileFormat::Csv, &[vec![3.0]]);
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "0.5\n-1\n2.25\n3\n");
+        std::fs::remove_file(path).unwrap();
+
+        let path = std::env::temp_dir().join("kappa_test_file_sink.bin");
+        let path = path.to_str().unwrap();
+        let _ = std::fs::remove_file(path);
+        write_samples("test_file_sink_bin", path, FileFormat::Binary, &[vec![0.5, -1.0], vec![2.25]]);
+        let bytes = std::fs::read(path).unwrap();
+        let samples: Vec<f64> = bytes.chunks_exact(8).map(|b| f64::from_le_bytes(b.try_into().unwrap())).collect();
+        assert_eq!(samples, vec![0.5, -1.0, 2.25]);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,150 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufReader, ErrorKind, Read, Seek, SeekFrom};
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Emits the samples of a raw file of little-endian `f64`, `chunk_size` at a
+/// time. At the end of the file the block either stops, after a last shorter
+/// chunk, or wraps around to the start when the `loop` statics is set.
+#[derive(StreamBlockMacro)]
+pub struct FileSourceProcess {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    reader:     Option<BufReader<File>>,
+}
+
+impl FileSourceProcess {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            reader: None,
+        };
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<String>("path", "./samples.bin".to_string(), None).unwrap();
+        ret.new_parameter::<usize>("chunk_size", 1024, Some([1, usize::MAX])).unwrap();
+        ret.new_statics::<bool>("loop", false, None).unwrap();
+        ret
+    }
+    /// Reads up to `chunk_size` samples, rewinding once at the end of the file
+    /// when looping. A trailing partial sample is dropped.
+    fn read_chunk(&mut self, chunk_size: usize, looping: bool) -> Result<Vec<f64>, StreamErrCode> {
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => {return Err(StreamErrCode::FileNotFound);}
+        };
+        let mut chunk = Vec::with_capacity(chunk_size);
+        let mut sample = [0u8; 8];
+        // Guards against spinning on a file too short to hold a sample.
+        let mut rewound = false;
+        while chunk.len() < chunk_size {
+            match reader.read_exact(&mut sample) {
+                Ok(_) => {
+                    chunk.push(f64::from_le_bytes(sample));
+                    rewound = false;
+                }
+                Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                    if !looping || rewound {
+                        break;
+                    }
+                    reader.seek(SeekFrom::Start(0)).map_err(|_| StreamErrCode::ReadError)?;
+                    rewound = true;
+                }
+                Err(_) => {return Err(StreamErrCode::ReadError);}
+            }
+        }
+        Ok(chunk)
+    }
+}
+
+impl StreamProcessor for FileSourceProcess {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        let path = self.get_parameter_value::<String>("path")?;
+        let file = File::open(&path).map_err(|_| StreamErrCode::FileNotFound)?;
+        self.reader = Some(BufReader::new(file));
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let chunk_size = self.get_parameter_value::<usize>("chunk_size")?;
+        let looping = self.get_statics_value::<bool>("loop")?;
+        let chunk = self.read_chunk(chunk_size, looping)?;
+        if chunk.is_empty() {
+            self.set_state(StreamingState::Stopped);
+            return Ok(());
+        }
+        self.send_output::<Vec<f64>>("output", chunk)
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        self.reader = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn source(name: &'static str, path: &str, looping: bool) -> (FileSourceProcess, std::sync::mpsc::Receiver<Vec<f64>>) {
+        let mut source = FileSourceProcess::new(name);
+        source.set_parameter_value::<String>("path", path.to_string()).unwrap();
+        source.set_parameter_value::<usize>("chunk_size", 2).unwrap();
+        source.set_statics_value::<bool>("loop", looping).unwrap();
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(10);
+        source.connect("output", sender).unwrap();
+        assert!(source.init().is_ok());
+        (source, receiver)
+    }
+
+    #[test]
+    fn test_file_source() {
+        let path = std::env::temp_dir().join("kappa_test_file_source.bin");
+        let path = path.to_str().unwrap();
+        let bytes: Vec<u8> = [0.0f64, 1.0, 2.0, 3.0, 4.0].iter().flat_map(|v| v.to_le_bytes()).collect();
+        std::fs::write(path, bytes).unwrap();
+
+        let (mut once, receiver) = source("test_file_source_once", path, false);
+        for expected in [vec![0.0, 1.0], vec![2.0, 3.0], vec![4.0]] {
+            once.process().unwrap();
+            assert_eq!(receiver.try_recv().unwrap(), expected);
+        }
+        once.process().unwrap();
+        assert!(receiver.try_recv().is_err());
+        assert!(once.check_state(StreamingState::Stopped));
+
+        let (mut looped, receiver) = source("test_file_source_loop", path, true);
+        for expected in [vec![0.0, 1.0], vec![2.0, 3.0], vec![4.0, 0.0], vec![1.0, 2.0]] {
+            looped.process().unwrap();
+            assert_eq!(receiver.try_recv().unwrap(), expected);
+        }
+        std::fs::remove_file(path).unwrap();
+    }
+}
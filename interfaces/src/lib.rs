@@ -4,4 +4,9 @@
 pub mod tcp_sender;
 pub mod tcp_receiver;
 pub mod udp_receiver;
-pub mod udp_sender;
\ No newline at end of file
+pub mod udp_sender;
+pub mod recorder;
+pub mod file_source;
+pub mod file_sink;
+pub mod serial;
+pub mod control_server;
\ No newline at end of file
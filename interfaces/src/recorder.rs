@@ -0,0 +1,375 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, ErrorKind, Read, Seek, SeekFrom, Write};
+use std::marker::PhantomData;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingError, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+use utils::bytes::{bytes_from_pod, pod_from_bytes, Pod};
+
+pub const RECORDING_MAGIC: [u8; 4] = *b"KREC";
+pub const RECORDING_VERSION: u32 = 1;
+/// Longest type name accepted in a header, checked before reading it.
+pub const MAX_TYPE_NAME_LENGTH: usize = 1024;
+
+/// Header of a recording file. All the fields are little-endian:
+/// magic (4 bytes), version (u32), type name length (u32), type name (UTF-8),
+/// sample rate (f64), frame count (u64). The header is followed by frames,
+/// each one prefixed by its length in bytes (u32). Frames hold the raw bytes of
+/// plain old data samples, so only `Pod` types can be recorded.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordingHeader {
+    pub version: u32,
+    pub type_name: String,
+    pub sample_rate: f64,
+    pub frame_count: u64,
+}
+
+impl RecordingHeader {
+    pub fn new(type_name: &str, sample_rate: f64) -> Self {
+        Self {
+            version: RECORDING_VERSION,
+            type_name: type_name.to_string(),
+            sample_rate,
+            frame_count: 0,
+        }
+    }
+    fn frame_count_offset(&self) -> u64 {
+        (RECORDING_MAGIC.len() + 4 + 4 + self.type_name.len() + 8) as u64
+    }
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        writer.write_all(&RECORDING_MAGIC)?;
+        writer.write_all(&self.version.to_le_bytes())?;
+        writer.write_all(&(self.type_name.len() as u32).to_le_bytes())?;
+        writer.write_all(self.type_name.as_bytes())?;
+        writer.write_all(&self.sample_rate.to_le_bytes())?;
+        writer.write_all(&self.frame_count.to_le_bytes())
+    }
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<Self, StreamingError> {
+        let read_error = |_| StreamingError::new(StreamErrCode::ReadError, "Truncated recording header");
+        let mut magic = [0u8; 4];
+        reader.read_exact(&mut magic).map_err(read_error)?;
+        if magic != RECORDING_MAGIC {
+            return Err(StreamingError::new(StreamErrCode::InvalidInput, "Not a recording file"));
+        }
+        let mut word = [0u8; 4];
+        reader.read_exact(&mut word).map_err(read_error)?;
+        let version = u32::from_le_bytes(word);
+        reader.read_exact(&mut word).map_err(read_error)?;
+        let type_name_length = u32::from_le_bytes(word) as usize;
+        if type_name_length > MAX_TYPE_NAME_LENGTH {
+            return Err(StreamingError::new(StreamErrCode::InvalidInput,
+                &format!("Recording type name of {} bytes, at most {} allowed", type_name_length, MAX_TYPE_NAME_LENGTH)));
+        }
+        let mut type_name = vec![0u8; type_name_length];
+        reader.read_exact(&mut type_name).map_err(read_error)?;
+        let mut long_word = [0u8; 8];
+        reader.read_exact(&mut long_word).map_err(read_error)?;
+        let sample_rate = f64::from_le_bytes(long_word);
+        reader.read_exact(&mut long_word).map_err(read_error)?;
+        let frame_count = u64::from_le_bytes(long_word);
+        Ok(Self {
+            version,
+            type_name: String::from_utf8_lossy(&type_name).into_owned(),
+            sample_rate,
+            frame_count,
+        })
+    }
+    pub fn validate(&self, type_name: &str) -> Result<(), StreamingError> {
+        if self.version != RECORDING_VERSION {
+            return Err(StreamingError::new(StreamErrCode::InvalidInput,
+                &format!("Unsupported recording version {} (expected {})", self.version, RECORDING_VERSION)));
+        }
+        if self.type_name != type_name {
+            return Err(StreamingError::new(StreamErrCode::WrongType,
+                &format!("Recording contains {} samples, expected {}", self.type_name, type_name)));
+        }
+        Ok(())
+    }
+}
+
+#[derive(StreamBlockMacro)]
+pub struct RecorderProcess<T: 'static + Send + Clone + Pod> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    header:     RecordingHeader,
+    writer:     Option<BufWriter<File>>,
+}
+
+impl<T> RecorderProcess<T> where T: 'static + Send + Clone + Pod {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            header: RecordingHeader::new(std::any::type_name::<T>(), 0.0),
+            writer: None,
+        };
+        ret.new_input::<T>("input").unwrap();
+        ret.new_statics::<String>("path", "./recording.krec".to_string(), None).unwrap();
+        ret.new_statics::<f64>("sample_rate", 0.0, None).unwrap();
+        ret
+    }
+    fn finalize(&mut self) -> std::io::Result<()> {
+        if let Some(mut writer) = self.writer.take() {
+            writer.seek(SeekFrom::Start(self.header.frame_count_offset()))?;
+            writer.write_all(&self.header.frame_count.to_le_bytes())?;
+            writer.flush()?;
+        }
+        Ok(())
+    }
+}
+
+impl<T> StreamProcessor for RecorderProcess<T> where T: 'static + Send + Clone + Pod {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        let path = self.get_statics_value::<String>("path")?;
+        let sample_rate = self.get_statics_value::<f64>("sample_rate")?;
+        self.header = RecordingHeader::new(std::any::type_name::<T>(), sample_rate);
+        let mut writer = BufWriter::new(File::create(path).map_err(|_| StreamErrCode::CreateError)?);
+        self.header.write_to(&mut writer).map_err(|_| StreamErrCode::WriteError)?;
+        self.writer = Some(writer);
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let input = self.recv_input::<T>("input")?;
+        let frame = bytes_from_pod(&input);
+        match self.writer.as_mut() {
+            Some(writer) => {
+                writer.write_all(&(frame.len() as u32).to_le_bytes()).map_err(|_| StreamErrCode::WriteError)?;
+                writer.write_all(frame).map_err(|_| StreamErrCode::WriteError)?;
+            }
+            None => {return Err(StreamErrCode::FileNotFound);}
+        }
+        self.header.frame_count += 1;
+        Ok(())
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        self.finalize().map_err(|_| StreamErrCode::WriteError)
+    }
+}
+
+#[derive(StreamBlockMacro)]
+pub struct ReplayProcess<T: 'static + Send + Clone + Pod> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    header:     Option<RecordingHeader>,
+    reader:     Option<BufReader<File>>,
+}
+
+impl<T> ReplayProcess<T> where T: 'static + Send + Clone + Pod {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            header: None,
+            reader: None,
+        };
+        ret.new_output::<T>("output").unwrap();
+        ret.new_statics::<String>("path", "./recording.krec".to_string(), None).unwrap();
+        ret
+    }
+    /// Opens the recording and checks that it holds samples of type `T`.
+    pub fn open(&mut self) -> Result<RecordingHeader, StreamingError> {
+        let path = self.get_statics_value::<String>("path")
+            .map_err(|e| StreamingError::new(e, "Recording path not available"))?;
+        let file = File::open(&path)
+            .map_err(|_| StreamingError::new(StreamErrCode::FileNotFound, &format!("Unable to open {}", path)))?;
+        let mut reader = BufReader::new(file);
+        let header = RecordingHeader::read_from(&mut reader)?;
+        header.validate(std::any::type_name::<T>())?;
+        self.header = Some(header.clone());
+        self.reader = Some(reader);
+        Ok(header)
+    }
+    pub fn get_header(&self) -> Option<&RecordingHeader> {
+        self.header.as_ref()
+    }
+}
+
+impl<T> StreamProcessor for ReplayProcess<T> where T: 'static + Send + Clone + Pod {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        if let Err(e) = self.open() {
+            eprintln!("{}", e);
+            return Err(e.code);
+        }
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let reader = match self.reader.as_mut() {
+            Some(reader) => reader,
+            None => {return Err(StreamErrCode::FileNotFound);}
+        };
+        let mut length = [0u8; 4];
+        match reader.read_exact(&mut length) {
+            Ok(_) => {}
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => {
+                self.set_state(StreamingState::Stopped);
+                return Ok(());
+            }
+            Err(_) => {return Err(StreamErrCode::ReadError);}
+        }
+        // Every frame holds one sample: any other length is a corrupt file.
+        if u32::from_le_bytes(length) as usize != std::mem::size_of::<T>() {
+            return Err(StreamErrCode::ReadError);
+        }
+        let mut frame = vec![0u8; std::mem::size_of::<T>()];
+        reader.read_exact(&mut frame).map_err(|_| StreamErrCode::ReadError)?;
+        let message = pod_from_bytes::<T>(&frame).map_err(|_| StreamErrCode::ReadError)?;
+        self.send_output::<T>("output", message)
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        self.reader = None;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn record_u32(name: &'static str, path: &str, values: &[u32]) {
+        let mut recorder = RecorderProcess::<u32>::new(name);
+        recorder.set_statics_value::<String>("path", path.to_string()).unwrap();
+        recorder.set_statics_value::<f64>("sample_rate", 1000.0).unwrap();
+        assert!(recorder.init().is_ok());
+        let input = recorder.get_input_channel::<u32>("input").unwrap();
+        for v in values {
+            input.send(*v).unwrap();
+            recorder.process().unwrap();
+        }
+        recorder.stop().unwrap();
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = std::env::temp_dir().join("kappa_test_record_and_replay.krec");
+        let path = path.to_str().unwrap();
+        record_u32("test_recorder", path, &[3, 5, 8]);
+
+        let mut replay = ReplayProcess::<u32>::new("test_replay");
+        replay.set_statics_value::<String>("path", path.to_string()).unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        replay.connect("output", out_sender).unwrap();
+        assert!(replay.init().is_ok());
+        let header = replay.get_header().unwrap();
+        assert_eq!(header.frame_count, 3);
+        assert_eq!(header.sample_rate, 1000.0);
+        for expected in [3, 5, 8] {
+            replay.process().unwrap();
+            assert_eq!(out_receiver.recv().unwrap(), expected);
+        }
+        replay.process().unwrap();
+        assert!(replay.check_state(StreamingState::Stopped));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_rejects_wrong_type() {
+        let path = std::env::temp_dir().join("kappa_test_replay_wrong_type.krec");
+        let path = path.to_str().unwrap();
+        record_u32("test_recorder_type", path, &[1]);
+
+        let mut replay = ReplayProcess::<f32>::new("test_replay_type");
+        replay.set_statics_value::<String>("path", path.to_string()).unwrap();
+        let err = replay.open().err().unwrap();
+        assert_eq!(err.code, StreamErrCode::WrongType);
+        assert!(err.message.contains("u32"));
+        assert_eq!(replay.init(), Err(StreamErrCode::WrongType));
+        std::fs::remove_file(path).unwrap();
+    }
+    #[test]
+    fn test_replay_corrupt_file() {
+        let path = std::env::temp_dir().join("kappa_test_replay_corrupt.krec");
+        let path = path.to_str().unwrap();
+        let replay_of = |name: &'static str| {
+            let mut replay = ReplayProcess::<u32>::new(name);
+            replay.set_statics_value::<String>("path", path.to_string()).unwrap();
+            replay
+        };
+
+        // A type name length read from a corrupt header is not allocated.
+        let mut header = Vec::new();
+        header.extend_from_slice(&RECORDING_MAGIC);
+        header.extend_from_slice(&RECORDING_VERSION.to_le_bytes());
+        header.extend_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(path, &header).unwrap();
+        let err = replay_of("test_replay_long_name").open().err().unwrap();
+        assert_eq!(err.code, StreamErrCode::InvalidInput);
+
+        // Neither is a frame length other than the size of a sample.
+        record_u32("test_recorder_corrupt", path, &[3]);
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        let mut replay = replay_of("test_replay_long_frame");
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        replay.connect("output", out_sender).unwrap();
+        assert!(replay.init().is_ok());
+        replay.process().unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 3);
+        assert_eq!(replay.process(), Err(StreamErrCode::ReadError));
+
+        // A truncated last frame is reported as well.
+        record_u32("test_recorder_truncated", path, &[3, 5]);
+        let length = std::fs::metadata(path).unwrap().len();
+        std::fs::OpenOptions::new().write(true).open(path).unwrap().set_len(length - 2).unwrap();
+        let mut replay = replay_of("test_replay_truncated");
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        replay.connect("output", out_sender).unwrap();
+        assert!(replay.init().is_ok());
+        replay.process().unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 3);
+        assert_eq!(replay.process(), Err(StreamErrCode::ReadError));
+        std::fs::remove_file(path).unwrap();
+    }
+}
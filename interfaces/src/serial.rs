@@ -0,0 +1,423 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::fs::{File, OpenOptions};
+use std::io::{ErrorKind, Read, Write};
+use std::marker::PhantomData;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::OpenOptionsExt;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+use utils::bytes::{bytes_from_pod, pod_from_bytes, Pod};
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum Parity {
+    None,
+    Even,
+    Odd,
+}
+
+fn baud_rate_constant(baud_rate: u32) -> Option<libc::speed_t> {
+    Some(match baud_rate {
+        1200 => libc::B1200,
+        2400 => libc::B2400,
+        4800 => libc::B4800,
+        9600 => libc::B9600,
+        19200 => libc::B19200,
+        38400 => libc::B38400,
+        57600 => libc::B57600,
+        115200 => libc::B115200,
+        230400 => libc::B230400,
+        460800 => libc::B460800,
+        921600 => libc::B921600,
+        _ => return None,
+    })
+}
+
+/// Opens `device` as a raw serial port with the given framing. Reads return
+/// after at most 100 ms, possibly with no data, so that a reader can notice
+/// a stop request.
+pub fn open_serial_port(device: &str, baud_rate: u32, data_bits: u8, parity: Parity, stop_bits: u8) -> std::io::Result<File> {
+    let invalid = |message: &str| std::io::Error::new(ErrorKind::InvalidInput, message.to_string());
+    let speed = baud_rate_constant(baud_rate).ok_or_else(|| invalid("Unsupported baud rate"))?;
+    let size = match data_bits {
+        5 => libc::CS5,
+        6 => libc::CS6,
+        7 => libc::CS7,
+        8 => libc::CS8,
+        _ => return Err(invalid("Data bits must be between 5 and 8")),
+    };
+    if stop_bits != 1 && stop_bits != 2 {
+        return Err(invalid("Stop bits must be 1 or 2"));
+    }
+    let port = OpenOptions::new().read(true).write(true).custom_flags(libc::O_NOCTTY).open(device)?;
+    let fd = port.as_raw_fd();
+    let mut termios: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(fd, &mut termios) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    unsafe { libc::cfmakeraw(&mut termios) };
+    termios.c_cflag &= !(libc::CSIZE | libc::PARENB | libc::PARODD | libc::CSTOPB);
+    termios.c_cflag |= size | libc::CLOCAL | libc::CREAD;
+    match parity {
+        Parity::None => {}
+        Parity::Even => termios.c_cflag |= libc::PARENB,
+        Parity::Odd => termios.c_cflag |= libc::PARENB | libc::PARODD,
+    }
+    if stop_bits == 2 {
+        termios.c_cflag |= libc::CSTOPB;
+    }
+    termios.c_cc[libc::VMIN] = 0;
+    termios.c_cc[libc::VTIME] = 1;
+    let ret = unsafe {
+        libc::cfsetispeed(&mut termios, speed) | libc::cfsetospeed(&mut termios, speed) | libc::tcsetattr(fd, libc::TCSANOW, &termios)
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(port)
+}
+
+/// Removes the first complete frame, a little-endian `u32` length followed by
+/// the payload, from `buffer`. A length above `max_length` means the stream
+/// is corrupted or out of sync: the buffer is discarded and an error returned
+/// instead of waiting for a payload that may never arrive.
+fn take_frame(buffer: &mut Vec<u8>, max_length: usize) -> Result<Option<Vec<u8>>, StreamErrCode> {
+    let length = match buffer.get(..4) {
+        Some(prefix) => u32::from_le_bytes(prefix.try_into().unwrap()) as usize,
+        None => return Ok(None),
+    };
+    if length > max_length {
+        buffer.clear();
+        return Err(StreamErrCode::ReceiveDataError);
+    }
+    if buffer.len() < 4 + length {
+        return Ok(None);
+    }
+    let frame = buffer[4..4 + length].to_vec();
+    buffer.drain(..4 + length);
+    Ok(Some(frame))
+}
+
+macro_rules! serial_statics {
+    ($block:expr) => {
+        $block.new_statics::<String>("device", "/dev/ttyUSB0".to_string(), None).unwrap();
+        $block.new_statics::<u32>("baud_rate", 115200, None).unwrap();
+        $block.new_statics::<u8>("data_bits", 8, Some([5, 8])).unwrap();
+        $block.new_statics::<Parity>("parity", Parity::None, None).unwrap();
+        $block.new_statics::<u8>("stop_bits", 1, Some([1, 2])).unwrap();
+    };
+}
+
+macro_rules! open_port {
+    ($block:expr) => {
+        open_serial_port(
+            &$block.get_statics_value::<String>("device")?,
+            $block.get_statics_value::<u32>("baud_rate")?,
+            $block.get_statics_value::<u8>("data_bits")?,
+            $block.get_statics_value::<Parity>("parity")?,
+            $block.get_statics_value::<u8>("stop_bits")?,
+        ).map_err(|_| StreamErrCode::CreateError)
+    };
+}
+
+/// Receives length-prefixed frames from a serial port, each one holding the
+/// raw bytes of a plain old data `T`.
+#[derive(StreamBlockMacro)]
+pub struct SerialReceiver<T: 'static + Send + Clone + Pod> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    port:       Option<File>,
+    buffer:     Vec<u8>,
+}
+impl<T> SerialReceiver<T> where T: 'static + Send + Clone + Pod {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            port: None,
+            buffer: Vec::new(),
+        };
+        ret.new_output::<T>("output").unwrap();
+        serial_statics!(ret);
+        ret
+    }
+    fn open_port(&mut self) -> Result<(), StreamErrCode> {
+        self.port = Some(open_port!(self)?);
+        self.buffer.clear();
+        Ok(())
+    }
+}
+impl<T> StreamProcessor for SerialReceiver<T> where T: 'static + Send + Clone + Pod {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn run(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Stopped) {
+            return Err(StreamErrCode::InvalidStateTransition);
+        }
+        self.set_state(StreamingState::Running);
+        self.open_port()?;
+        while !self.check_state(StreamingState::Stopped) {
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process() {
+                // A stop request interrupts a pending receive with an error.
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let port = match self.port.as_mut() {
+            Some(port) => port,
+            None => {return Err(StreamErrCode::ReceiveDataError);}
+        };
+        let mut chunk = [0u8; 4096];
+        let read = match port.read(&mut chunk) {
+            Ok(read) => read,
+            Err(e) if e.kind() == ErrorKind::Interrupted || e.kind() == ErrorKind::WouldBlock => 0,
+            Err(_) => {return Err(StreamErrCode::ReceiveDataError);}
+        };
+        self.buffer.extend_from_slice(&chunk[..read]);
+        while let Some(frame) = take_frame(&mut self.buffer, std::mem::size_of::<T>())? {
+            let message = pod_from_bytes::<T>(&frame)
+                .map_err(|_| StreamErrCode::ReceiveDataError)?;
+            self.send_output::<T>("output", message)?;
+        }
+        Ok(())
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        self.port = None;
+        Ok(())
+    }
+}
+
+/// Sends each input `T` to a serial port as a length-prefixed frame holding
+/// its raw bytes, so only `Pod` types can be sent.
+#[derive(StreamBlockMacro)]
+pub struct SerialSender<T: 'static + Send + Clone + Pod> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    port:       Option<File>,
+}
+impl<T> SerialSender<T> where T: 'static + Send + Clone + Pod {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            port: None,
+        };
+        ret.new_input::<T>("input").unwrap();
+        serial_statics!(ret);
+        ret
+    }
+    fn open_port(&mut self) -> Result<(), StreamErrCode> {
+        self.port = Some(open_port!(self)?);
+        Ok(())
+    }
+}
+impl<T> StreamProcessor for SerialSender<T> where T: 'static + Send + Clone + Pod {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn run(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Stopped) {
+            return Err(StreamErrCode::InvalidStateTransition);
+        }
+        self.set_state(StreamingState::Running);
+        self.open_port()?;
+        while !self.check_state(StreamingState::Stopped) {
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process() {
+                // A stop request interrupts a pending receive with an error.
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let input = self.recv_input::<T>("input")?;
+        let payload = bytes_from_pod::<T>(&input);
+        let port = match self.port.as_mut() {
+            Some(port) => port,
+            None => {return Err(StreamErrCode::SendDataError);}
+        };
+        let mut frame = (payload.len() as u32).to_le_bytes().to_vec();
+        frame.extend_from_slice(payload);
+        port.write_all(&frame).map_err(|_| StreamErrCode::SendDataError)
+    }
+    fn stop(&mut self) -> Result<(), StreamErrCode> {
+        self.set_state(StreamingState::Stopped);
+        if let Some(mut port) = self.port.take() {
+            port.flush().map_err(|_| StreamErrCode::SendDataError)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::os::fd::FromRawFd;
+
+    /// Opens a pseudo-terminal and returns its master side with the path of
+    /// the slave, which stands in for the serial device.
+    fn open_pty() -> (File, String) {
+        unsafe {
+            let fd = libc::posix_openpt(libc::O_RDWR | libc::O_NOCTTY);
+            assert!(fd >= 0);
+            assert_eq!(libc::grantpt(fd), 0);
+            assert_eq!(libc::unlockpt(fd), 0);
+            let mut name = [0 as libc::c_char; 128];
+            assert_eq!(libc::ptsname_r(fd, name.as_mut_ptr(), name.len()), 0);
+            let path = std::ffi::CStr::from_ptr(name.as_ptr()).to_string_lossy().into_owned();
+            (File::from_raw_fd(fd), path)
+        }
+    }
+
+    fn set_statics<B: StreamBlock>(block: &mut B, device: &str) {
+        block.set_statics_value::<String>("device", device.to_string()).unwrap();
+        block.set_statics_value::<u32>("baud_rate", 9600).unwrap();
+        block.set_statics_value::<u8>("data_bits", 8).unwrap();
+        block.set_statics_value::<Parity>("parity", Parity::Even).unwrap();
+        block.set_statics_value::<u8>("stop_bits", 1).unwrap();
+    }
+
+    #[test]
+    fn test_serial_receiver() {
+        let (mut master, device) = open_pty();
+        let mut receiver = SerialReceiver::<u32>::new("serial_receiver_test");
+        set_statics(&mut receiver, &device);
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        receiver.connect("output", out_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        receiver.open_port().unwrap();
+
+        // Nothing to read: process() returns after the read timeout.
+        receiver.process().unwrap();
+        assert!(out_receiver.try_recv().is_err());
+
+        // Two frames, the second one split across reads.
+        let mut bytes = Vec::new();
+        for value in [7u32, 11] {
+            bytes.extend_from_slice(&4u32.to_le_bytes());
+            bytes.extend_from_slice(&value.to_ne_bytes());
+        }
+        master.write_all(&bytes[..10]).unwrap();
+        receiver.process().unwrap();
+        assert_eq!(out_receiver.try_recv().unwrap(), 7);
+        assert!(out_receiver.try_recv().is_err());
+        master.write_all(&bytes[10..]).unwrap();
+        receiver.process().unwrap();
+        assert_eq!(out_receiver.try_recv().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_serial_frame_too_long() {
+        let (mut master, device) = open_pty();
+        let mut receiver = SerialReceiver::<u32>::new("serial_receiver_long_test");
+        set_statics(&mut receiver, &device);
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        receiver.connect("output", out_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        receiver.open_port().unwrap();
+
+        // A corrupted length prefix is rejected instead of being waited for.
+        master.write_all(&u32::MAX.to_le_bytes()).unwrap();
+        assert_eq!(receiver.process().unwrap_err(), StreamErrCode::ReceiveDataError);
+        assert!(receiver.buffer.is_empty());
+
+        // The stream resynchronises on the next frame.
+        master.write_all(&4u32.to_le_bytes()).unwrap();
+        master.write_all(&5u32.to_ne_bytes()).unwrap();
+        receiver.process().unwrap();
+        assert_eq!(out_receiver.try_recv().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_serial_sender() {
+        let (mut master, device) = open_pty();
+        let mut sender = SerialSender::<u32>::new("serial_sender_test");
+        set_statics(&mut sender, &device);
+        assert!(sender.init().is_ok());
+        sender.open_port().unwrap();
+        sender.get_input_channel::<u32>("input").unwrap().send(42).unwrap();
+        sender.process().unwrap();
+        let mut frame = [0u8; 8];
+        master.read_exact(&mut frame).unwrap();
+        assert_eq!(frame[..4], 4u32.to_le_bytes());
+        assert_eq!(frame[4..], 42u32.to_ne_bytes());
+    }
+
+    #[test]
+    fn test_serial_invalid_framing() {
+        let (_master, device) = open_pty();
+        assert_eq!(open_serial_port(&device, 1234, 8, Parity::None, 1).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(open_serial_port(&device, 9600, 9, Parity::None, 1).unwrap_err().kind(), ErrorKind::InvalidInput);
+        assert_eq!(open_serial_port(&device, 9600, 8, Parity::None, 3).unwrap_err().kind(), ErrorKind::InvalidInput);
+    }
+}
@@ -1,15 +1,17 @@
 use std::collections::HashMap;
 use std::any::{Any, TypeId};
 use std::fmt::Debug;
-use std::io::{Read, Write};
+use std::io::{ErrorKind, Read, Write};
 use std::marker::PhantomData;
 use std::mem;
-use std::net::{TcpListener, TcpStream};
-use std::sync::mpsc::{Receiver, SyncSender};
-use std::sync::{Arc, Mutex, OnceLock};
-use std::thread::JoinHandle;
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc::{Receiver, RecvTimeoutError, SyncSender};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use processor_engine::log;
-use processor_engine::logger::{LogLevel, Logger,LogEntry};
+use processor_engine::logger::{LogLevel, Logger, LogEntry, LogSender};
 use processor_engine::task_monitor::TaskManager;
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
@@ -18,7 +20,12 @@ use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Stat
 use processor_engine::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
 use data_model::connectors::{ConnectorTrait, Input, Output};
 
-use crate::tcp_sender::as_byte;
+use crate::tcp_sender::{as_byte, set_keepalive, HEARTBEAT};
+
+// Read timeout of the client sockets, bounding the time to see a stop request.
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+// Heartbeat periods without data after which a client is considered dead.
+const HEARTBEAT_MISSES: f64 = 3.0;
 
 pub unsafe fn from_bytes<T: 'static>(data: &[u8]) -> Result<&T, StreamErrCode> {
     if TypeId::of::<T>() == TypeId::of::<String>() {
@@ -41,18 +48,35 @@ pub unsafe fn from_bytes<T: 'static>(data: &[u8]) -> Result<&T, StreamErrCode> {
     unsafe {Ok(&*ptr_t)}
 }
 
+/// Lifecycle of the client connections of a TcpReceiver. `id` is the
+/// `id_stream` of the messages of the connection.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionEvent {
+    Connected { id: u32, addr: SocketAddr },
+    Disconnected { id: u32 },
+}
+
 #[derive(Clone)]
 pub struct TcpMessage<T> {
     pub id_stream: u32,
     pub message: T,
 }
 
+// Receive buffer of a client, aligned so that from_bytes can read a T in place.
+#[repr(C, align(16))]
+struct ReadBuffer([u8; 65535]);
+
 pub struct TcpHandler<T> where T: 'static + Send + Clone {
     pub stream_id: u32,
     pub stream: TcpStream,
     pub data_sender: Output<TcpMessage<T>>,
     pub receiver: Receiver<TcpMessage<T>>,
     pub sender: SyncSender<TcpMessage<T>>,
+    /// Silence after which the client is dropped, None to wait forever.
+    pub idle_timeout: Option<Duration>,
+    pub last_activity: Instant,
+    /// Exit flag of the receiver, ending the wait for an answer.
+    pub exit: Arc<AtomicBool>,
 }
 
 impl<T> TcpHandler<T> where T: 'static + Send + Clone {
@@ -68,6 +92,9 @@ impl<T> TcpHandler<T> where T: 'static + Send + Clone {
             data_sender,
             receiver,
             sender,
+            idle_timeout: None,
+            last_activity: Instant::now(),
+            exit: Arc::new(AtomicBool::new(false)),
         }
     }
     pub fn get_sender(&self) -> SyncSender<TcpMessage<T>> 
@@ -77,9 +104,16 @@ impl<T> TcpHandler<T> where T: 'static + Send + Clone {
     }
     pub fn handle_stream(&mut self) -> Result<(), String> 
     {
-        let mut buffer = [0; 65535];
-        match self.stream.read(&mut buffer) {
+        let mut buffer = ReadBuffer([0; 65535]);
+        let buffer = &mut buffer.0;
+        match self.stream.read(buffer) {
+            Ok(0) => Err("Server: connection closed by the client".to_string()),
+            Ok(n) if &buffer[0..n] == HEARTBEAT => {
+                self.last_activity = Instant::now();
+                self.stream.write_all(HEARTBEAT).map_err(|_| "Server: write stream error".to_string())
+            }
             Ok(n) => {
+                self.last_activity = Instant::now();
                 let data = unsafe{from_bytes::<T>(&buffer[0..n])};
                 match data {
                     Ok(data) => {
@@ -88,15 +122,22 @@ impl<T> TcpHandler<T> where T: 'static + Send + Clone {
                             message: data.clone(),
                         };
                         let _ = self.data_sender.send(message);
-                        let recv_message = self.receiver.recv();
-                        match recv_message {
-                            Ok(msg) => {
-                                if self.stream.write_all(as_byte::<T>(&msg.message)).is_err() {
-                                    return Err("Server: write stream error".to_string());
-                                }   
-                                Ok(())
+                        loop {
+                            match self.receiver.recv_timeout(READ_TIMEOUT) {
+                                Ok(msg) => {
+                                    if self.stream.write_all(as_byte::<T>(&msg.message)).is_err() {
+                                        return Err("Server: write stream error".to_string());
+                                    }
+                                    return Ok(());
+                                }
+                                Err(RecvTimeoutError::Timeout) if !self.exit.load(Ordering::SeqCst) => {}
+                                Err(RecvTimeoutError::Timeout) => {
+                                    return Err("Handler: receiver stopped".to_string());
+                                }
+                                Err(RecvTimeoutError::Disconnected) => {
+                                    return Err("Handler: receive message error".to_string());
+                                }
                             }
-                            Err(_) => Err("Handler: receive message error".to_string()),
                         }
                     }
                     Err(_) => {
@@ -107,6 +148,14 @@ impl<T> TcpHandler<T> where T: 'static + Send + Clone {
                     }
                 }
             }
+            // Nothing received within READ_TIMEOUT.
+            Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                match self.idle_timeout {
+                    Some(timeout) if self.last_activity.elapsed() >= timeout =>
+                        Err("Server: no data nor heartbeat from the client".to_string()),
+                    _ => Ok(()),
+                }
+            }
             Err(e) => {
                 Err(format!("Server: read stream error: {}", e))
             }
@@ -130,6 +179,12 @@ pub struct TcpReceiver<T: 'static + Send + Clone> {
     tcp_listen: Option<TcpListener>,
     tcp_stream: HashMap<u32, Arc<Mutex<TcpHandler<T>>>>,
     tcp_handle: Vec<JoinHandle<()>>,
+    // Client loops still running, mirrored in the active_connections state.
+    active:     Arc<AtomicUsize>,
+    // Last value written to active_connections.
+    reported_active: usize,
+    // Set by stop to end the client loops of this receiver.
+    exit:       Arc<AtomicBool>,
 }
 
 impl<T> TcpReceiver<T> 
@@ -150,18 +205,41 @@ where
             tcp_listen: None,
             tcp_stream: HashMap::new(),
             tcp_handle: Vec::new(),
+            active: Arc::new(AtomicUsize::new(0)),
+            reported_active: 0,
+            exit: Arc::new(AtomicBool::new(false)),
         };
         ret.new_input::<TcpMessage<T>>("response").unwrap();
         ret.new_output::<TcpMessage<T>>("received").unwrap();
+        ret.new_output::<ConnectionEvent>("connection_events").unwrap();
         ret.new_statics::<u16>("port", 50000, None).unwrap();
         ret.new_statics::<String>("address", "0.0.0.0".to_string(), None).unwrap();
+        // Connections beyond this limit are closed as soon as they are accepted.
+        ret.new_statics::<usize>("max_connections", 64, Some([1, 4096])).unwrap();
+        // Idle time before the TCP keepalive probes, 0 to disable them.
+        ret.new_statics::<u64>("keepalive_secs", 0, None).unwrap();
+        // Heartbeat period of the clients, 0 if they send none. A client silent
+        // for HEARTBEAT_MISSES periods is dropped.
+        ret.new_statics::<f64>("heartbeat_secs", 0.0, Some([0.0, 3600.0])).unwrap();
+        ret.new_state::<usize>("active_connections", 0).unwrap();
         ret
     }
-    pub fn receiver_loop(handler: Arc<Mutex<TcpHandler<T>>>, logger_input: SyncSender<LogEntry>, name: &'static str) {
-        loop {
-            let exit = THREAD_EXIT.get().unwrap().lock().unwrap();
-            if *exit {
-                break;
+    /// Writes the number of client loops to active_connections when it has
+    /// changed since the last update. Cheap enough to be polled.
+    fn update_active_connections(&mut self) -> Result<(), StreamErrCode> {
+        let active = self.active.load(Ordering::SeqCst);
+        if active != self.reported_active {
+            self.set_state_value::<usize>("active_connections", active)?;
+            self.reported_active = active;
+        }
+        Ok(())
+    }
+    pub fn receiver_loop(handler: Arc<Mutex<TcpHandler<T>>>, logger_input: LogSender, name: &'static str, proc_state: Arc<Mutex<StreamingState>>,
+                         events: Output<ConnectionEvent>, exit: Arc<AtomicBool>) {
+        while !exit.load(Ordering::SeqCst) {
+            if *proc_state.lock().unwrap() == StreamingState::Paused {
+                thread::sleep(Duration::from_millis(10));
+                continue;
             }
             match handler.lock().unwrap().handle_stream() {
                 Ok(_) => {}
@@ -170,11 +248,17 @@ where
                         LogLevel::Error, 
                         name.to_string(),
                          e.clone());
-                    logger_input.send(log_entry).unwrap();
+                    let _ = logger_input.send(log_entry);
                     break;
                 }
             }
         }
+        let id = {
+            let handler = handler.lock().unwrap();
+            let _ = handler.stream.shutdown(Shutdown::Both);
+            handler.stream_id
+        };
+        let _ = events.send(ConnectionEvent::Disconnected { id });
     }
     pub fn send_answer(&self, message: TcpMessage<T>) -> Result<(), StreamErrCode> {
 
@@ -202,12 +286,14 @@ where T: 'static + Send + Clone
         let port = self.get_statics_value::<u16>("port").expect("");
         let address = self.get_statics_value::<String>("address").expect("");
         match TcpListener::bind(format!("{}:{}", address, port)) {
-            Ok(tcp_listen) => {self.tcp_listen = Some(tcp_listen);}
-            Err(_) => {
+            // Non blocking, so that the accept loop sees a stop request.
+            Ok(tcp_listen) if tcp_listen.set_nonblocking(true).is_ok() => {self.tcp_listen = Some(tcp_listen);}
+            _ => {
                 self.set_state(StreamingState::Stopped);
                 return Err(StreamErrCode::SendDataError);
             }
         }
+        self.exit.store(false, Ordering::SeqCst);
         self.set_state(StreamingState::Initial);
         Ok(())
     }
@@ -222,39 +308,278 @@ where T: 'static + Send + Clone
     }
     fn process(&mut self) -> Result<(), StreamErrCode > {
         let mut counter_stream: u32 = 0;
-        for stream in self.tcp_listen.as_ref().unwrap().incoming() {
-            if stream.is_ok() {
+        let max_connections = self.get_statics_value::<usize>("max_connections")?;
+        let keepalive_secs = self.get_statics_value::<u64>("keepalive_secs")?;
+        let heartbeat_secs = self.get_statics_value::<f64>("heartbeat_secs")?;
+        let idle_timeout = (heartbeat_secs > 0.0).then(|| Duration::from_secs_f64(heartbeat_secs * HEARTBEAT_MISSES));
+        // Owned by the accept loop, so that the port is closed as soon as it ends.
+        let listener = self.tcp_listen.take().ok_or(StreamErrCode::ReceiveDataError)?;
+        loop {
+            // The listener stays bound while paused: new connections wait to be handled.
+            while self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+            }
+            // Picks up the client loops that ended since the last pass.
+            self.update_active_connections()?;
+            let stream = match listener.accept() {
+                Ok((stream, addr)) => stream.set_nonblocking(false)
+                    .and_then(|_| stream.set_read_timeout(Some(READ_TIMEOUT)))
+                    .and_then(|_| set_keepalive(&stream, keepalive_secs))
+                    .map(|_| (stream, addr)),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                    if self.check_state(StreamingState::Stopped) {
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(10));
+                    continue;
+                }
+                Err(e) => Err(e),
+            };
+            if stream.is_ok() && self.active.load(Ordering::SeqCst) >= max_connections {
+                log!(self.logger, LogLevel::Warning, self.name, "Connection rejected: max_connections reached.");
+                continue;
+            }
+            if let Ok((stream, addr)) = stream {
                 counter_stream += 1;
                 log!(self.logger, LogLevel::Info, self.name, "New connection.");
                 let _lock = self.lock.lock().unwrap();
                 let output = self.get_output::<TcpMessage<T>>("received").expect("").clone();
+                let events = self.get_output::<ConnectionEvent>("connection_events").expect("").clone();
+                let _ = events.send(ConnectionEvent::Connected { id: counter_stream, addr });
                 let mut tm = TaskManager::get().lock().unwrap();
-                let tcp_handler = TcpHandler::new(counter_stream, stream.as_ref().unwrap().try_clone().unwrap(), output.clone());
+                let mut tcp_handler = TcpHandler::new(counter_stream, stream.try_clone().unwrap(), output.clone());
+                tcp_handler.idle_timeout = idle_timeout;
+                tcp_handler.exit = self.exit.clone();
                 let tcp_handler_arc = Arc::new(Mutex::new(tcp_handler));
                 self.tcp_stream.insert(counter_stream, tcp_handler_arc.clone());
                 let name = self.name;
-                let logger_input = self.logger.get_input("LogEntry").unwrap().sender.clone();
+                let logger_input = self.logger.get_log_sender()?;
+                let proc_state = self.proc_state.clone();
+                let active = self.active.clone();
+                let exit = self.exit.clone();
+                let task_events = events.clone();
+                active.fetch_add(1, Ordering::SeqCst);
                 let handle = tm.create_task(name, move || {
-                    Self::receiver_loop(tcp_handler_arc, logger_input, name);
+                    Self::receiver_loop(tcp_handler_arc, logger_input, name, proc_state, task_events, exit);
+                    active.fetch_sub(1, Ordering::SeqCst);
                 });
                 match handle {
                     Ok(handle) => {
                         self.tcp_handle.push(handle);
                     }
-                    Err(e) => {}
+                    Err(e) => {
+                        self.active.fetch_sub(1, Ordering::SeqCst);
+                        log!(self.logger, LogLevel::Error, self.name, format!("Unable to start the client task: {}", e));
+                        self.tcp_stream.remove(&counter_stream);
+                        let _ = stream.shutdown(Shutdown::Both);
+                        let _ = events.send(ConnectionEvent::Disconnected { id: counter_stream });
+                    }
                 }
             }
         }
         Ok(())
     }
     fn stop(&mut self) -> Result<(), StreamErrCode > {
-        THREAD_EXIT.get_or_init(|| Arc::new(Mutex::new(true)));
+        self.exit.store(true, Ordering::SeqCst);
+        // The listening socket was closed when process returned, or here if it
+        // never ran. The client loops see the exit flag within READ_TIMEOUT.
+        self.tcp_listen = None;
         for j in self.tcp_handle.drain(..) {
             let _ = j.join();
         }
+        self.update_active_connections()?;
         self.set_state(StreamingState::Stopped);
         Ok(())
     }
 }
 
-static THREAD_EXIT: OnceLock<Arc<Mutex<bool>>> = OnceLock::new();
\ No newline at end of file
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Instant;
+
+    fn new_receiver(name: &'static str, port: u16, max_connections: usize, heartbeat_secs: f64) -> TcpReceiver<u32> {
+        let mut receiver = TcpReceiver::<u32>::new(name);
+        receiver.set_statics_value::<u16>("port", port).unwrap();
+        receiver.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        receiver.set_statics_value::<usize>("max_connections", max_connections).unwrap();
+        receiver.set_statics_value::<u64>("keepalive_secs", 30).unwrap();
+        receiver.set_statics_value::<f64>("heartbeat_secs", heartbeat_secs).unwrap();
+        receiver
+    }
+
+    type RunHandle = JoinHandle<(TcpReceiver<u32>, Result<(), StreamErrCode>)>;
+
+    // Runs the receiver in a thread, which hands it back once stopped.
+    fn start_receiver(mut receiver: TcpReceiver<u32>) -> (Arc<Mutex<StreamingState>>, RunHandle) {
+        let state = receiver.get_state_handle();
+        let handle = thread::spawn(move || {
+            let ret = receiver.run();
+            (receiver, ret)
+        });
+        (state, handle)
+    }
+
+    #[test]
+    fn test_tcp_receiver_stop() {
+        let port: u16 = 50331;
+        let mut receiver = new_receiver("tcp_stop_test", port, 4, 0.0);
+        assert!(receiver.init().is_ok());
+        let (state, handle) = start_receiver(receiver);
+        // An idle client, whose handler waits in read.
+        let _client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        // A client whose handler waits for an answer to its payload.
+        let mut sender = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        sender.write_all(&7u32.to_ne_bytes()).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        *state.lock().unwrap() = StreamingState::Stopped;
+        let (mut receiver, ret) = handle.join().unwrap();
+        assert!(ret.is_ok());
+        // The port is closed once the accept loop is over, before stop.
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+        assert_eq!(receiver.tcp_handle.len(), 2);
+        let start = Instant::now();
+        assert!(receiver.stop().is_ok());
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(receiver.tcp_handle.is_empty());
+        assert!(TcpStream::connect(("127.0.0.1", port)).is_err());
+    }
+
+    #[test]
+    fn test_tcp_receiver_restart() {
+        let port: u16 = 50332;
+        let mut receiver = new_receiver("tcp_restart_test", port, 4, 0.0);
+        // The flag set by a stop is cleared by the next init, then set again by the next stop.
+        for _ in 0..2 {
+            assert!(receiver.init().is_ok());
+            assert!(!receiver.exit.load(Ordering::SeqCst));
+            let (state, handle) = start_receiver(receiver);
+            let _client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+            thread::sleep(Duration::from_millis(100));
+            *state.lock().unwrap() = StreamingState::Stopped;
+            let (stopped, ret) = handle.join().unwrap();
+            receiver = stopped;
+            assert!(ret.is_ok());
+            let start = Instant::now();
+            assert!(receiver.stop().is_ok());
+            assert!(receiver.exit.load(Ordering::SeqCst));
+            assert!(start.elapsed() < Duration::from_secs(1));
+        }
+    }
+    #[test]
+    fn test_tcp_receiver_independent_stop() {
+        let mut first = new_receiver("tcp_first_test", 50338, 4, 0.0);
+        let mut second = new_receiver("tcp_second_test", 50339, 4, 0.0);
+        assert!(first.init().is_ok());
+        assert!(second.init().is_ok());
+        let (first_state, first_handle) = start_receiver(first);
+        let (second_state, second_handle) = start_receiver(second);
+        let mut first_client = TcpStream::connect(("127.0.0.1", 50338)).unwrap();
+        let mut second_client = TcpStream::connect(("127.0.0.1", 50339)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+
+        *first_state.lock().unwrap() = StreamingState::Stopped;
+        let (mut first, ret) = first_handle.join().unwrap();
+        assert!(ret.is_ok());
+        assert!(first.stop().is_ok());
+        first_client.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(first_client.read(&mut [0u8; 4]).unwrap(), 0);
+        // Stopping the first receiver leaves the clients of the second one alone.
+        second_client.set_read_timeout(Some(Duration::from_millis(300))).unwrap();
+        let kind = second_client.read(&mut [0u8; 4]).unwrap_err().kind();
+        assert!(kind == ErrorKind::WouldBlock || kind == ErrorKind::TimedOut);
+
+        *second_state.lock().unwrap() = StreamingState::Stopped;
+        let (mut second, ret) = second_handle.join().unwrap();
+        assert!(ret.is_ok());
+        assert!(second.stop().is_ok());
+    }
+    #[test]
+    fn test_tcp_receiver_max_connections() {
+        let port: u16 = 50333;
+        let mut receiver = new_receiver("tcp_max_connections_test", port, 1, 0.0);
+        assert!(receiver.init().is_ok());
+        let (state, handle) = start_receiver(receiver);
+        let first = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        // The second connection is closed by the receiver.
+        let mut rejected = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        rejected.set_read_timeout(Some(Duration::from_secs(1))).unwrap();
+        assert_eq!(rejected.read(&mut [0u8; 4]).unwrap(), 0);
+
+        // Once the first client leaves, a new one is served.
+        drop(first);
+        thread::sleep(Duration::from_millis(300));
+        let mut accepted = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        accepted.set_read_timeout(Some(Duration::from_millis(200))).unwrap();
+        let kind = accepted.read(&mut [0u8; 4]).unwrap_err().kind();
+        assert!(kind == ErrorKind::WouldBlock || kind == ErrorKind::TimedOut);
+
+        *state.lock().unwrap() = StreamingState::Stopped;
+        let (mut receiver, ret) = handle.join().unwrap();
+        assert!(ret.is_ok());
+        assert_eq!(receiver.get_state_value::<usize>("active_connections"), Ok(1));
+        assert!(receiver.stop().is_ok());
+        assert_eq!(receiver.get_state_value::<usize>("active_connections"), Ok(0));
+    }
+    #[test]
+    fn test_tcp_receiver_connection_events() {
+        let port: u16 = 50334;
+        let mut receiver = new_receiver("tcp_events_test", port, 4, 0.0);
+        let (event_sender, event_receiver) = std::sync::mpsc::sync_channel::<ConnectionEvent>(10);
+        receiver.connect("connection_events", event_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        let (state, handle) = start_receiver(receiver);
+
+        let client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let addr = client.local_addr().unwrap();
+        let timeout = Duration::from_secs(1);
+        assert_eq!(event_receiver.recv_timeout(timeout).unwrap(), ConnectionEvent::Connected { id: 1, addr });
+        drop(client);
+        assert_eq!(event_receiver.recv_timeout(timeout).unwrap(), ConnectionEvent::Disconnected { id: 1 });
+
+        // A client still connected at stop is reported as disconnected too.
+        let client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        let addr = client.local_addr().unwrap();
+        assert_eq!(event_receiver.recv_timeout(timeout).unwrap(), ConnectionEvent::Connected { id: 2, addr });
+        *state.lock().unwrap() = StreamingState::Stopped;
+        let (mut receiver, ret) = handle.join().unwrap();
+        assert!(ret.is_ok());
+        assert!(receiver.stop().is_ok());
+        assert_eq!(event_receiver.try_recv().unwrap(), ConnectionEvent::Disconnected { id: 2 });
+        assert!(event_receiver.try_recv().is_err());
+    }
+    #[test]
+    fn test_tcp_receiver_heartbeat() {
+        let port: u16 = 50335;
+        let mut receiver = new_receiver("tcp_heartbeat_test", port, 4, 0.1);
+        let (event_sender, event_receiver) = std::sync::mpsc::sync_channel::<ConnectionEvent>(10);
+        receiver.connect("connection_events", event_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        let (state, handle) = start_receiver(receiver);
+
+        let mut client = TcpStream::connect(("127.0.0.1", port)).unwrap();
+        client.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let timeout = Duration::from_secs(1);
+        assert!(matches!(event_receiver.recv_timeout(timeout).unwrap(), ConnectionEvent::Connected { id: 1, .. }));
+        let mut buffer = [0u8; 64];
+        for _ in 0..5 {
+            client.write_all(HEARTBEAT).unwrap();
+            let n = client.read(&mut buffer).unwrap();
+            assert_eq!(&buffer[0..n], HEARTBEAT);
+            thread::sleep(Duration::from_millis(100));
+        }
+        // Beyond HEARTBEAT_MISSES periods of silence the client is dropped.
+        assert!(event_receiver.try_recv().is_err());
+        let start = Instant::now();
+        assert_eq!(event_receiver.recv_timeout(Duration::from_secs(2)).unwrap(), ConnectionEvent::Disconnected { id: 1 });
+        assert!(start.elapsed() >= Duration::from_millis(150));
+        assert_eq!(client.read(&mut buffer).unwrap(), 0);
+
+        *state.lock().unwrap() = StreamingState::Stopped;
+        let (mut receiver, ret) = handle.join().unwrap();
+        assert!(ret.is_ok());
+        assert!(receiver.stop().is_ok());
+    }
+}
@@ -5,8 +5,10 @@ use std::io::{Read, Write};
 use std::marker::PhantomData;
 use std::mem;
 use std::net::TcpStream;
+use std::os::fd::AsRawFd;
 use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
 use data_model::streaming_data::{StreamErrCode, StreamingState};
@@ -26,6 +28,36 @@ pub fn as_byte<T>(value: &T) -> &[u8] {
     }
 }
 
+/// Message sent by a TcpSender when no data has flowed for `heartbeat_secs`,
+/// and echoed by the TcpReceiver.
+pub const HEARTBEAT: &[u8] = b"\0heartbeat\0";
+
+// Input polling period while waiting to send a heartbeat.
+const HEARTBEAT_POLL: Duration = Duration::from_millis(100);
+
+fn set_socket_option(stream: &TcpStream, level: libc::c_int, option: libc::c_int, value: libc::c_int) -> std::io::Result<()> {
+    let ret = unsafe {
+        libc::setsockopt(stream.as_raw_fd(), level, option,
+            &value as *const libc::c_int as *const libc::c_void, mem::size_of::<libc::c_int>() as libc::socklen_t)
+    };
+    if ret < 0 { Err(std::io::Error::last_os_error()) } else { Ok(()) }
+}
+
+/// Enables the TCP keepalive probes of `stream` after `secs` seconds of
+/// silence, repeated every `secs` seconds. 0 leaves the keepalive disabled.
+pub fn set_keepalive(stream: &TcpStream, secs: u64) -> std::io::Result<()> {
+    if secs == 0 {
+        return Ok(());
+    }
+    let secs = secs.min(libc::c_int::MAX as u64) as libc::c_int;
+    set_socket_option(stream, libc::SOL_SOCKET, libc::SO_KEEPALIVE, 1)?;
+    #[cfg(target_os = "linux")]
+    set_socket_option(stream, libc::IPPROTO_TCP, libc::TCP_KEEPIDLE, secs)?;
+    #[cfg(target_os = "macos")]
+    set_socket_option(stream, libc::IPPROTO_TCP, libc::TCP_KEEPALIVE, secs)?;
+    set_socket_option(stream, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL, secs)
+}
+
 #[derive(StreamBlockMacro)]
 pub struct TcpSender<T: 'static + Send + Clone> {
     name:       &'static str,
@@ -38,6 +70,9 @@ pub struct TcpSender<T: 'static + Send + Clone> {
     proc_state: Arc<Mutex<StreamingState>>,
     phantom:    PhantomData<T>,
     tcp_stream: Option<TcpStream>,
+    last_activity: Instant,
+    // heartbeat_secs, read by init.
+    heartbeat:  Duration,
 }
 
 impl<T> TcpSender<T> 
@@ -56,12 +91,38 @@ where
             proc_state: Arc::new(Mutex::new(StreamingState::Null)),
             phantom: PhantomData,
             tcp_stream: None,
+            last_activity: Instant::now(),
+            heartbeat: Duration::ZERO,
         };
         ret.new_input::<T>("input").unwrap();
         ret.new_statics::<u16>("port", 50000, None).unwrap();
         ret.new_statics::<String>("address", "0.0.0.0".to_string(), None).unwrap();
+        // Idle time before the TCP keepalive probes, 0 to disable them.
+        ret.new_statics::<u64>("keepalive_secs", 0, None).unwrap();
+        // Idle time before a HEARTBEAT is sent, 0 to disable it. It also bounds
+        // the wait for an answer, beyond which the peer is considered dead.
+        ret.new_statics::<f64>("heartbeat_secs", 0.0, Some([0.0, 3600.0])).unwrap();
         ret
     }
+    /// Writes `message` and checks that the peer answers `expected`. No
+    /// answer within the heartbeat period counts as a wrong one.
+    fn exchange(&mut self, message: &[u8], expected: &[u8]) -> Result<(), StreamErrCode> {
+        let stream = self.tcp_stream.as_mut().ok_or(StreamErrCode::SendDataError)?;
+        let answered = {
+            let _lock = self.lock.lock().unwrap();
+            let mut buffer = [0; 65535];
+            match stream.write(message).and_then(|_| stream.read(&mut buffer)) {
+                Ok(n) => &buffer[0..n] == expected,
+                Err(_) => false,
+            }
+        };
+        if !answered {
+            self.set_state(StreamingState::Stopped);
+            return Err(StreamErrCode::SendDataError);
+        }
+        self.last_activity = Instant::now();
+        Ok(())
+    }
 }
 
 impl<T> StreamProcessor for TcpSender<T> 
@@ -77,9 +138,16 @@ where T: 'static + Send + Clone
         }
         let port = self.get_statics_value::<u16>("port").expect("");
         let address = self.get_statics_value::<String>("address").expect("");
+        let keepalive_secs = self.get_statics_value::<u64>("keepalive_secs")?;
+        self.heartbeat = Duration::from_secs_f64(self.get_statics_value::<f64>("heartbeat_secs")?);
+        let read_timeout = (!self.heartbeat.is_zero()).then_some(self.heartbeat);
         match TcpStream::connect(format!("{}:{}", address, port)) {
-            Ok(tcp_stream) => {self.tcp_stream = Some(tcp_stream);}
-            Err(_) => {
+            Ok(tcp_stream) if set_keepalive(&tcp_stream, keepalive_secs).is_ok()
+                && tcp_stream.set_read_timeout(read_timeout).is_ok() => {
+                self.tcp_stream = Some(tcp_stream);
+                self.last_activity = Instant::now();
+            }
+            _ => {
                 self.set_state(StreamingState::Stopped);
                 return Err(StreamErrCode::SendDataError);
             }
@@ -92,35 +160,106 @@ where T: 'static + Send + Clone
             self.set_state(StreamingState::Stopped);
             return Err(StreamErrCode::SendDataError);
         }
-        match self.recv_input::<T>("input") {
-            Ok(input) => {
-                let error_send: bool;
-                let stream = self.tcp_stream.as_mut().unwrap();
-                {               
-                    let _lock = self.lock.lock().unwrap();
-                    match stream.write(as_byte::<T>(&input)) {
-                        Ok(_) => {
-                            let mut buffer = [0; 65535];
-                            match stream.read(&mut buffer) {
-                                Ok(n) => {
-                                    let response = String::from_utf8_lossy(&buffer[0..n]);
-                                    if response != "Ok" {
-                                        error_send = true;
-                                    } else {error_send = false;}
-                                }
-                                Err(_) => {error_send = true;}
-                            }
-                        }
-                        Err(_) => {error_send = true;}
-                    };
-                }                
-                if error_send {
-                    self.set_state(StreamingState::Stopped);
-                    return Err(StreamErrCode::SendDataError);
+        let input = if self.heartbeat.is_zero() {
+            self.recv_input::<T>("input")?
+        } else {
+            // Looked up once, as a qualified name is allocated at each lookup.
+            let key = self.get_qualified_name("input");
+            loop {
+                let input = self.inputs.get_mut(key)
+                    .and_then(|container| container.as_mut().as_any_mut().downcast_mut::<Input<T>>())
+                    .ok_or(StreamErrCode::InvalidInput)?;
+                if let Some(input) = input.recv_timeout(HEARTBEAT_POLL)? {
+                    break input;
+                }
+                if self.check_state(StreamingState::Stopped) {
+                    return Err(StreamErrCode::ReceiveDataError);
+                }
+                if self.last_activity.elapsed() >= self.heartbeat {
+                    self.exchange(HEARTBEAT, HEARTBEAT)?;
                 }
             }
-            Err(e) => {return Err(e);}
-        }
-        Ok(())
+        };
+        self.exchange(as_byte::<T>(&input), b"Ok")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn socket_option(stream: &TcpStream, level: libc::c_int, option: libc::c_int) -> libc::c_int {
+        let mut value: libc::c_int = 0;
+        let mut len = mem::size_of::<libc::c_int>() as libc::socklen_t;
+        let ret = unsafe {
+            libc::getsockopt(stream.as_raw_fd(), level, option, &mut value as *mut libc::c_int as *mut libc::c_void, &mut len)
+        };
+        assert_eq!(ret, 0);
+        value
+    }
+
+    #[test]
+    fn test_tcp_sender_heartbeat() {
+        let port: u16 = 50336;
+        let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+        let mut sender = TcpSender::<u32>::new("tcp_heartbeat_sender_test");
+        sender.set_statics_value::<u16>("port", port).unwrap();
+        sender.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        sender.set_statics_value::<u64>("keepalive_secs", 7).unwrap();
+        sender.set_statics_value::<f64>("heartbeat_secs", 0.1).unwrap();
+        assert!(sender.init().is_ok());
+        let stream = sender.tcp_stream.as_ref().unwrap();
+        assert_ne!(socket_option(stream, libc::SOL_SOCKET, libc::SO_KEEPALIVE), 0);
+        assert_eq!(socket_option(stream, libc::IPPROTO_TCP, libc::TCP_KEEPINTVL), 7);
+        let (mut server, _) = listener.accept().unwrap();
+        server.set_read_timeout(Some(Duration::from_secs(2))).unwrap();
+        let input = sender.get_input_channel::<u32>("input").unwrap();
+        let handle = thread::spawn(move || {
+            let ret = sender.process();
+            (sender, ret)
+        });
+
+        // Without input, a heartbeat is sent and has to be echoed.
+        let mut buffer = [0u8; 64];
+        let n = server.read(&mut buffer).unwrap();
+        assert_eq!(&buffer[0..n], HEARTBEAT);
+        server.write_all(HEARTBEAT).unwrap();
+        input.send(7).unwrap();
+        let n = server.read(&mut buffer).unwrap();
+        assert_eq!(&buffer[0..n], 7u32.to_ne_bytes());
+        server.write_all(b"Ok").unwrap();
+        let (mut sender, ret) = handle.join().unwrap();
+        assert!(ret.is_ok());
+
+        // A wrong answer to the heartbeat means a dead peer.
+        let handle = thread::spawn(move || sender.process());
+        let n = server.read(&mut buffer).unwrap();
+        assert_eq!(&buffer[0..n], HEARTBEAT);
+        server.write_all(b"??").unwrap();
+        assert_eq!(handle.join().unwrap(), Err(StreamErrCode::SendDataError));
+    }
+
+    #[test]
+    fn test_tcp_sender_silent_peer() {
+        let port: u16 = 50340;
+        let listener = TcpListener::bind(("127.0.0.1", port)).unwrap();
+        let mut sender = TcpSender::<u32>::new("tcp_silent_sender_test");
+        sender.set_statics_value::<u16>("port", port).unwrap();
+        sender.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        sender.set_statics_value::<u64>("keepalive_secs", 0).unwrap();
+        sender.set_statics_value::<f64>("heartbeat_secs", 0.1).unwrap();
+        assert!(sender.init().is_ok());
+        // The peer reads the data but never answers.
+        let (mut server, _) = listener.accept().unwrap();
+        sender.get_input_channel::<u32>("input").unwrap().send(7).unwrap();
+        let start = Instant::now();
+        assert_eq!(sender.process(), Err(StreamErrCode::SendDataError));
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(sender.check_state(StreamingState::Stopped));
+        let mut buffer = [0u8; 4];
+        server.read_exact(&mut buffer).unwrap();
+        assert_eq!(buffer, 7u32.to_ne_bytes());
     }
-}
\ No newline at end of file
+}
@@ -1,10 +1,15 @@
 use std::collections::HashMap;
 use std::any::Any;
 use std::fmt::Debug;
+use std::io::ErrorKind;
 use std::marker::PhantomData;
+use std::mem;
 use std::net::{Ipv4Addr, UdpSocket};
+use std::os::fd::FromRawFd;
 use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
 use data_model::streaming_data::{StreamErrCode, StreamingState};
@@ -14,6 +19,89 @@ use data_model::connectors::{ConnectorTrait, Input, Output};
 
 use crate::tcp_receiver::from_bytes;
 
+pub fn bind_udp_socket(port: u16, reuse_address: bool, reuse_port: bool) -> std::io::Result<UdpSocket> {
+    if !reuse_address && !reuse_port {
+        return UdpSocket::bind(format!("0.0.0.0:{}", port));
+    }
+    // std binds the socket on creation, so the reuse options must be set on a raw socket first.
+    let fd = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM, 0) };
+    if fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let socket = unsafe { UdpSocket::from_raw_fd(fd) };
+    let enable: libc::c_int = 1;
+    let options = [(reuse_address, libc::SO_REUSEADDR), (reuse_port, libc::SO_REUSEPORT)];
+    for (enabled, option) in options {
+        if !enabled {
+            continue;
+        }
+        let ret = unsafe {
+            libc::setsockopt(fd,
+                             libc::SOL_SOCKET,
+                             option,
+                             &enable as *const libc::c_int as *const libc::c_void,
+                             mem::size_of::<libc::c_int>() as libc::socklen_t)
+        };
+        if ret != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+    }
+    let address = libc::sockaddr_in {
+        sin_family: libc::AF_INET as libc::sa_family_t,
+        sin_port: port.to_be(),
+        sin_addr: libc::in_addr { s_addr: libc::INADDR_ANY },
+        sin_zero: [0; 8],
+    };
+    let ret = unsafe {
+        libc::bind(fd,
+                   &address as *const libc::sockaddr_in as *const libc::sockaddr,
+                   mem::size_of::<libc::sockaddr_in>() as libc::socklen_t)
+    };
+    if ret != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(socket)
+}
+
+/// Puts back in order payloads carrying a sequence number, holding at most
+/// `window` of them while waiting for a missing one.
+#[derive(Default)]
+struct Reorderer {
+    next:    Option<u32>,
+    pending: HashMap<u32, Vec<u8>>,
+}
+
+impl Reorderer {
+    /// Accepts a payload and returns the ones now in order, with the count of
+    /// sequence numbers given up as lost. Late and duplicate payloads are
+    /// discarded. Sequence numbers may wrap around.
+    fn push(&mut self, sequence: u32, payload: Vec<u8>, window: usize) -> (Vec<Vec<u8>>, u64) {
+        let next = *self.next.get_or_insert(sequence);
+        if (sequence.wrapping_sub(next) as i32) < 0 {
+            return (Vec::new(), 0);
+        }
+        self.pending.insert(sequence, payload);
+        let mut ready = Vec::new();
+        let mut dropped = 0;
+        loop {
+            let mut next = self.next.unwrap();
+            while let Some(payload) = self.pending.remove(&next) {
+                ready.push(payload);
+                next = next.wrapping_add(1);
+            }
+            self.next = Some(next);
+            if self.pending.len() <= window {
+                break;
+            }
+            // The window is full: skip to the oldest payload held.
+            let oldest = *self.pending.keys().min_by_key(|k| k.wrapping_sub(next)).unwrap();
+            dropped += oldest.wrapping_sub(next) as u64;
+            self.next = Some(oldest);
+        }
+        (ready, dropped)
+    }
+}
+
 #[derive(StreamBlockMacro)]
 pub struct UdpReceiver<T: 'static + Send + Clone> {
     name:       &'static str,
@@ -26,6 +114,7 @@ pub struct UdpReceiver<T: 'static + Send + Clone> {
     proc_state: Arc<Mutex<StreamingState>>,
     phantom:    PhantomData<T>,
     socket:    Option<UdpSocket>,
+    reorderer:  Reorderer,
 }
 impl<T> UdpReceiver<T> where T: 'static + Send + Clone {
     pub fn new(name: &'static str) -> Self {
@@ -40,12 +129,64 @@ impl<T> UdpReceiver<T> where T: 'static + Send + Clone {
             proc_state: Arc::new(Mutex::new(StreamingState::Null)),
             phantom: PhantomData,
             socket: None,
+            reorderer: Reorderer::default(),
         };
         ret.new_output::<T>("output").unwrap();
         ret.new_statics::<u16>("port", 50000, None).unwrap();
         ret.new_statics::<String>("address", "0.0.0.0".to_string(), None).unwrap();
+        ret.new_statics::<bool>("reuse_address", false, None).unwrap();
+        ret.new_statics::<bool>("reuse_port", false, None).unwrap();
+        ret.new_statics::<u32>("multicast_ttl", 1, Some([0, 255])).unwrap();
+        ret.new_statics::<bool>("multicast_loopback", true, None).unwrap();
+        // Sequenced datagrams start with a little-endian u32 sequence number.
+        ret.new_statics::<bool>("sequenced", false, None).unwrap();
+        ret.new_parameter::<usize>("reorder_window", 0, Some([0, 1024])).unwrap();
+        ret.new_state::<u64>("dropped_count", 0).unwrap();
+        ret.new_parameter::<bool>("reconnect", true, None).unwrap();
+        ret.new_parameter::<u64>("reconnect_delay_ms", 1000, None).unwrap();
+        ret.new_parameter::<u64>("health_check_ms", 1000, Some([1, 60000])).unwrap();
         ret
     }
+    fn open_socket(&mut self) -> Result<(), StreamErrCode> {
+        let port = self.get_statics_value::<u16>("port")?;
+        let address = self.get_statics_value::<String>("address")?;
+        let reuse_address = self.get_statics_value::<bool>("reuse_address")?;
+        let reuse_port = self.get_statics_value::<bool>("reuse_port")?;
+        let health_check = self.get_parameter_value::<u64>("health_check_ms")?;
+        let socket = bind_udp_socket(port, reuse_address, reuse_port)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        let dest_addr: Ipv4Addr = address.parse()
+            .map_err(|_| StreamErrCode::InvalidStatics)?;
+        let interface: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
+        if dest_addr.is_multicast() {
+            socket.join_multicast_v4(&dest_addr, &interface)
+                .map_err(|_| StreamErrCode::CreateError)?;
+        }
+        socket.set_multicast_ttl_v4(self.get_statics_value::<u32>("multicast_ttl")?)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        socket.set_multicast_loop_v4(self.get_statics_value::<bool>("multicast_loopback")?)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        // The read timeout bounds the time between two health checks of the socket.
+        socket.set_read_timeout(Some(Duration::from_millis(health_check)))
+            .map_err(|_| StreamErrCode::CreateError)?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+    fn forward(&mut self, payload: &[u8]) -> Result<(), StreamErrCode> {
+        let message = unsafe{from_bytes::<T>(payload)}
+            .map_err(|_| StreamErrCode::ReceiveDataError)?;
+        self.send_output::<T>("output", message.clone())
+    }
+    fn reconnect(&mut self) -> Result<(), StreamErrCode> {
+        self.socket = None;
+        if !self.get_parameter_value::<bool>("reconnect")? {
+            return Err(StreamErrCode::ReceiveDataError);
+        }
+        thread::sleep(Duration::from_millis(self.get_parameter_value::<u64>("reconnect_delay_ms")?));
+        // A failed rebind is retried on the next process() call.
+        let _ = self.open_socket();
+        Ok(())
+    }
 }
 impl<T> StreamProcessor for UdpReceiver<T> where T: 'static + Send + Clone {
     fn init(&mut self) -> Result<(), StreamErrCode> {
@@ -63,35 +204,61 @@ impl<T> StreamProcessor for UdpReceiver<T> where T: 'static + Send + Clone {
             return Err(StreamErrCode::InvalidStateTransition);
         }
         self.set_state(StreamingState::Running);
-        let port = self.get_statics_value::<u16>("port")?;
-        let address = self.get_statics_value::<String>("address")?;
-        let socket = UdpSocket::bind(format!("0.0.0.0:{}", port))
-            .map_err(|_| StreamErrCode::CreateError)?;
-        let dest_addr: Ipv4Addr = address.parse()
-            .map_err(|_| StreamErrCode::InvalidStatics)?;
-        let interface: Ipv4Addr = Ipv4Addr::new(0, 0, 0, 0);
-        if dest_addr.is_multicast() {
-            socket.join_multicast_v4(&dest_addr, &interface)
-                .map_err(|_| StreamErrCode::CreateError)?;
-        }
-        self.socket = Some(socket);
+        self.open_socket()?;
         while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process() {
+                // A stop request interrupts a pending receive with an error.
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
         }
         Ok(())
     }
     fn process(&mut self) -> Result<(), StreamErrCode> {
-        if let Some(socket) = &self.socket {
+        let received = if let Some(socket) = &self.socket {
             let mut buf = vec![0u8; 65536];
-            let (amt, _src) = socket.recv_from(&mut buf)
-                .map_err(|_| StreamErrCode::ReceiveDataError)?;
-            buf.truncate(amt);
-            let message = unsafe{from_bytes::<T>(&buf[0..amt])}
-                .map_err(|_| StreamErrCode::ReceiveDataError)?;
-            self.send_output::<T>("output", message.clone())?;
-            Ok(())
+            match socket.recv_from(&mut buf) {
+                Ok((amt, _src)) => {
+                    buf.truncate(amt);
+                    Some(buf)
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock || e.kind() == ErrorKind::TimedOut => {
+                    // Idle socket: check for pending errors (e.g. interface gone down).
+                    match socket.take_error() {
+                        Ok(None) => {return Ok(());}
+                        _ => None,
+                    }
+                }
+                Err(_) => None,
+            }
         } else {
-            Err(StreamErrCode::FileNotFound)
+            None
+        };
+        match received {
+            Some(buf) if self.get_statics_value::<bool>("sequenced")? => {
+                if buf.len() < 4 {
+                    return Err(StreamErrCode::ReceiveDataError);
+                }
+                let sequence = u32::from_le_bytes(buf[..4].try_into().unwrap());
+                let window = self.get_parameter_value::<usize>("reorder_window")?;
+                let (ready, dropped) = self.reorderer.push(sequence, buf[4..].to_vec(), window);
+                if dropped > 0 {
+                    let total = self.get_state_value::<u64>("dropped_count")? + dropped;
+                    self.set_state_value::<u64>("dropped_count", total)?;
+                }
+                for payload in ready {
+                    self.forward(&payload)?;
+                }
+                Ok(())
+            }
+            Some(buf) => self.forward(&buf),
+            None => self.reconnect(),
         }
     }
     fn stop(&mut self) -> Result<(), StreamErrCode> {
@@ -99,3 +266,88 @@ impl<T> StreamProcessor for UdpReceiver<T> where T: 'static + Send + Clone {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_udp_receiver_reconnect() {
+        let port: u16 = 50321;
+        let mut receiver = UdpReceiver::<u32>::new("udp_reconnect_test");
+        receiver.set_statics_value::<u16>("port", port).unwrap();
+        receiver.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        receiver.set_statics_value::<bool>("reuse_address", true).unwrap();
+        receiver.set_statics_value::<bool>("reuse_port", false).unwrap();
+        receiver.set_statics_value::<u32>("multicast_ttl", 2).unwrap();
+        receiver.set_statics_value::<bool>("multicast_loopback", true).unwrap();
+        receiver.set_statics_value::<bool>("sequenced", false).unwrap();
+        receiver.set_parameter_value::<u64>("reconnect_delay_ms", 10).unwrap();
+        receiver.set_parameter_value::<u64>("health_check_ms", 100).unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        receiver.connect("output", out_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        receiver.open_socket().unwrap();
+        assert_eq!(receiver.socket.as_ref().unwrap().multicast_ttl_v4().unwrap(), 2);
+
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        client.send_to(&7u32.to_ne_bytes(), ("127.0.0.1", port)).unwrap();
+        receiver.process().unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 7);
+
+        // Break the socket for real: a datagram to a closed port makes the
+        // next receive fail with ConnectionRefused, and process() rebinds.
+        let closed = UdpSocket::bind("127.0.0.1:0").unwrap();
+        let closed_port = closed.local_addr().unwrap().port();
+        drop(closed);
+        let socket = receiver.socket.as_ref().unwrap();
+        socket.connect(("127.0.0.1", closed_port)).unwrap();
+        socket.send(&[0]).unwrap();
+        thread::sleep(Duration::from_millis(20));
+        receiver.process().unwrap();
+        // The rebound socket is a new, unconnected one.
+        assert_eq!(receiver.socket.as_ref().unwrap().peer_addr().unwrap_err().kind(), ErrorKind::NotConnected);
+        client.send_to(&11u32.to_ne_bytes(), ("127.0.0.1", port)).unwrap();
+        receiver.process().unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 11);
+    }
+
+    #[test]
+    fn test_udp_receiver_sequenced() {
+        let port: u16 = 50322;
+        let mut receiver = UdpReceiver::<u32>::new("udp_sequenced_test");
+        receiver.set_statics_value::<u16>("port", port).unwrap();
+        receiver.set_statics_value::<String>("address", "127.0.0.1".to_string()).unwrap();
+        receiver.set_statics_value::<bool>("reuse_address", true).unwrap();
+        receiver.set_statics_value::<bool>("reuse_port", false).unwrap();
+        receiver.set_statics_value::<u32>("multicast_ttl", 1).unwrap();
+        receiver.set_statics_value::<bool>("multicast_loopback", true).unwrap();
+        receiver.set_statics_value::<bool>("sequenced", true).unwrap();
+        receiver.set_parameter_value::<usize>("reorder_window", 2).unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        receiver.connect("output", out_sender).unwrap();
+        assert!(receiver.init().is_ok());
+        receiver.open_socket().unwrap();
+
+        let client = UdpSocket::bind("127.0.0.1:0").unwrap();
+        // 1 arrives late, 3 and 4 are lost, 0 is duplicated.
+        for sequence in [0u32, 2, 1, 0, 5, 6, 7] {
+            let mut datagram = sequence.to_le_bytes().to_vec();
+            datagram.extend_from_slice(&(sequence * 10).to_ne_bytes());
+            client.send_to(&datagram, ("127.0.0.1", port)).unwrap();
+            receiver.process().unwrap();
+        }
+        let received: Vec<u32> = out_receiver.try_iter().collect();
+        assert_eq!(received, vec![0, 10, 20, 50, 60, 70]);
+        assert_eq!(receiver.get_state_value::<u64>("dropped_count").unwrap(), 2);
+    }
+
+    #[test]
+    fn test_reorderer_wraps() {
+        let mut reorderer = Reorderer::default();
+        assert_eq!(reorderer.push(u32::MAX, vec![1], 1), (vec![vec![1]], 0));
+        assert_eq!(reorderer.push(1, vec![3], 1), (vec![], 0));
+        assert_eq!(reorderer.push(0, vec![2], 1), (vec![vec![2], vec![3]], 0));
+        assert_eq!(reorderer.push(u32::MAX, vec![1], 1), (vec![], 0));
+    }
+}
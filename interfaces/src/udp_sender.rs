@@ -5,6 +5,8 @@ use std::marker::PhantomData;
 use std::net::UdpSocket;
 use std::sync::mpsc::SyncSender;
 use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
 use data_model::streaming_data::{StreamErrCode, StreamingState};
@@ -44,8 +46,34 @@ impl<T> UdpSender<T> where T: 'static + Send + Clone {
         ret.new_input::<T>("input").unwrap();
         ret.new_statics::<u16>("port", 50000, None).unwrap();
         ret.new_statics::<String>("address", "0.0.0.0".to_string(), None).unwrap();
+        ret.new_statics::<u32>("multicast_ttl", 1, Some([0, 255])).unwrap();
+        ret.new_statics::<bool>("multicast_loopback", true, None).unwrap();
+        ret.new_parameter::<bool>("reconnect", true, None).unwrap();
+        ret.new_parameter::<u64>("reconnect_delay_ms", 1000, None).unwrap();
         ret
     }
+    fn open_socket(&mut self) -> Result<(), StreamErrCode> {
+        let port = self.get_statics_value::<u16>("port")?;
+        let address = self.get_statics_value::<String>("address")?;
+        let socket = UdpSocket::bind("0.0.0.0:0")
+            .map_err(|_| StreamErrCode::CreateError)?;
+        socket.set_multicast_ttl_v4(self.get_statics_value::<u32>("multicast_ttl")?)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        socket.set_multicast_loop_v4(self.get_statics_value::<bool>("multicast_loopback")?)
+            .map_err(|_| StreamErrCode::CreateError)?;
+        socket.connect(format!("{}:{}", address, port))
+            .map_err(|_| StreamErrCode::CreateError)?;
+        self.socket = Some(socket);
+        Ok(())
+    }
+    fn reconnect(&mut self) -> Result<(), StreamErrCode> {
+        self.socket = None;
+        if !self.get_parameter_value::<bool>("reconnect")? {
+            return Err(StreamErrCode::SendDataError);
+        }
+        thread::sleep(Duration::from_millis(self.get_parameter_value::<u64>("reconnect_delay_ms")?));
+        self.open_socket()
+    }
 }
 impl<T> StreamProcessor for UdpSender<T> where T: 'static + Send + Clone {
     fn init(&mut self) -> Result<(), StreamErrCode> {
@@ -63,32 +91,66 @@ impl<T> StreamProcessor for UdpSender<T> where T: 'static + Send + Clone {
             return Err(StreamErrCode::InvalidStateTransition);
         }
         self.set_state(StreamingState::Running);
-        let port = self.get_statics_value::<u16>("port")?;
-        let address = self.get_statics_value::<String>("address")?;
-        let socket = UdpSocket::bind("0.0.0.0:0")
-            .map_err(|_| StreamErrCode::CreateError)?;
-        socket.connect(format!("{}:{}", address, port))
-            .map_err(|_| StreamErrCode::CreateError)?;
-        self.socket = Some(socket);
-        while self.check_state(StreamingState::Running) {
-            self.process()?;
+        self.open_socket()?;
+        while !self.check_state(StreamingState::Stopped) {
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process() {
+                // A stop request interrupts a pending receive with an error.
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
         }
         Ok(())
     }
     fn process(&mut self) -> Result<(), StreamErrCode> {
         let input = self.recv_input::<T>("input")?;
-        if let Some(socket) = &self.socket {
-            let bytes = as_byte::<T>(&input);
-            if socket.send(&bytes).map_err(|_| StreamErrCode::SendDataError).is_err() {
-                return Err(StreamErrCode::SendDataError);
+        let healthy = match &self.socket {
+            Some(socket) => matches!(socket.take_error(), Ok(None)),
+            None => false,
+        };
+        if !healthy {
+            self.reconnect()?;
+        }
+        let bytes = as_byte::<T>(&input);
+        let sent = match &self.socket {
+            Some(socket) => socket.send(bytes).is_ok(),
+            None => false,
+        };
+        if !sent {
+            // Retry once on a fresh socket before reporting the failure.
+            self.reconnect()?;
+            if let Some(socket) = &self.socket {
+                socket.send(bytes).map_err(|_| StreamErrCode::SendDataError)?;
             }
-            Ok(())
-        } else {
-            Err(StreamErrCode::FileNotFound)
         }
+        Ok(())
     }
     fn stop(&mut self) -> Result<(), StreamErrCode> {
         self.set_state(StreamingState::Stopped);
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_udp_sender_multicast_options() {
+        let mut sender = UdpSender::<u32>::new("udp_multicast_options_test");
+        sender.set_statics_value::<u16>("port", 50323).unwrap();
+        sender.set_statics_value::<String>("address", "239.255.0.1".to_string()).unwrap();
+        sender.set_statics_value::<u32>("multicast_ttl", 4).unwrap();
+        sender.set_statics_value::<bool>("multicast_loopback", false).unwrap();
+        assert!(sender.init().is_ok());
+        sender.open_socket().unwrap();
+        let socket = sender.socket.as_ref().unwrap();
+        assert_eq!(socket.multicast_ttl_v4().unwrap(), 4);
+        assert!(!socket.multicast_loop_v4().unwrap());
+    }
+}
@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Hard-clips each sample to the `[min, max]` parameters.
+#[derive(StreamBlockMacro)]
+pub struct Clipping {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl Clipping {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<f64>("min", -1.0, None).unwrap();
+        ret.new_parameter::<f64>("max", 1.0, None).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for Clipping {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        let min = self.get_parameter_value::<f64>("min")?;
+        let max = self.get_parameter_value::<f64>("max")?;
+        if min > max {
+            return Err(StreamErrCode::InvalidParameter);
+        }
+        samples.iter_mut().for_each(|x| *x = x.clamp(min, max));
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    #[test]
+    fn test_clipping() {
+        let mut clipping = Clipping::new("clipping_test");
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        clipping.connect("output", sender).unwrap();
+        assert_eq!(process_once(&mut clipping, &receiver, vec![-2.0, -0.5, 0.5, 3.0]), vec![-1.0, -0.5, 0.5, 1.0]);
+        clipping.set_parameter_value::<f64>("min", 0.0).unwrap();
+        clipping.set_parameter_value::<f64>("max", 0.25).unwrap();
+        assert_eq!(process_once(&mut clipping, &receiver, vec![-2.0, 0.1, 0.5]), vec![0.0, 0.1, 0.25]);
+        clipping.set_parameter_value::<f64>("min", 1.0).unwrap();
+        clipping.get_input_channel::<Vec<f64>>("input").unwrap().send(vec![0.0]).unwrap();
+        assert_eq!(clipping.process(), Err(StreamErrCode::InvalidParameter));
+    }
+}
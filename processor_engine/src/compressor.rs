@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Reduces the dynamics of the signal: above the `threshold` parameter, the
+/// envelope level is divided by `ratio`. The envelope follows rises of the
+/// signal within `attack_ms` and decays within `release_ms`, and is kept
+/// across the input vectors.
+#[derive(StreamBlockMacro)]
+pub struct Compressor {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    envelope:   f64,
+}
+
+impl Compressor {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            envelope: 0.0,
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        // Strictly positive, as the gain divides by the threshold.
+        ret.new_parameter::<f64>("threshold", 0.5, Some([f64::MIN_POSITIVE, f64::MAX])).unwrap();
+        ret.new_parameter::<f64>("ratio", 4.0, Some([1.0, 100.0])).unwrap();
+        ret.new_parameter::<f64>("attack_ms", 10.0, Some([0.0, 10000.0])).unwrap();
+        ret.new_parameter::<f64>("release_ms", 100.0, Some([0.0, 10000.0])).unwrap();
+        ret.new_parameter::<f64>("sample_rate", 48000.0, Some([1.0, f64::MAX])).unwrap();
+        ret
+    }
+    /// One-pole smoothing coefficient of a `time_ms` time constant.
+    fn smoothing(time_ms: f64, sample_rate: f64) -> f64 {
+        if time_ms <= 0.0 {
+            return 0.0;
+        }
+        (-1000.0 / (time_ms * sample_rate)).exp()
+    }
+}
+
+impl StreamProcessor for Compressor {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        let threshold = self.get_parameter_value::<f64>("threshold")?;
+        let ratio = self.get_parameter_value::<f64>("ratio")?;
+        let sample_rate = self.get_parameter_value::<f64>("sample_rate")?;
+        let attack = Self::smoothing(self.get_parameter_value::<f64>("attack_ms")?, sample_rate);
+        let release = Self::smoothing(self.get_parameter_value::<f64>("release_ms")?, sample_rate);
+        for x in samples.iter_mut() {
+            let level = x.abs();
+            let coefficient = if level > self.envelope { attack } else { release };
+            self.envelope = coefficient * self.envelope + (1.0 - coefficient) * level;
+            if self.envelope > threshold {
+                // threshold * (envelope / threshold)^(1 / ratio) / envelope,
+                // written so that no intermediate value can overflow.
+                *x *= (threshold / self.envelope).powf(1.0 - 1.0 / ratio);
+            }
+        }
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    fn compressor(name: &'static str) -> (Compressor, std::sync::mpsc::Receiver<Vec<f64>>) {
+        let mut compressor = Compressor::new(name);
+        compressor.set_parameter_value::<f64>("sample_rate", 1000.0).unwrap();
+        compressor.set_parameter_value::<f64>("attack_ms", 1.0).unwrap();
+        compressor.set_parameter_value::<f64>("release_ms", 10.0).unwrap();
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        compressor.connect("output", sender).unwrap();
+        (compressor, receiver)
+    }
+
+    #[test]
+    fn test_compressor() {
+        let (mut compressor, receiver) = compressor("compressor_test");
+        let output = process_once(&mut compressor, &receiver, vec![1.0; 50]);
+        // 0.5 above the 0.5 threshold, divided by 4 in log scale.
+        let expected = 0.5 * 2f64.powf(0.25);
+        assert!((output[49] - expected).abs() < 1e-9);
+        assert!(output.iter().all(|&y| y <= 1.0 && y >= expected - 1e-9));
+
+        // Below the threshold, once released, the signal passes unchanged.
+        let output = process_once(&mut compressor, &receiver, vec![0.25; 200]);
+        assert!(output[0] < 0.25);
+        assert_eq!(output[199], 0.25);
+    }
+
+    #[test]
+    fn test_compressor_threshold() {
+        let (mut compressor, receiver) = compressor("compressor_threshold");
+        assert!(compressor.set_parameter_value::<f64>("threshold", 0.0).is_err());
+        compressor.set_parameter_value::<f64>("threshold", f64::MIN_POSITIVE).unwrap();
+        let output = process_once(&mut compressor, &receiver, vec![1.0, -1e6, 1e300]);
+        assert!(output.iter().all(|y| y.is_finite()));
+    }
+
+    #[test]
+    fn test_compressor_state() {
+        let signal: Vec<f64> = (0..300).map(|n| (n as f64 * 0.1).sin() * (n as f64 / 100.0)).collect();
+        let (mut whole, whole_receiver) = compressor("compressor_whole");
+        let expected = process_once(&mut whole, &whole_receiver, signal.clone());
+        let (mut split, split_receiver) = compressor("compressor_split");
+        let output: Vec<f64> = signal.chunks(64).flat_map(|c| process_once(&mut split, &split_receiver, c.to_vec())).collect();
+        assert_eq!(output, expected);
+    }
+}
@@ -1,90 +1,296 @@
-
 use std::sync::{Mutex, OnceLock, Arc};
-use std::{collections::HashMap, thread::JoinHandle};
-use data_model::{memory_manager::MemoryManager, streaming_data::StreamErrCode};
+use std::{collections::HashMap, thread::{self, JoinHandle}};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use data_model::{ffi::ModuleRegistry, memory_manager::MemoryManager, modules::ModuleStruct, streaming_data::StreamErrCode};
+use libloading::Library;
+use crate::ffi::ModuleProcessor;
 use crate::task_monitor::TaskManager;
-use crate::stream_processor::StreamProcessor;
-pub struct ProcessorNode {
-    pub processor: Box<dyn StreamProcessor>,
-    pub next_node: Option<Box<ProcessorNode>>,
-    pub prev_node: Option<*mut ProcessorNode>,
+use crate::stream_processor::{connect_blocks, StreamProcessor};
+use data_model::streaming_data::StreamingState;
+
+pub struct ProcessorChain {
+    pub name: String,
+    pub processors: Vec<Box<dyn StreamProcessor>>,
+    /// Time of the last complete pass through the chain.
+    pub last_output: Arc<Mutex<DateTime<Utc>>>,
 }
+
+/// Controls a chain from other threads while the chain itself is owned by
+/// the task running it.
 #[derive(Clone)]
-pub struct ProcessorChain {
+pub struct ChainHandle {
     pub name: String,
-    pub head: Option<*mut ProcessorNode>,
-    pub tail: Option<*mut ProcessorNode>,
-    pub nodes: Vec<*mut ProcessorNode>,
+    pub last_output: Arc<Mutex<DateTime<Utc>>>,
+    states: Vec<Arc<Mutex<StreamingState>>>,
+}
+
+impl ChainHandle {
+    /// Requests the chain to stop: the running task exits after the current pass.
+    pub fn stop(&self) {
+        for state in &self.states {
+            *state.lock().unwrap() = StreamingState::Stopped;
+        }
+    }
+    pub fn is_stopped(&self) -> bool {
+        self.states.iter().any(|state| *state.lock().unwrap() == StreamingState::Stopped)
+    }
 }
 
 impl ProcessorChain {
     pub fn new(name: String) -> Self {
         ProcessorChain {
             name,
-            head: None,
-            tail: None,
-            nodes: Vec::new(),
+            processors: Vec::new(),
+            last_output: Arc::new(Mutex::new(Utc::now())),
         }
     }
     pub fn add_processor(&mut self, processor: Box<dyn StreamProcessor>) {
-        let mut new_node = Box::new(ProcessorNode {
-            processor,
-            next_node: None,
-            prev_node: None,
-        });
-        let new_node_ptr: *mut ProcessorNode = &mut *new_node;
-
-        match self.tail {
-            Some(tail_ptr) => unsafe {
-                (*tail_ptr).next_node = Some(new_node);
-                (*new_node_ptr).prev_node = Some(tail_ptr);
-            },
-            None => {
-                self.head = Some(new_node_ptr);
-            }
+        self.processors.push(processor);
+    }
+    pub fn handle(&self) -> ChainHandle {
+        ChainHandle {
+            name: self.name.clone(),
+            last_output: self.last_output.clone(),
+            states: self.processors.iter().map(|processor| processor.get_state_handle()).collect(),
         }
-
-        self.tail = Some(new_node_ptr);
-        self.nodes.push(new_node_ptr);
     }
+    /// Runs a single pass through the chain. Nothing is processed while one
+    /// of the processors is paused.
     pub fn process(&mut self) -> Result<(), StreamErrCode> {
-        let mut current_node_ptr = self.head;
-
-        while let Some(node_ptr) = current_node_ptr {
-            unsafe {
-                let node = &mut *node_ptr;
-                node.processor.process()?;
-                current_node_ptr = match &node.next_node {
-                    Some(next_node) => Some(&**next_node as *const ProcessorNode as *mut ProcessorNode),
-                    None => None,
-                };
+        if self.is_paused() {
+            return Ok(());
+        }
+        for index in 0..self.processors.len() {
+            self.processors[index].process_step()?;
+        }
+        *self.last_output.lock().unwrap() = Utc::now();
+        Ok(())
+    }
+    /// Marks every processor as running, ahead of `run`.
+    pub fn set_running(&mut self) {
+        for processor in self.processors.iter_mut() {
+            processor.set_state(StreamingState::Running);
+        }
+    }
+    /// Processes the chain until one of its processors is stopped, then stops all of them.
+    /// The chain waits while one of its processors is paused.
+    pub fn run(&mut self) -> Result<(), StreamErrCode> {
+        *self.last_output.lock().unwrap() = Utc::now();
+        let mut ret = Ok(());
+        while !self.is_stopped() {
+            if self.is_paused() {
+                wait_paused(&self.last_output);
+                continue;
             }
+            ret = self.process();
+            if ret.is_err() {
+                // A stop request interrupts a pending receive with an error.
+                if self.is_stopped() {
+                    ret = Ok(());
+                }
+                break;
+            }
+        }
+        self.stop()?;
+        ret
+    }
+    pub fn stop(&mut self) -> Result<(), StreamErrCode> {
+        for index in 0..self.processors.len() {
+            self.processors[index].stop()?;
         }
+        Ok(())
+    }
+    pub fn is_stopped(&self) -> bool {
+        self.processors.iter().any(|processor| processor.check_state(StreamingState::Stopped))
+    }
+    pub fn is_paused(&self) -> bool {
+        self.processors.iter().any(|processor| processor.check_state(StreamingState::Paused))
+    }
+}
 
+/// Sleeps for one polling period of a paused chain or graph. The time of the
+/// last output is refreshed, as a paused chain is not late.
+fn wait_paused(last_output: &Mutex<DateTime<Utc>>) {
+    thread::sleep(Duration::from_millis(10));
+    *last_output.lock().unwrap() = Utc::now();
+}
+
+/// A directed connection from the output `out_key` of `from_block` to the
+/// input `in_key` of `to_block`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphEdge {
+    pub from_block: String,
+    pub out_key: String,
+    pub to_block: String,
+    pub in_key: String,
+}
+
+/// Processors connected as a directed acyclic graph, allowing fan-out and
+/// fan-in. Each pass processes the nodes in topological order.
+pub struct ProcessorGraph {
+    pub name: String,
+    nodes: Vec<(String, Box<dyn StreamProcessor>)>,
+    edges: Vec<GraphEdge>,
+    order: Vec<usize>,
+    /// Time of the last complete pass through the graph.
+    pub last_output: Arc<Mutex<DateTime<Utc>>>,
+}
+
+impl ProcessorGraph {
+    pub fn new(name: String) -> Self {
+        ProcessorGraph {
+            name,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            order: Vec::new(),
+            last_output: Arc::new(Mutex::new(Utc::now())),
+        }
+    }
+    pub fn add_node(&mut self, name: &str, processor: Box<dyn StreamProcessor>) -> Result<(), StreamErrCode> {
+        if self.node_index(name).is_some() {
+            return Err(StreamErrCode::AlreadyDefined);
+        }
+        self.nodes.push((name.to_string(), processor));
+        self.validate()
+    }
+    pub fn edges(&self) -> &[GraphEdge] {
+        &self.edges
+    }
+    fn node_index(&self, name: &str) -> Option<usize> {
+        self.nodes.iter().position(|(node, _)| node == name)
+    }
+    /// Connects `from_block.out_key` to `to_block.in_key`. Edges closing a
+    /// cycle are refused with `InvalidOperation` and leave the graph unchanged.
+    pub fn add_edge(&mut self, from_block: &str, out_key: &str, to_block: &str, in_key: &str) -> Result<(), StreamErrCode> {
+        let from = self.node_index(from_block).ok_or(StreamErrCode::InvalidInput)?;
+        let to = self.node_index(to_block).ok_or(StreamErrCode::InvalidInput)?;
+        let edge = GraphEdge {
+            from_block: from_block.to_string(),
+            out_key: out_key.to_string(),
+            to_block: to_block.to_string(),
+            in_key: in_key.to_string(),
+        };
+        self.edges.push(edge);
+        if let Err(e) = self.validate() {
+            self.edges.pop();
+            return Err(e);
+        }
+        // from != to here, a self loop being a cycle.
+        let (upstream, downstream) = if from < to {
+            let (head, tail) = self.nodes.split_at_mut(to);
+            (&mut head[from].1, &tail[0].1)
+        } else {
+            let (head, tail) = self.nodes.split_at_mut(from);
+            (&mut tail[0].1, &head[to].1)
+        };
+        let ret = connect_blocks(upstream.as_mut(), out_key, downstream.as_ref(), in_key);
+        if ret.is_err() {
+            self.edges.pop();
+        }
+        ret
+    }
+    /// Sorts the nodes topologically, failing with `InvalidOperation` if the
+    /// edges contain a cycle.
+    pub fn validate(&mut self) -> Result<(), StreamErrCode> {
+        let mut in_degree = vec![0; self.nodes.len()];
+        let mut successors = vec![Vec::new(); self.nodes.len()];
+        for edge in &self.edges {
+            let from = self.node_index(&edge.from_block).ok_or(StreamErrCode::InvalidInput)?;
+            let to = self.node_index(&edge.to_block).ok_or(StreamErrCode::InvalidInput)?;
+            successors[from].push(to);
+            in_degree[to] += 1;
+        }
+        let mut order: Vec<usize> = (0..self.nodes.len()).filter(|&node| in_degree[node] == 0).collect();
+        let mut next = 0;
+        while next < order.len() {
+            for &successor in &successors[order[next]] {
+                in_degree[successor] -= 1;
+                if in_degree[successor] == 0 {
+                    order.push(successor);
+                }
+            }
+            next += 1;
+        }
+        if order.len() != self.nodes.len() {
+            return Err(StreamErrCode::InvalidOperation);
+        }
+        self.order = order;
         Ok(())
     }
-    pub fn stop(&mut self) -> Result<(), StreamErrCode> {
-        for &node_ptr in &self.nodes {
-            unsafe {
-                let node = &mut *node_ptr;
-                node.processor.stop()?;
+    /// Node names in processing order.
+    pub fn processing_order(&self) -> Vec<&str> {
+        self.order.iter().map(|&node| self.nodes[node].0.as_str()).collect()
+    }
+    pub fn handle(&self) -> ChainHandle {
+        ChainHandle {
+            name: self.name.clone(),
+            last_output: self.last_output.clone(),
+            states: self.nodes.iter().map(|(_, processor)| processor.get_state_handle()).collect(),
+        }
+    }
+    /// Runs a single pass through the graph. Nothing is processed while one
+    /// of the processors is paused.
+    pub fn process(&mut self) -> Result<(), StreamErrCode> {
+        if self.is_paused() {
+            return Ok(());
+        }
+        for index in 0..self.order.len() {
+            let node = self.order[index];
+            self.nodes[node].1.process_step()?;
+        }
+        *self.last_output.lock().unwrap() = Utc::now();
+        Ok(())
+    }
+    /// Marks every processor as running, ahead of `run`.
+    pub fn set_running(&mut self) {
+        for (_, processor) in self.nodes.iter_mut() {
+            processor.set_state(StreamingState::Running);
+        }
+    }
+    /// Validates the graph, then processes it until one of its processors is
+    /// stopped and stops all of them. The graph waits while one of its
+    /// processors is paused.
+    pub fn run(&mut self) -> Result<(), StreamErrCode> {
+        self.validate()?;
+        *self.last_output.lock().unwrap() = Utc::now();
+        let mut ret = Ok(());
+        while !self.is_stopped() {
+            if self.is_paused() {
+                wait_paused(&self.last_output);
+                continue;
+            }
+            ret = self.process();
+            if ret.is_err() {
+                // A stop request interrupts a pending receive with an error.
+                if self.is_stopped() {
+                    ret = Ok(());
+                }
+                break;
             }
-        }  
+        }
+        self.stop()?;
+        ret
+    }
+    pub fn stop(&mut self) -> Result<(), StreamErrCode> {
+        for (_, processor) in self.nodes.iter_mut() {
+            processor.stop()?;
+        }
         Ok(())
     }
+    pub fn is_stopped(&self) -> bool {
+        self.nodes.iter().any(|(_, processor)| processor.check_state(StreamingState::Stopped))
+    }
+    pub fn is_paused(&self) -> bool {
+        self.nodes.iter().any(|(_, processor)| processor.check_state(StreamingState::Paused))
+    }
 }
 
-unsafe impl Send for ProcessorChain {}
-unsafe impl Sync for ProcessorChain {}
-pub struct ChainNode {
-    pub processor: Box<ProcessorChain>,
-    pub next_node: Option<*mut ChainNode>,
-    pub prev_node: Option<*mut ChainNode>,
-}
-#[derive(Clone)]
 pub struct ProcessorMode {
     pub name: String,
     pub chains: Vec<ProcessorChain>,
+    running: Vec<(ChainHandle, JoinHandle<ProcessorChain>)>,
+    active: bool,
 }
 
 impl ProcessorMode {
@@ -92,30 +298,76 @@ impl ProcessorMode {
         ProcessorMode {
             name: name.to_string(),
             chains: Vec::new(),
+            running: Vec::new(),
+            active: false,
         }
     }
     pub fn add_chain(&mut self, chain: Box<ProcessorChain>) {
         self.chains.push(*chain);
     }
-    pub fn run(&mut self) -> Result<(), StreamErrCode> {
-        let mut handles = Vec::new();
-        let mut tm = TaskManager::get().lock().unwrap();
-        for mut chain in self.chains.clone().into_iter() {
-            let handle = tm.create_task(chain.name.clone(), move|| {
-                loop {
-                    chain.process().unwrap();
-                }
-            });
-            handles.push(handle.unwrap());
+    /// Adds a chain to the mode, starting it right away if the mode is running.
+    pub fn add_chain_live(&mut self, chain: Box<ProcessorChain>) -> Result<(), StreamErrCode> {
+        if self.active {
+            self.spawn(*chain)
+        } else {
+            self.add_chain(chain);
+            Ok(())
+        }
+    }
+    /// Removes a chain from the mode, stopping and joining its task first
+    /// if it is running. The other chains are left untouched.
+    pub fn remove_chain(&mut self, name: &str) -> Result<ProcessorChain, StreamErrCode> {
+        if let Some(index) = self.running.iter().position(|(handle, _)| handle.name == name) {
+            let (handle, task) = self.running.remove(index);
+            handle.stop();
+            return task.join().map_err(|_| StreamErrCode::TaskError);
         }
-        for handle in handles.drain(..) {
-            handle.join().unwrap();
+        match self.chains.iter().position(|chain| chain.name == name) {
+            Some(index) => Ok(self.chains.remove(index)),
+            None => Err(StreamErrCode::InvalidInput),
         }
+    }
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+    /// Moves every chain into its own task. The chains come back on `stop()`.
+    pub fn start(&mut self) -> Result<(), StreamErrCode> {
+        self.active = true;
+        let chains: Vec<ProcessorChain> = self.chains.drain(..).collect();
+        for chain in chains {
+            self.spawn(chain)?;
+        }
+        Ok(())
+    }
+    fn spawn(&mut self, mut chain: ProcessorChain) -> Result<(), StreamErrCode> {
+        // Set before spawning, so that a stop request cannot be overwritten.
+        chain.set_running();
+        let handle = chain.handle();
+        let task = TaskManager::get().lock().unwrap().create_task(chain.name.clone(), move || {
+            if let Err(e) = chain.run() {
+                eprintln!("Chain '{}' error: {}", chain.name, e);
+            }
+            chain
+        }).map_err(|_| StreamErrCode::TaskError)?;
+        self.running.push((handle, task));
         Ok(())
     }
+    /// Starts the chains and waits for all of them to stop.
+    pub fn run(&mut self) -> Result<(), StreamErrCode> {
+        self.start()?;
+        self.join()
+    }
     pub fn stop(&mut self) -> Result<(), StreamErrCode> {
-        for chain in self.chains.iter_mut() {
-            chain.stop()?;
+        for (handle, _) in self.running.iter() {
+            handle.stop();
+        }
+        self.join()
+    }
+    fn join(&mut self) -> Result<(), StreamErrCode> {
+        self.active = false;
+        for (_, task) in self.running.drain(..) {
+            let chain = task.join().map_err(|_| StreamErrCode::TaskError)?;
+            self.chains.push(chain);
         }
         Ok(())
     }
@@ -124,14 +376,12 @@ impl ProcessorMode {
 pub struct ProcessorManager {
     pub modes: HashMap<usize, ProcessorMode>,
     pub current_mode_index: usize,
-    pub curr_mode_handle: Option<JoinHandle<()>>,
 }
 impl ProcessorManager {
     pub fn new() -> Self {
         ProcessorManager {
             modes: HashMap::new(),
             current_mode_index: 0,
-            curr_mode_handle: None,
         }
     }
     pub fn add_mode(&mut self, mode: ProcessorMode) {
@@ -144,22 +394,15 @@ impl ProcessorManager {
             return Ok(());
         }
         if self.modes.contains_key(&index) {
-            if let Some(handle) = self.curr_mode_handle.take() {
             // Stop current mode
-                let mut curr_mode = self.modes.get_mut(&self.current_mode_index).unwrap().clone();
-                curr_mode.stop().unwrap();
-                handle.join().unwrap();
+            if let Some(curr_mode) = self.modes.get_mut(&self.current_mode_index) {
+                curr_mode.stop().map_err(|e| e.to_string())?;
             }
             // Switch memory manager
-            MemoryManager::get_memory_manager().unwrap().set_mode(self.current_mode_index);
+            MemoryManager::get_memory_manager().unwrap().set_mode(index);
             // Start new mode
             self.current_mode_index = index;
-            let mut new_mode = self.modes.get_mut(&self.current_mode_index).unwrap().clone();
-            let mut tm = TaskManager::get().lock().unwrap();
-            self.curr_mode_handle  = Some(tm.create_task( new_mode.name.clone(), move || {
-                new_mode.run().unwrap();
-            }).unwrap());
-            Ok(())
+            self.modes.get_mut(&index).unwrap().start().map_err(|e| e.to_string())
         } else {
             Err(format!("Mode with index {} does not exist.", index))
         }
@@ -169,11 +412,14 @@ impl ProcessorManager {
 
 pub struct ProcessorEngine {
     processor_map: HashMap<&'static str, Box<dyn StreamProcessor>>,
+    // The module and library of each module processor. Declared after
+    // `processor_map`, so that the processors are dropped first.
+    processor_origin: HashMap<&'static str, (ModuleStruct, Arc<Library>)>,
 }
 
 impl ProcessorEngine {
     fn new() -> Self {
-        Self { processor_map: HashMap::new() }
+        Self { processor_map: HashMap::new(), processor_origin: HashMap::new() }
     }
     pub fn get() -> &'static Mutex<ProcessorEngine> {
         PROCESSOR_ENGINE.get_or_init(|| Arc::new(Mutex::new(ProcessorEngine::new())))
@@ -185,6 +431,37 @@ impl ProcessorEngine {
         self.processor_map.insert(name, processor);
         Ok(())
     }
+    /// Registers a processor created by a dynamic module, keeping track of
+    /// the module that provided it and keeping its library loaded.
+    pub fn register_module_processor(&mut self, name: &'static str, processor: ModuleProcessor) -> Result<(), StreamErrCode> {
+        let (processor, module, lib) = processor.into_parts();
+        self.register_processor(name, processor)?;
+        self.processor_origin.insert(name, (module, lib));
+        Ok(())
+    }
+    /// Returns the module a processor was loaded from, None for processors
+    /// registered directly.
+    pub fn processor_origin(&self, name: &str) -> Option<&ModuleStruct> {
+        self.processor_origin.get(name).map(|(module, _)| module)
+    }
+    /// Stops and drops the processors created by module `name`, then unloads
+    /// the module from `registry`. Nothing is dropped if the module cannot be
+    /// unloaded.
+    pub fn unload_module(&mut self, registry: &mut ModuleRegistry, name: &str) -> Result<(), StreamErrCode> {
+        registry.can_unload(name)?;
+        let processors: Vec<&'static str> = self.processor_origin.iter()
+            .filter(|(_, (module, _))| module.name == name)
+            .map(|(key, _)| *key)
+            .collect();
+        for key in processors {
+            if let Some(mut processor) = self.processor_map.remove(key) {
+                let _ = processor.stop();
+            }
+            // Only now may the library be closed.
+            self.processor_origin.remove(key);
+        }
+        registry.unload(name)
+    }
     pub fn init(&mut self) -> Result<(), StreamErrCode>{
         for (_, value) in self.processor_map.iter_mut() {
             match value.init() {
@@ -212,6 +489,26 @@ impl ProcessorEngine {
             None => Err(StreamErrCode::InvalidInput),
         }
     }
+    /// Returns the names of the registered processors, sorted.
+    pub fn processor_names(&self) -> Vec<&'static str> {
+        let mut names: Vec<&'static str> = self.processor_map.keys().copied().collect();
+        names.sort();
+        names
+    }
+    /// Returns the parameters of a processor, keyed by qualified name.
+    pub fn get_parameters(&self, processor_name: &str) -> Result<serde_json::Value, StreamErrCode> {
+        match self.processor_map.get(processor_name) {
+            Some(processor) => Ok(processor.get_parameters_json()),
+            None => Err(StreamErrCode::InvalidInput),
+        }
+    }
+    /// Returns the name and help of the commands accepted by a processor.
+    pub fn list_commands(&self, processor_name: &str) -> Result<Vec<(&'static str, &'static str)>, StreamErrCode> {
+        match self.processor_map.get(processor_name) {
+            Some(processor) => Ok(processor.list_commands()),
+            None => Err(StreamErrCode::InvalidInput),
+        }
+    }
 }
 
 static PROCESSOR_ENGINE: OnceLock<Arc<Mutex<ProcessorEngine>>> = OnceLock::new();
@@ -221,6 +518,50 @@ mod test {
     use super::*;
     use crate::test::TestBlock;
     use crate::stream_processor::{StreamBlock};
+    use std::any::Any;
+    use std::fmt::Debug;
+    use std::sync::mpsc::SyncSender;
+    use serde::Serialize;
+    use stream_proc_macro::StreamBlockMacro;
+    use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+    use data_model::connectors::{ConnectorTrait, Input, Output};
+    use crate::stream_processor::StreamBlockDyn;
+
+    /// Doubles its input, to build graphs of matching connectors.
+    #[derive(StreamBlockMacro)]
+    struct GainBlock {
+        name:       &'static str,
+        inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+        outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+        parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+        statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+        state:      HashMap<&'static str, Box<dyn DataTrait>>,
+        lock:       Arc<Mutex<()>>,
+        proc_state: Arc<Mutex<StreamingState>>,
+    }
+    impl GainBlock {
+        fn new(name: &'static str) -> Self {
+            let mut ret = Self {
+                name,
+                inputs: HashMap::new(),
+                outputs: HashMap::new(),
+                parameters: HashMap::new(),
+                statics: HashMap::new(),
+                state: HashMap::new(),
+                lock: Arc::new(Mutex::new(())),
+                proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            };
+            ret.new_input::<f32>("input").unwrap();
+            ret.new_output::<f32>("output").unwrap();
+            ret
+        }
+    }
+    impl StreamProcessor for GainBlock {
+        fn process(&mut self) -> Result<(), StreamErrCode> {
+            let value = self.recv_input::<f32>("input")?;
+            self.send_output::<f32>("output", 2.0 * value)
+        }
+    }
     #[test]
     fn test_engine() {
         let mut engine = ProcessorEngine::new();
@@ -230,4 +571,300 @@ mod test {
         engine.init().unwrap();
         engine.stop().unwrap();
     }
-}
\ No newline at end of file
+    #[test]
+    fn test_engine_commands() {
+        let mut engine = ProcessorEngine::new();
+        let mut test_block = TestBlock::new("command_processor");
+        test_block.set_state(StreamingState::Running);
+        let state = test_block.get_state_handle();
+        engine.register_processor("command_processor", Box::new(test_block)).unwrap();
+        let commands: Vec<&str> = engine.list_commands("command_processor").unwrap().iter().map(|(name, _)| *name).collect();
+        assert_eq!(commands, vec!["pause", "resume", "set_parameter"]);
+        assert_eq!(engine.processor_names(), vec!["command_processor"]);
+        assert_eq!(engine.get_parameters("command_processor").unwrap()["command_processor.change_sign"], false);
+        assert_eq!(engine.get_parameters("missing").err(), Some(StreamErrCode::InvalidInput));
+        assert_eq!(engine.list_commands("missing").err(), Some(StreamErrCode::InvalidInput));
+        assert_eq!(engine.execute_command("command_processor", "pause", vec![]), Ok(String::new()));
+        assert_eq!(*state.lock().unwrap(), StreamingState::Paused);
+        assert_eq!(engine.execute_command("command_processor", "resume", vec!["now"]), Err(StreamErrCode::InvalidInput));
+        assert_eq!(engine.execute_command("command_processor", "resume", vec![]), Ok(String::new()));
+        assert_eq!(*state.lock().unwrap(), StreamingState::Running);
+        assert_eq!(engine.execute_command("command_processor", "unknown", vec![]), Err(StreamErrCode::InvalidOperation));
+    }
+    #[test]
+    fn test_processor_chain() {
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(10);
+        let mut chain = ProcessorChain::new("test_chain".to_string());
+        for (name, value) in [("chain_first", 1), ("chain_second", 2)] {
+            let mut block = TestBlock::new(name);
+            block.connect("test_output", out_sender.clone()).unwrap();
+            block.get_input_channel::<i32>("test_input").unwrap().send(value).unwrap();
+            chain.add_processor(Box::new(block));
+        }
+        chain.process().unwrap();
+        assert_eq!(out_receiver.try_iter().collect::<Vec<f32>>(), vec![1.0, 2.0]);
+        let handle = chain.handle();
+        assert!(!handle.is_stopped());
+        chain.stop().unwrap();
+        assert!(chain.is_stopped());
+        assert!(chain.processors.iter().all(|p| p.check_state(StreamingState::Stopped)));
+        assert!(handle.is_stopped());
+    }
+    #[test]
+    fn test_processor_chain_pause() {
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(10);
+        let mut chain = ProcessorChain::new("test_pause_chain".to_string());
+        let mut block = TestBlock::new("pause_chain_block");
+        block.connect("test_output", out_sender).unwrap();
+        let input = block.get_input_channel::<i32>("test_input").unwrap();
+        let state = block.get_state_handle();
+        chain.add_processor(Box::new(block));
+        chain.set_running();
+        let handle = chain.handle();
+        let task = std::thread::spawn(move || chain.run());
+        input.send(1).unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 1.0);
+        *state.lock().unwrap() = StreamingState::Paused;
+        input.send(2).unwrap();
+        input.send(3).unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+        // At most the pass already waiting for its input completes while paused.
+        let mut received: Vec<f32> = out_receiver.try_iter().collect();
+        assert!(received.is_empty() || received == vec![2.0]);
+        *state.lock().unwrap() = StreamingState::Running;
+        while received.len() < 2 {
+            received.push(out_receiver.recv().unwrap());
+        }
+        assert_eq!(received, vec![2.0, 3.0]);
+        handle.stop();
+        assert!(task.join().unwrap().is_ok());
+    }
+    #[test]
+    fn test_processor_mode_stop() {
+        let mut mode = ProcessorMode::new("test_mode");
+        let mut chain = ProcessorChain::new("test_mode_chain".to_string());
+        let block = TestBlock::new("mode_block");
+        let input = block.get_input_channel::<i32>("test_input").unwrap();
+        chain.add_processor(Box::new(block));
+        mode.add_chain(Box::new(chain));
+        mode.start().unwrap();
+        assert!(mode.chains.is_empty());
+        // Stop is observed once the pending pass completes.
+        let stopper = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+            input.send(1).unwrap();
+        });
+        mode.stop().unwrap();
+        stopper.join().unwrap();
+        assert_eq!(mode.chains.len(), 1);
+        assert!(mode.chains[0].is_stopped());
+    }
+    #[test]
+    fn test_processor_mode_restart() {
+        let mut mode = ProcessorMode::new("test_restart_mode");
+        let mut chain = ProcessorChain::new("test_restart_chain".to_string());
+        let block = TestBlock::new("restart_block");
+        let input = block.get_input_channel::<i32>("test_input").unwrap();
+        let state = block.get_state_handle();
+        chain.add_processor(Box::new(block));
+        mode.add_chain(Box::new(chain));
+        for _ in 0..2 {
+            mode.start().unwrap();
+            // The block and the handle of the running task share the state.
+            assert_eq!(Arc::strong_count(&state), 3);
+            input.send(1).unwrap();
+            mode.stop().unwrap();
+            assert_eq!(mode.chains.len(), 1);
+            assert_eq!(Arc::strong_count(&state), 2);
+        }
+        drop(mode);
+        assert_eq!(Arc::strong_count(&state), 1);
+    }
+    #[test]
+    fn test_processor_mode_live_chains() {
+        let chain_with_block = |name: &'static str| {
+            let mut chain = ProcessorChain::new(name.to_string());
+            let block = TestBlock::new(name);
+            let input = block.get_input_channel::<i32>("test_input").unwrap();
+            chain.add_processor(Box::new(block));
+            (Box::new(chain), input)
+        };
+        let mut mode = ProcessorMode::new("test_live_mode");
+        let (chain, first_input) = chain_with_block("live_first");
+        mode.add_chain_live(chain).unwrap();
+        assert!(!mode.is_active());
+        assert_eq!(mode.chains.len(), 1);
+        mode.start().unwrap();
+        let (chain, second_input) = chain_with_block("live_second");
+        mode.add_chain_live(chain).unwrap();
+        assert!(mode.chains.is_empty());
+
+        // Removing a running chain leaves the other one running.
+        second_input.send(1).unwrap();
+        let removed = mode.remove_chain("live_second").unwrap();
+        assert!(removed.is_stopped());
+        assert!(mode.is_active());
+        assert_eq!(mode.remove_chain("live_second").err(), Some(StreamErrCode::InvalidInput));
+        first_input.send(1).unwrap();
+        mode.stop().unwrap();
+        assert!(!mode.is_active());
+        assert_eq!(mode.chains.len(), 1);
+        assert_eq!(mode.remove_chain("live_first").unwrap().name, "live_first");
+        assert!(mode.chains.is_empty());
+    }
+    #[test]
+    fn test_connect_blocks() {
+        let mut source = TestBlock::new("connect_source");
+        let mut gain = GainBlock::new("connect_gain");
+        let sink = TestBlock::new("connect_sink");
+        connect_blocks(&mut source, "test_output", &gain, "input").unwrap();
+        assert_eq!(connect_blocks(&mut gain, "output", &sink, "test_input"), Err(StreamErrCode::WrongType));
+        assert_eq!(connect_blocks(&mut gain, "missing", &sink, "test_input"), Err(StreamErrCode::InvalidOutput));
+        assert_eq!(connect_blocks(&mut source, "test_output", &sink, "missing"), Err(StreamErrCode::InvalidInput));
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(1);
+        gain.connect("output", out_sender).unwrap();
+        source.get_input_channel::<i32>("test_input").unwrap().send(4).unwrap();
+        source.process().unwrap();
+        gain.process().unwrap();
+        assert_eq!(out_receiver.try_recv(), Ok(8.0));
+    }
+    #[test]
+    fn test_processor_graph() {
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(10);
+        let mut graph = ProcessorGraph::new("test_graph".to_string());
+        // Nodes added out of order: the graph sorts them.
+        for name in ["graph_tail", "graph_left", "graph_right"] {
+            let mut block = GainBlock::new(name);
+            if name != "graph_left" {
+                block.connect("output", out_sender.clone()).unwrap();
+            }
+            graph.add_node(name, Box::new(block)).unwrap();
+        }
+        let source = TestBlock::new("graph_source");
+        let input = source.get_input_channel::<i32>("test_input").unwrap();
+        graph.add_node("graph_source", Box::new(source)).unwrap();
+        assert_eq!(graph.add_node("graph_source", Box::new(TestBlock::new("graph_source"))), Err(StreamErrCode::AlreadyDefined));
+
+        // Fan out from the source, then one more stage on the left branch.
+        graph.add_edge("graph_source", "test_output", "graph_left", "input").unwrap();
+        graph.add_edge("graph_source", "test_output", "graph_right", "input").unwrap();
+        graph.add_edge("graph_left", "output", "graph_tail", "input").unwrap();
+        let order = graph.processing_order();
+        let position = |name| order.iter().position(|node| *node == name).unwrap();
+        assert!(position("graph_source") < position("graph_left"));
+        assert!(position("graph_source") < position("graph_right"));
+        assert!(position("graph_left") < position("graph_tail"));
+
+        input.send(3).unwrap();
+        graph.process().unwrap();
+        let mut received = out_receiver.try_iter().collect::<Vec<f32>>();
+        received.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        assert_eq!(received, vec![6.0, 12.0]);
+
+        assert_eq!(graph.add_edge("graph_tail", "output", "graph_left", "input"), Err(StreamErrCode::InvalidOperation));
+        assert_eq!(graph.add_edge("graph_tail", "output", "graph_tail", "input"), Err(StreamErrCode::InvalidOperation));
+        assert_eq!(graph.add_edge("graph_right", "output", "graph_source", "test_input"), Err(StreamErrCode::InvalidOperation));
+        graph.add_node("graph_sink", Box::new(TestBlock::new("graph_sink"))).unwrap();
+        assert_eq!(graph.add_edge("graph_right", "output", "graph_sink", "test_input"), Err(StreamErrCode::WrongType));
+        assert_eq!(graph.add_edge("graph_right", "output", "graph_missing", "input"), Err(StreamErrCode::InvalidInput));
+        assert_eq!(graph.edges().len(), 3);
+        assert!(graph.validate().is_ok());
+    }
+    #[test]
+    fn test_switch_mode_memory() {
+        let mut manager = ProcessorManager::new();
+        manager.add_mode(ProcessorMode::new("memory_mode_0"));
+        manager.add_mode(ProcessorMode::new("memory_mode_1"));
+        MemoryManager::get_memory_manager().unwrap().set_mode(0);
+        let mut block = TestBlock::new("switch_mode_block");
+        block.set_parameter_value("change_sign", true).unwrap();
+        manager.switch_mode(1).unwrap();
+        let mut other = TestBlock::new("switch_mode_other");
+        other.set_parameter_value("change_sign", true).unwrap();
+        let parameters = |index| {
+            let mut mm = MemoryManager::get_memory_manager().unwrap();
+            let json: serde_json::Value = serde_json::from_str(&mm.get_memory_mode(index).unwrap().serialize_all()).unwrap();
+            json["memory_mapped"]["parameters"].clone()
+        };
+        assert_eq!(parameters(0)["switch_mode_block.change_sign"], true);
+        assert!(parameters(0).get("switch_mode_other.change_sign").is_none());
+        assert!(parameters(1).get("switch_mode_block.change_sign").is_none());
+        assert_eq!(parameters(1)["switch_mode_other.change_sign"], true);
+    }
+    #[test]
+    fn test_processor_origin() {
+        use data_model::modules::Version;
+        let mut engine = ProcessorEngine::new();
+        let module = ModuleStruct {
+            name: "test_module".to_string(),
+            description: String::new(),
+            authors: String::new(),
+            release_date: String::new(),
+            version: Version { major: 1, minor: 2, build: 3 },
+            dependencies: Vec::new(),
+            provides: vec!["TestBlock".to_string()],
+        };
+        let lib: Arc<Library> = Arc::new(libloading::os::unix::Library::this().into());
+        let processor = |name| ModuleProcessor::new(Box::new(TestBlock::new(name)), module.clone(), lib.clone());
+        engine.register_module_processor("module_processor", processor("module_processor")).unwrap();
+        engine.register_processor("local_processor", Box::new(TestBlock::new("local_processor"))).unwrap();
+        let origin = engine.processor_origin("module_processor").unwrap();
+        assert_eq!(origin.name, "test_module");
+        assert_eq!((origin.version.major, origin.version.minor, origin.version.build), (1, 2, 3));
+        assert!(engine.processor_origin("local_processor").is_none());
+        assert!(engine.register_module_processor("local_processor", processor("local_processor")).is_err());
+        assert!(engine.processor_origin("local_processor").is_none());
+    }
+    #[test]
+    fn test_unload_module() {
+        // A minimal module, with the C layout of ModuleStructFFI.
+        let source = r#"
+            #include <stddef.h>
+            struct Version { unsigned int major, minor, build; };
+            struct Module {
+                const char *name, *description, *authors, *release_date;
+                struct Version version;
+                const void *const *dependencies; size_t dependency_number;
+                const char *const *provides; size_t provides_lengths;
+            };
+            const struct Module MODULE = { "c_module", "", "", "", {1, 0, 0}, NULL, 0, NULL, 0 };
+            void *create_processor(const char *name) { return NULL; }
+        "#;
+        let dir = std::env::temp_dir();
+        let source_path = dir.join(format!("kappa_test_module_{}.c", std::process::id()));
+        let library_path = dir.join(format!("kappa_test_module_{}.so", std::process::id()));
+        std::fs::write(&source_path, source).unwrap();
+        let built = std::process::Command::new("cc")
+            .args(["-shared", "-fPIC", "-o"]).arg(&library_path).arg(&source_path)
+            .status();
+        std::fs::remove_file(&source_path).unwrap();
+        if !matches!(built, Ok(status) if status.success()) {
+            eprintln!("No C compiler available, skipping the module unload test");
+            return;
+        }
+
+        let mut registry = ModuleRegistry::new();
+        registry.load(vec![library_path.to_str().unwrap().to_string()]).unwrap();
+        let handle = registry.get("c_module").unwrap();
+        assert!(crate::ffi::create_module_processor(handle, "any").is_err());
+        let lib = std::sync::Arc::downgrade(&handle.lib);
+        let module = handle.module.clone();
+
+        let mut engine = ProcessorEngine::new();
+        let processor = ModuleProcessor::new(Box::new(TestBlock::new("c_module_processor")), module.clone(), handle.lib.clone());
+        engine.register_module_processor("c_module_processor", processor).unwrap();
+        let held = ModuleProcessor::new(Box::new(TestBlock::new("c_module_held")), module, handle.lib.clone());
+        engine.register_processor("local_unload_processor", Box::new(TestBlock::new("local_unload_processor"))).unwrap();
+        assert_eq!(engine.unload_module(&mut registry, "missing"), Err(StreamErrCode::FileNotFound));
+        engine.unload_module(&mut registry, "c_module").unwrap();
+        assert!(!engine.processor_map.contains_key("c_module_processor"));
+        assert!(engine.processor_origin("c_module_processor").is_none());
+        assert!(engine.processor_map.contains_key("local_unload_processor"));
+        assert!(registry.module_names().is_empty());
+        // A processor still alive keeps the library loaded.
+        assert!(lib.upgrade().is_some());
+        drop(held);
+        assert!(lib.upgrade().is_none());
+        std::fs::remove_file(&library_path).unwrap();
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::HashMap;
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, OnceLock};
+use serde_json::Value;
+
+/// Process-wide publish/subscribe of events by topic, for blocks that signal
+/// each other without a direct channel.
+pub struct EventBus {
+    subscribers: HashMap<String, Vec<SyncSender<Value>>>,
+}
+
+impl EventBus {
+    fn new() -> Self {
+        Self { subscribers: HashMap::new() }
+    }
+    pub fn get() -> &'static Mutex<EventBus> {
+        EVENT_BUS.get_or_init(|| Arc::new(Mutex::new(EventBus::new())))
+    }
+    /// Returns a receiver of the events later published on `topic`.
+    pub fn subscribe(&mut self, topic: &str) -> Receiver<Value> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel(50);
+        self.subscribers.entry(topic.to_string()).or_default().push(sender);
+        receiver
+    }
+    /// Sends `event` to the subscribers of `topic` and returns how many
+    /// received it. Never blocks: a full subscriber misses the event and a
+    /// closed one is removed.
+    pub fn publish(&mut self, topic: &str, event: Value) -> usize {
+        let mut delivered = 0;
+        if let Some(senders) = self.subscribers.get_mut(topic) {
+            senders.retain(|s| match s.try_send(event.clone()) {
+                Ok(_) => {
+                    delivered += 1;
+                    true
+                }
+                Err(TrySendError::Full(_)) => true,
+                Err(TrySendError::Disconnected(_)) => false,
+            });
+        }
+        delivered
+    }
+}
+
+static EVENT_BUS: OnceLock<Arc<Mutex<EventBus>>> = OnceLock::new();
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::any::Any;
+    use std::fmt::Debug;
+    use serde::Serialize;
+    use stream_proc_macro::StreamBlockMacro;
+    use data_model::streaming_data::{StreamErrCode, StreamingState};
+    use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+    use data_model::connectors::{ConnectorTrait, Input, Output};
+    use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+
+    /// Publishes every value received on its input.
+    #[derive(StreamBlockMacro)]
+    struct PublisherBlock {
+        name:       &'static str,
+        inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+        outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+        parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+        statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+        state:      HashMap<&'static str, Box<dyn DataTrait>>,
+        lock:       Arc<Mutex<()>>,
+        proc_state: Arc<Mutex<StreamingState>>,
+    }
+    impl StreamProcessor for PublisherBlock {
+        fn process(&mut self) -> Result<(), StreamErrCode> {
+            let estimate = self.recv_input::<f64>("estimate")?;
+            EventBus::get().lock().unwrap().publish("test_noise_estimate", serde_json::json!({"estimate": estimate}));
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_event_bus() {
+        let mut block = PublisherBlock {
+            name: "publisher",
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        block.new_input::<f64>("estimate").unwrap();
+        let first = EventBus::get().lock().unwrap().subscribe("test_noise_estimate");
+        let second = EventBus::get().lock().unwrap().subscribe("test_noise_estimate");
+        let other = EventBus::get().lock().unwrap().subscribe("test_target_acquired");
+        block.get_input_channel::<f64>("estimate").unwrap().send(0.25).unwrap();
+        block.process().unwrap();
+        assert_eq!(first.try_recv().unwrap()["estimate"], 0.25);
+        assert_eq!(second.try_recv().unwrap()["estimate"], 0.25);
+        assert!(other.try_recv().is_err());
+
+        drop(second);
+        assert_eq!(EventBus::get().lock().unwrap().publish("test_noise_estimate", Value::Null), 1);
+        assert_eq!(first.try_recv().unwrap(), Value::Null);
+    }
+}
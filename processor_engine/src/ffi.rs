@@ -0,0 +1,131 @@
+use std::ffi::{c_void, CString};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+use libloading::Library;
+use data_model::ffi::ModuleHandle;
+use data_model::modules::ModuleStruct;
+use data_model::streaming_data::StreamErrCode;
+use crate::stream_processor::StreamProcessor;
+
+/// Module side of `CreateProcessorFn`: hands `processor` over to the host.
+///
+/// Only a thin pointer crosses the C boundary. The `Box<dyn StreamProcessor>`
+/// fat pointer, with the vtable of the module, stays in the heap cell it points
+/// to, so the host never rebuilds a vtable address. Host and module must still
+/// be built with the same compiler and `processor_engine`, as the trait layout
+/// is not stable, and the module must stay loaded while the processor lives.
+pub fn export_stream_processor(processor: Box<dyn StreamProcessor>) -> *mut c_void {
+    Box::into_raw(Box::new(processor)) as *mut c_void
+}
+
+/// Host side of `CreateProcessorFn`: takes ownership of a processor returned
+/// by a module. Returns None for a null pointer.
+///
+/// # Safety
+/// `ptr` must be null or come from `export_stream_processor`, and must not be
+/// imported twice.
+pub unsafe fn import_stream_processor(ptr: *mut c_void) -> Option<Box<dyn StreamProcessor>> {
+    if ptr.is_null() {
+        return None;
+    }
+    Some(*unsafe { Box::from_raw(ptr as *mut Box<dyn StreamProcessor>) })
+}
+
+/// A processor created by a module. It holds the library of the module, so
+/// that its code and vtable stay loaded until the processor is dropped, and
+/// derefs to the processor itself.
+pub struct ModuleProcessor {
+    // Declared before `lib`, so it is dropped first.
+    processor: Box<dyn StreamProcessor>,
+    module: ModuleStruct,
+    lib: Arc<Library>,
+}
+
+impl ModuleProcessor {
+    /// Wraps `processor`, whose code must live in `lib` or in the host.
+    pub(crate) fn new(processor: Box<dyn StreamProcessor>, module: ModuleStruct, lib: Arc<Library>) -> Self {
+        Self { processor, module, lib }
+    }
+    /// The module the processor comes from.
+    pub fn module(&self) -> &ModuleStruct {
+        &self.module
+    }
+    pub(crate) fn into_parts(self) -> (Box<dyn StreamProcessor>, ModuleStruct, Arc<Library>) {
+        (self.processor, self.module, self.lib)
+    }
+}
+
+impl Deref for ModuleProcessor {
+    type Target = Box<dyn StreamProcessor>;
+    fn deref(&self) -> &Self::Target {
+        &self.processor
+    }
+}
+
+impl DerefMut for ModuleProcessor {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.processor
+    }
+}
+
+/// Creates the processor `name` through the constructor of a loaded module.
+pub fn create_module_processor(module: &ModuleHandle, name: &str) -> Result<ModuleProcessor, StreamErrCode> {
+    let c_name = CString::new(name).map_err(|_| StreamErrCode::InvalidInput)?;
+    let ptr = module.create_processor(&c_name);
+    let processor = unsafe { import_stream_processor(ptr) }.ok_or(StreamErrCode::InvalidProcessorBlock)?;
+    Ok(ModuleProcessor::new(processor, module.module.clone(), module.lib.clone()))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::ffi::{c_char, CStr};
+    use data_model::modules::Version;
+    use crate::test::TestBlock;
+
+    // What a module exports; here compiled into the host.
+    unsafe extern "C" fn create_processor(name: *const c_char) -> *mut c_void {
+        match unsafe { CStr::from_ptr(name) }.to_str() {
+            Ok("test_block") => export_stream_processor(Box::new(TestBlock::new("ffi_test_block"))),
+            _ => std::ptr::null_mut(),
+        }
+    }
+
+    /// A handle on the test binary itself, standing in for a module library.
+    fn module_handle() -> ModuleHandle {
+        let module = ModuleStruct {
+            name: "ffi_test_module".to_string(),
+            description: String::new(),
+            authors: String::new(),
+            release_date: String::new(),
+            version: Version { major: 1, minor: 0, build: 0 },
+            dependencies: Vec::new(),
+            provides: vec!["test_block".to_string()],
+        };
+        let lib = Arc::new(libloading::os::unix::Library::this().into());
+        unsafe { ModuleHandle::from_parts(module, String::new(), lib, create_processor) }
+    }
+
+    #[test]
+    fn test_create_module_processor() {
+        let module = module_handle();
+        let processor = create_module_processor(&module, "test_block").unwrap();
+        assert!(processor.as_any().downcast_ref::<TestBlock>().is_some());
+        assert_eq!(processor.get_input_list(), vec!["ffi_test_block.test_input"]);
+        assert_eq!(create_module_processor(&module, "missing").err(), Some(StreamErrCode::InvalidProcessorBlock));
+        assert_eq!(create_module_processor(&module, "nul\0name").err(), Some(StreamErrCode::InvalidInput));
+        assert!(unsafe { import_stream_processor(std::ptr::null_mut()) }.is_none());
+    }
+
+    #[test]
+    fn test_module_processor_holds_library() {
+        let module = module_handle();
+        let processor = create_module_processor(&module, "test_block").unwrap();
+        assert_eq!(processor.module().name, "ffi_test_module");
+        let lib = Arc::downgrade(&module.lib);
+        drop(module);
+        assert!(lib.upgrade().is_some());
+        drop(processor);
+        assert!(lib.upgrade().is_none());
+    }
+}
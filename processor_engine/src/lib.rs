@@ -2,4 +2,18 @@ pub mod stream_processor;
 pub mod task_monitor;
 pub mod engine;
 pub mod logger;
-pub mod test;
\ No newline at end of file
+pub mod watchdog;
+pub mod spectral_features;
+pub mod event_bus;
+pub mod null_sink;
+pub mod tee;
+pub mod resampler;
+pub mod scaler;
+pub mod offset;
+pub mod normalizer;
+pub mod clipping;
+pub mod limiter;
+pub mod compressor;
+pub mod sliding_window;
+pub mod ffi;
+pub mod test;
@@ -0,0 +1,88 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+fn limit(x: f64, threshold: f64) -> f64 {
+    let magnitude = x.abs();
+    if magnitude <= threshold {
+        return x;
+    }
+    let headroom = 1.0 - threshold;
+    if headroom <= 0.0 {
+        return x.signum();
+    }
+    x.signum() * (threshold + headroom * ((magnitude - threshold) / headroom).tanh())
+}
+
+/// Keeps the samples within [-1, 1]. Samples whose magnitude exceeds the
+/// `threshold` parameter are bent by a tanh knee towards full scale, instead
+/// of being clipped.
+#[derive(StreamBlockMacro)]
+pub struct Limiter {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl Limiter {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<f64>("threshold", 0.8, Some([0.0, 1.0])).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for Limiter {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        let threshold = self.get_parameter_value::<f64>("threshold")?;
+        samples.iter_mut().for_each(|x| *x = limit(*x, threshold));
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    #[test]
+    fn test_limiter() {
+        let mut limiter = Limiter::new("limiter_test");
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        limiter.connect("output", sender).unwrap();
+        let output = process_once(&mut limiter, &receiver, vec![0.5, -0.8, 0.9, -10.0]);
+        assert_eq!(output[..2], [0.5, -0.8]);
+        assert!(output[2] > 0.8 && output[2] < 0.9);
+        assert!(output[3] < -0.99 && output[3] >= -1.0);
+        // The knee is continuous at the threshold, with unit slope.
+        assert!((limit(0.8 + 1e-9, 0.8) - (0.8 + 1e-9)).abs() < 1e-15);
+
+        limiter.set_parameter_value::<f64>("threshold", 1.0).unwrap();
+        assert_eq!(process_once(&mut limiter, &receiver, vec![0.9, 1.5, -3.0]), vec![0.9, 1.0, -1.0]);
+    }
+}
@@ -1,22 +1,27 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::any::Any;
-use std::io::Write;
+use std::io::{IsTerminal, Write};
 use std::thread;
 use std::process::Command;
 use std::fs;
 use std::path::Path;
 use std::fmt::Debug;
-use std::sync::mpsc::SyncSender;
-use std::sync::{Arc, Mutex, OnceLock};
+use std::net::UdpSocket;
+use std::os::unix::net::UnixDatagram;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
 use chrono::prelude::*;
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use stream_proc_macro::{StreamBlockMacro};
 use data_model::streaming_data::{StreamErrCode, StreamingState};
 use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
-use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor, builtin_commands, execute_builtin_command};
 use data_model::connectors::{ConnectorTrait, Input, Output};
+use crate::task_monitor::TaskManager;
 
-#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize)]
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
 pub enum LogLevel {
     Emergency,
     Alert,
@@ -28,6 +33,51 @@ pub enum LogLevel {
     Debug,
 }
 
+impl LogLevel {
+    /// Levels are ordered from the most severe (`Emergency`) to the least
+    /// severe (`Debug`): an entry is written when it is at least as severe
+    /// as the configured threshold.
+    pub fn is_enabled(&self, threshold: LogLevel) -> bool {
+        *self <= threshold
+    }
+    /// ANSI escape sequence setting the console color of the level.
+    pub fn ansi_color(&self) -> &'static str {
+        match self {
+            LogLevel::Emergency | LogLevel::Alert => "\x1b[1;31m",
+            LogLevel::Critical | LogLevel::Error => "\x1b[31m",
+            LogLevel::Warning => "\x1b[33m",
+            LogLevel::Notice => "\x1b[36m",
+            LogLevel::Info => "\x1b[32m",
+            LogLevel::Debug => "\x1b[90m",
+        }
+    }
+}
+
+const ANSI_RESET: &str = "\x1b[0m";
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum LogFormat {
+    Plain,
+    Json,
+}
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum LogSink {
+    File,
+    Stdout,
+    Stderr,
+    Syslog,
+    /// Keeps the last `capacity` entries, returned by the "dump" command.
+    Memory { capacity: usize },
+}
+
+/// Behaviour of a `LogSender` when the logger input queue is full.
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum LogOverflow {
+    Block,
+    Drop,
+}
+
 #[repr(C)]
 #[derive(Clone)]
 pub struct LogEntry {
@@ -46,6 +96,211 @@ impl LogEntry {
             time: Utc::now(),
         }
     }
+    pub fn to_json(&self) -> String {
+        serde_json::json!({
+            "time": self.time.to_rfc3339(),
+            "level": self.level,
+            "module": self.module,
+            "message": self.message,
+        }).to_string()
+    }
+    /// Formats the entry as a single line, terminated by a newline.
+    pub fn format(&self, format: LogFormat) -> String {
+        match format {
+            LogFormat::Plain => format!("{}[{}]: {}\n", self.time, self.module, self.message),
+            LogFormat::Json => format!("{}\n", self.to_json()),
+        }
+    }
+    /// Plain line with the level tag, colored after the level.
+    pub fn format_colored(&self) -> String {
+        format!("{}{:?}{} {}[{}]: {}\n", self.level.ansi_color(), self.level, ANSI_RESET, self.time, self.module, self.message)
+    }
+}
+
+// Counters shared between the logger and its senders.
+#[derive(Default)]
+struct LogQueue {
+    pending: AtomicUsize,
+    dropped: AtomicU64,
+}
+
+/// Producer side of the logger input, applying the overflow policy
+/// configured when the sender was created.
+#[derive(Clone)]
+pub struct LogSender {
+    sender: SyncSender<LogEntry>,
+    queue: Arc<LogQueue>,
+    overflow: LogOverflow,
+}
+
+impl LogSender {
+    pub fn send(&self, log_entry: LogEntry) -> Result<(), StreamErrCode> {
+        self.queue.pending.fetch_add(1, Ordering::SeqCst);
+        let ret = match self.overflow {
+            LogOverflow::Block => self.sender.send(log_entry).map_err(|_| StreamErrCode::SendDataError),
+            LogOverflow::Drop => match self.sender.try_send(log_entry) {
+                Ok(_) => Ok(()),
+                Err(TrySendError::Full(_)) => {
+                    self.queue.dropped.fetch_add(1, Ordering::SeqCst);
+                    self.queue.pending.fetch_sub(1, Ordering::SeqCst);
+                    return Ok(());
+                }
+                Err(TrySendError::Disconnected(_)) => Err(StreamErrCode::SendDataError),
+            },
+        };
+        if ret.is_err() {
+            self.queue.pending.fetch_sub(1, Ordering::SeqCst);
+        }
+        ret
+    }
+    /// Allows the `log!` macro to be used with a sender as well as with a logger.
+    pub fn get_log_sender(&self) -> Result<LogSender, StreamErrCode> {
+        Ok(self.clone())
+    }
+}
+
+// Snapshot of the file-related parameters, shared with the rotation task.
+#[derive(Clone)]
+struct LogFileConfig {
+    path: String,
+    prefix: String,
+    suffix: String,
+    compress: bool,
+    rotate: bool,
+    size_rotate: u64,
+    time_rotate: f64,
+    // Retention of the rotated files, 0 meaning no limit.
+    max_files: usize,
+    max_total_size: u64,
+    // Input of the logger, on which the compression outcomes are logged.
+    report: SyncSender<LogEntry>,
+    module: &'static str,
+}
+
+struct LogFile {
+    file: Option<fs::File>,
+    name: String,
+    time_start: DateTime<Utc>,
+    // Background compressions, with the file each one compresses.
+    compressions: Vec<(String, JoinHandle<()>)>,
+}
+
+impl LogFile {
+    fn new() -> Self {
+        Self {
+            file: None,
+            name: String::new(),
+            time_start: Utc::now(),
+            compressions: Vec::new(),
+        }
+    }
+    /// Compresses the closed file `path` to `path.xz` in the background. The
+    /// outcome is logged, unless the logger input is full.
+    fn compress(&mut self, path: String, config: &LogFileConfig) {
+        let report = config.report.clone();
+        let module = config.module.to_string();
+        self.compressions.retain(|(_, handle)| !handle.is_finished());
+        let file = path.clone();
+        self.compressions.push((file, thread::spawn(move || {
+            let log_entry = match Command::new("xz").arg(&path).status() {
+                Ok(status) if status.success() =>
+                    LogEntry::new(LogLevel::Info, module, format!("compressed {}", path)),
+                Ok(status) =>
+                    LogEntry::new(LogLevel::Error, module, format!("compression of {} failed: xz {}", path, status)),
+                Err(e) =>
+                    LogEntry::new(LogLevel::Error, module, format!("compression of {} failed: {}", path, e)),
+            };
+            let _ = report.try_send(log_entry);
+        })));
+    }
+    fn start(&mut self, config: &LogFileConfig) -> Result<(), std::io::Error> {
+        if config.compress && Path::new(self.name.as_str()).exists() {
+            self.file = None;
+            self.compress(self.name.clone(), config);
+        }
+        let date_str = Utc::now().to_rfc3339().to_string();
+        self.name = format!("{}/{}_{}.{}", config.path, config.prefix, date_str, config.suffix);
+        self.file = Some(fs::File::create(self.name.as_str())?);
+        self.time_start = Utc::now();
+        Ok(())
+    }
+    fn write_all(&mut self, buf: &[u8]) -> Result<(), std::io::Error> {
+        match self.file.as_mut() {
+            Some(file) => file.write_all(buf),
+            None => Err(std::io::Error::new(std::io::ErrorKind::NotFound, "Log file not opened")),
+        }
+    }
+    fn rotate_if_needed(&mut self, config: &LogFileConfig) -> Result<bool, std::io::Error> {
+        if !config.rotate {
+            return Ok(false);
+        }
+        let file_size = fs::metadata(self.name.as_str())?.len();
+        let elapsed_time = Utc::now().signed_duration_since(self.time_start).num_milliseconds() as f64 * 1e-3;
+        if (config.size_rotate > 0 && file_size >= config.size_rotate) ||
+           (config.time_rotate > 0.0 && elapsed_time >= config.time_rotate) {
+            self.start(config)?;
+            self.remove_old_files(config)?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+    /// Rotated files of this logger, oldest first, ordered by the timestamp in their name.
+    fn rotated_files(&self, config: &LogFileConfig) -> Result<Vec<(DateTime<FixedOffset>, fs::DirEntry)>, std::io::Error> {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&config.path)? {
+            let entry = entry?;
+            if entry.path() == Path::new(self.name.as_str()) {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let stamp = file_name.strip_prefix(&format!("{}_", config.prefix))
+                .map(|name| name.strip_suffix(".xz").unwrap_or(name))
+                .and_then(|name| name.strip_suffix(&format!(".{}", config.suffix)))
+                .and_then(|stamp| DateTime::parse_from_rfc3339(stamp).ok());
+            if let Some(stamp) = stamp {
+                files.push((stamp, entry));
+            }
+        }
+        files.sort_by_key(|(stamp, _)| *stamp);
+        Ok(files)
+    }
+    /// Deletes the oldest rotated files beyond `max_files` or `max_total_size`.
+    /// The current file is never deleted, nor are the files still being
+    /// compressed, which are left out of the count until xz is done.
+    fn remove_old_files(&self, config: &LogFileConfig) -> Result<(), std::io::Error> {
+        if config.max_files == 0 && config.max_total_size == 0 {
+            return Ok(());
+        }
+        let compressing: Vec<&Path> = self.compressions.iter()
+            .filter(|(_, handle)| !handle.is_finished())
+            .map(|(file, _)| Path::new(file.as_str()))
+            .collect();
+        let mut files = self.rotated_files(config)?;
+        files.retain(|(_, entry)| {
+            let path = entry.path();
+            let uncompressed = path.to_str().and_then(|p| p.strip_suffix(".xz")).map(Path::new).unwrap_or(&path);
+            !compressing.contains(&uncompressed)
+        });
+        let mut total_size = 0;
+        let mut sizes = Vec::with_capacity(files.len());
+        for (_, entry) in files.iter() {
+            let size = entry.metadata()?.len();
+            total_size += size;
+            sizes.push(size);
+        }
+        let mut count = files.len();
+        for ((_, entry), size) in files.iter().zip(sizes) {
+            let too_many = config.max_files > 0 && count > config.max_files;
+            let too_large = config.max_total_size > 0 && total_size > config.max_total_size;
+            if !too_many && !too_large {
+                break;
+            }
+            fs::remove_file(entry.path())?;
+            count -= 1;
+            total_size -= size;
+        }
+        Ok(())
+    }
 }
 
 #[derive(StreamBlockMacro)]
@@ -58,8 +313,34 @@ pub struct Logger {
     state:      HashMap<&'static str, Box<dyn DataTrait>>,
     lock:       Arc<Mutex<()>>,
     proc_state: Arc<Mutex<StreamingState>>,
-    log_time_start: DateTime<Utc>,
-    log_file_name: String,
+    log_file: Arc<Mutex<LogFile>>,
+    queue: Arc<LogQueue>,
+    // Parsed `module_levels`, with the JSON it was parsed from.
+    module_levels: (String, HashMap<String, LogLevel>),
+    memory: Mutex<VecDeque<LogEntry>>,
+    syslog: Mutex<Option<SyslogSocket>>,
+}
+
+/// Socket of the Syslog sink, with the address it sends to.
+enum SyslogSocket {
+    Local(UnixDatagram),
+    Remote(UdpSocket, String),
+}
+
+impl SyslogSocket {
+    fn open(address: String) -> Result<Self, std::io::Error> {
+        if address.is_empty() {
+            Ok(SyslogSocket::Local(UnixDatagram::unbound()?))
+        } else {
+            Ok(SyslogSocket::Remote(UdpSocket::bind("0.0.0.0:0")?, address))
+        }
+    }
+    fn send(&self, datagram: &[u8]) -> Result<usize, std::io::Error> {
+        match self {
+            SyslogSocket::Local(socket) => socket.send_to(datagram, "/dev/log"),
+            SyslogSocket::Remote(socket, address) => socket.send_to(datagram, address.as_str()),
+        }
+    }
 }
 
 impl Logger {
@@ -77,68 +358,192 @@ impl Logger {
             state: HashMap::new(),
             lock: Arc::new(Mutex::new(())),
             proc_state: Arc::new(Mutex::new(StreamingState::Null)),
-            log_time_start: Utc::now(),
-            log_file_name: String::new(),
+            log_file: Arc::new(Mutex::new(LogFile::new())),
+            queue: Arc::new(LogQueue::default()),
+            module_levels: ("{}".to_string(), HashMap::new()),
+            memory: Mutex::new(VecDeque::new()),
+            syslog: Mutex::new(None),
         };
-        logger.new_parameter::<&'static str>("log_file_path", "./log", None).unwrap();
-        logger.new_parameter::<&'static str>("log_file_prefix", "", None).unwrap();
-        logger.new_parameter::<&'static str>("log_file_suffix", "", None).unwrap();
+        logger.new_parameter::<String>("log_file_path", "./log".to_string(), None).unwrap();
+        logger.new_parameter::<String>("log_file_prefix", String::new(), None).unwrap();
+        logger.new_parameter::<String>("log_file_suffix", String::new(), None).unwrap();
         logger.new_parameter::<LogLevel>("log_level", LogLevel::Warning, None).unwrap();
+        // JSON object mapping module names to the LogLevel overriding log_level for them.
+        logger.new_parameter::<String>("module_levels", "{}".to_string(), None).unwrap();
+        logger.new_parameter::<LogFormat>("log_format", LogFormat::Plain, None).unwrap();
+        logger.new_parameter::<Vec<LogSink>>("log_sinks", vec![LogSink::File], None).unwrap();
+        // "host:port" of a syslog server reached over UDP; empty for the local /dev/log.
+        // Read when the syslog socket is opened, by the first syslog line after init.
+        logger.new_parameter::<String>("syslog_address", String::new(), None).unwrap();
+        // Colors the plain lines of the Stdout and Stderr sinks, when they are terminals.
+        logger.new_parameter::<bool>("ansi_color", false, None).unwrap();
+        logger.new_parameter::<LogOverflow>("log_overflow", LogOverflow::Drop, None).unwrap();
         logger.new_parameter::<bool>("log_rotate",  false, None).unwrap();
         logger.new_parameter::<bool>("log_compress", false, None).unwrap();
         logger.new_parameter::<f64>("size_rotate_MB",  500.0, None).unwrap();
         logger.new_parameter::<f64>("time_rotate_sec", 24.0*60.0*60.0, None).unwrap();
+        logger.new_parameter::<usize>("max_log_files", 0, None).unwrap();
+        logger.new_parameter::<f64>("max_total_size_MB", 0.0, None).unwrap();
         logger.new_input::<LogEntry>("log_entry").unwrap();
         logger.new_output::<LogEntry>("log_redirect").unwrap();
         logger
     }
-    fn get_file_path(&self) -> String {
-        let date_str = Utc::now().to_rfc3339().to_string();
-        let file_name = format!("{}/{}_{}.{}",
-                                self.get_parameter_value::<&'static str>("log_file_path").unwrap(),
-                                self.get_parameter_value::<&'static str>("log_file_prefix").unwrap(),
-                                date_str,
-                                self.get_parameter_value::<&'static str>("log_file_suffix").unwrap());
-        file_name
-    }
-
-    fn start_log_file(&mut self) -> Result<(), std::io::Error> {
-        if self.get_parameter_value::<bool>("log_compress").unwrap() {
-            if Path::new(self.log_file_name.as_str()).exists() {
-                let path = self.log_file_name.clone();
-                thread::spawn( move || {
-                    Command::new("xz").arg(format!("{}",path));
-                });
+    fn get_file_config(&self) -> Result<LogFileConfig, StreamErrCode> {
+        Ok(LogFileConfig {
+            path: self.get_parameter_value::<String>("log_file_path")?,
+            prefix: self.get_parameter_value::<String>("log_file_prefix")?,
+            suffix: self.get_parameter_value::<String>("log_file_suffix")?,
+            compress: self.get_parameter_value::<bool>("log_compress")?,
+            rotate: self.get_parameter_value::<bool>("log_rotate")?,
+            size_rotate: (self.get_parameter_value::<f64>("size_rotate_MB")? * 1024.0 * 1024.0) as u64,
+            time_rotate: self.get_parameter_value::<f64>("time_rotate_sec")?,
+            max_files: self.get_parameter_value::<usize>("max_log_files")?,
+            max_total_size: (self.get_parameter_value::<f64>("max_total_size_MB")? * 1024.0 * 1024.0) as u64,
+            report: self.get_input_channel::<LogEntry>("log_entry")?,
+            module: self.name,
+        })
+    }
+
+    /// Returns a sender for the logger input. The `log_overflow` policy is
+    /// read when the sender is created.
+    pub fn get_log_sender(&self) -> Result<LogSender, StreamErrCode> {
+        Ok(LogSender {
+            sender: self.get_input_channel::<LogEntry>("log_entry")?,
+            queue: self.queue.clone(),
+            overflow: self.get_parameter_value::<LogOverflow>("log_overflow")?,
+        })
+    }
+
+    fn has_file_sink(&self) -> Result<bool, StreamErrCode> {
+        Ok(self.get_parameter_value::<Vec<LogSink>>("log_sinks")?.contains(&LogSink::File))
+    }
+
+    fn write_sink(&self, sink: LogSink, log_entry: &LogEntry, buf: &[u8]) -> Result<(), std::io::Error> {
+        match sink {
+            LogSink::File => self.log_file.lock().unwrap().write_all(buf),
+            LogSink::Stdout => std::io::stdout().lock().write_all(buf),
+            LogSink::Stderr => std::io::stderr().lock().write_all(buf),
+            LogSink::Syslog => self.write_syslog(log_entry.level, buf),
+            LogSink::Memory { capacity } => {
+                let mut memory = self.memory.lock().unwrap();
+                memory.push_back(log_entry.clone());
+                while memory.len() > capacity {
+                    memory.pop_front();
+                }
+                Ok(())
             }
         }
-        self.log_file_name = self.get_file_path();
-        let file = fs::File::create(self.log_file_name.as_str());
-        if file.is_err() {
-            return Err(file.err().unwrap());
+    }
+    /// Entries kept by the Memory sink, oldest first, as a JSON array.
+    pub fn dump(&self) -> String {
+        let entries: Vec<String> = self.memory.lock().unwrap().iter().map(LogEntry::to_json).collect();
+        format!("[{}]", entries.join(","))
+    }
+    /// Sends `buf` as a syslog datagram of the user facility. The LogLevel
+    /// values are the syslog severities, from 0 (`Emergency`) to 7 (`Debug`).
+    fn write_syslog(&self, level: LogLevel, buf: &[u8]) -> Result<(), std::io::Error> {
+        let mut datagram = format!("<{}>{}: ", 8 + level as u8, self.name).into_bytes();
+        datagram.extend_from_slice(buf.strip_suffix(b"\n").unwrap_or(buf));
+        let mut syslog = self.syslog.lock().unwrap();
+        if syslog.is_none() {
+            *syslog = Some(self.open_syslog()?);
         }
-        
-        LOG_FILE.set(Mutex::new(file.unwrap())).unwrap();
-        
+        syslog.as_ref().unwrap().send(&datagram)?;
         Ok(())
     }
+    fn open_syslog(&self) -> Result<SyslogSocket, std::io::Error> {
+        let address = self.get_parameter_value::<String>("syslog_address")
+            .map_err(|e| std::io::Error::other(format!("{:?}", e)))?;
+        SyslogSocket::open(address)
+    }
+    fn is_terminal(sink: LogSink) -> bool {
+        match sink {
+            LogSink::File | LogSink::Syslog | LogSink::Memory { .. } => false,
+            LogSink::Stdout => std::io::stdout().is_terminal(),
+            LogSink::Stderr => std::io::stderr().is_terminal(),
+        }
+    }
 
-    pub fn rotate_log_file(&mut self) -> Result<(), std::io::Error> {
-        loop {
-            thread::sleep(std::time::Duration::from_secs(1));
-            let size_rotate = self.get_parameter_value::<u64>("size_rotate").unwrap();
-            let time_rotate = self.get_parameter_value::<u64>("time_rotate").unwrap();
-            let rotate_enabled = self.get_parameter_value::<bool>("log_rotate").unwrap();
-            if rotate_enabled {
-                let metadata = fs::metadata(self.log_file_name.as_str())?;
-                let file_size = metadata.len();
-                let elapsed_time = Utc::now().signed_duration_since(self.log_time_start).num_seconds() as u64;
-                if (size_rotate > 0 && file_size >= size_rotate) ||
-                   (time_rotate > 0 && elapsed_time >= time_rotate) {
-                    self.start_log_file()?;
-                    self.log_time_start = Utc::now();
+    /// Threshold for the entries of `module`: its `module_levels` override, or
+    /// `log_level`. Invalid `module_levels` are reported once and ignored.
+    fn module_threshold(&mut self, module: &str) -> Result<LogLevel, StreamErrCode> {
+        let raw = self.get_parameter_value::<String>("module_levels")?;
+        if raw != self.module_levels.0 {
+            let levels = serde_json::from_str::<HashMap<String, LogLevel>>(&raw).unwrap_or_else(|e| {
+                eprintln!("Invalid module_levels {}: {}", raw, e);
+                HashMap::new()
+            });
+            self.module_levels = (raw, levels);
+        }
+        match self.module_levels.1.get(module) {
+            Some(level) => Ok(*level),
+            None => self.get_parameter_value::<LogLevel>("log_level"),
+        }
+    }
+
+    fn write_entry(&mut self, log_entry: LogEntry) -> Result<(), StreamErrCode> {
+        if !log_entry.level.is_enabled(self.module_threshold(&log_entry.module)?) {
+            return Ok(());
+        }
+        let log_format = self.get_parameter_value::<LogFormat>("log_format")?;
+        let log_string = log_entry.format(log_format);
+        let colored_string = if log_format == LogFormat::Plain && self.get_parameter_value::<bool>("ansi_color")? {
+            Some(log_entry.format_colored())
+        } else {
+            None
+        };
+        let sinks = self.get_parameter_value::<Vec<LogSink>>("log_sinks")?;
+        // A failing sink is skipped; the block fails only when no sink accepted the line.
+        let mut written = 0;
+        {
+            let _lock = self.lock.lock().unwrap();
+            for sink in sinks.iter() {
+                let line = match &colored_string {
+                    Some(colored) if Self::is_terminal(*sink) => colored,
+                    _ => &log_string,
+                };
+                match self.write_sink(*sink, &log_entry, line.as_bytes()) {
+                    Ok(_) => {written += 1;}
+                    Err(e) => {eprintln!("Log sink {:?} error: {}", sink, e);}
                 }
             }
         }
+        if written == 0 {
+            self.set_state(StreamingState::Stopped);
+            return Err(StreamErrCode::WriteError);
+        }
+        // Sending without subscribers is a no-op; a vanished subscriber must not stop the logging.
+        let _ = self.send_output::<LogEntry>("log_redirect", log_entry);
+        Ok(())
+    }
+
+    fn start_log_file(&mut self) -> Result<(), StreamErrCode> {
+        let config = self.get_file_config()?;
+        self.log_file.lock().unwrap().start(&config).map_err(|_| StreamErrCode::CreateError)
+    }
+
+    /// Checks the current log file against the rotation limits once and
+    /// opens a new file if one of them is exceeded.
+    pub fn rotate_log_file(&mut self) -> Result<bool, StreamErrCode> {
+        let config = self.get_file_config()?;
+        self.log_file.lock().unwrap().rotate_if_needed(&config).map_err(|_| StreamErrCode::CreateError)
+    }
+
+    /// Spawns the task checking the rotation limits every second until the
+    /// logger is stopped. Parameters are read when the task starts.
+    pub fn start_rotation_task(&self) -> Result<JoinHandle<()>, StreamErrCode> {
+        let config = self.get_file_config()?;
+        let log_file = self.log_file.clone();
+        let proc_state = self.proc_state.clone();
+        let mut tm = TaskManager::get().lock().unwrap();
+        tm.create_task(format!("{}_rotation", self.name), move || {
+            while *proc_state.lock().unwrap() != StreamingState::Stopped {
+                thread::sleep(std::time::Duration::from_secs(1));
+                if let Err(e) = log_file.lock().unwrap().rotate_if_needed(&config) {
+                    eprintln!("Log rotation error: {}", e);
+                }
+            }
+        }).map_err(|_| StreamErrCode::TaskError)
     }
 }
 
@@ -146,18 +551,18 @@ impl StreamProcessor for Logger {
     // Implementazione dei metodi del trait StreamProcessor
     fn init(&mut self) -> Result<(), StreamErrCode> {
         // Implementazione specifica per Logger
-        let ret = self.get_parameter_value::<&'static str>("log_file_path");
-        let binding: &'static str;
+        let ret = self.get_parameter_value::<String>("log_file_path");
+        let binding: String;
         match ret {
             Ok(b) => {binding = b;},
             Err(_) => {
                 self.set_state(StreamingState::Stopped);
-                return Err(StreamErrCode::GenericError);
+                return Err(StreamErrCode::InvalidParameter);
             }
         }
-        let path = Path::new(binding);
+        let path = Path::new(&binding);
         if !path.exists() {
-            let res = fs::create_dir(binding);
+            let res = fs::create_dir(&binding);
             match res {
                 Ok(_) => {}
                 Err(_) => {
@@ -171,58 +576,71 @@ impl StreamProcessor for Logger {
                 return Err(StreamErrCode::PathError);
             }
         }
+        // Reopened by the next syslog line, with the current syslog_address.
+        *self.syslog.lock().unwrap() = None;
         self.set_state(StreamingState::Initial);
         Ok(())
     }
 
     fn run(&mut self) -> Result<(), StreamErrCode> {
-        self.start_log_file().map_err(|_| StreamErrCode::CreateError)?;
+        let file_sink = self.has_file_sink()?;
+        if file_sink {
+            self.start_log_file()?;
+        }
         self.set_state(StreamingState::Running);
-        self.log_time_start = Utc::now();
-        while self.check_state(StreamingState::Running) {
-            self.process()?;
+        let rotation_handle = if file_sink {
+            Some(self.start_rotation_task()?)
+        } else {
+            None
+        };
+        while !self.check_state(StreamingState::Stopped) {
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(std::time::Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process_step() {
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
+        }
+        if let Some(handle) = rotation_handle {
+            let _ = handle.join();
         }
         Ok(())
     }
 
     fn process(&mut self) -> Result<(), StreamErrCode> {
-        let mut error: bool = false; 
-        let input = self.recv_input::<LogEntry>("log_entry");
-        match input {
-            Ok(log_entry) => {
-                if log_entry.level < self.get_parameter_value::<LogLevel>("log_level").unwrap() {
-                    let log_string = format!("{}[{}]: {}\n",
-                                                log_entry.time,
-                                                log_entry.module,
-                                                log_entry.message);
-                    let _lock = self.lock.lock().unwrap();
-                    {
-                        let res = LOG_FILE.get().unwrap().lock().unwrap().write_all(log_string.as_bytes());
-                        match res {
-                            Ok(_) => {}
-                            Err(_) => {
-                                error = true;
-                            }
-                        }
-                    }
-                }
-                let _ = self.send_output::<LogEntry>("log_redirect", log_entry.clone());
-                if error {
-                    self.set_state(StreamingState::Stopped);
-                    return Err(StreamErrCode::WriteError);
-
-                } else {
-                    return Ok(());
-                }
+        let log_entry = self.recv_input::<LogEntry>("log_entry")?;
+        // Entries sent directly on the input channel are not counted as pending.
+        let _ = self.queue.pending.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| n.checked_sub(1));
+        self.write_entry(log_entry)?;
+        if self.queue.pending.load(Ordering::SeqCst) == 0 {
+            let dropped = self.queue.dropped.swap(0, Ordering::SeqCst);
+            if dropped > 0 {
+                let message = format!("dropped {} log entries", dropped);
+                self.write_entry(LogEntry::new(LogLevel::Warning, self.name.to_string(), message))?;
             }
-            Err(e) => {return Err(e);}
         }
+        Ok(())
     }
     fn stop(&mut self) -> Result<(), StreamErrCode> {
         self.set_state(StreamingState::Stopped);
         thread::sleep(std::time::Duration::from_secs(1));
         Ok(())
     }
+    fn execute_command(&mut self, command: &str, args: Vec<&str>) -> Result<String, StreamErrCode> {
+        match command {
+            "dump" if args.is_empty() => Ok(self.dump()),
+            _ => execute_builtin_command(self, command, args),
+        }
+    }
+    fn list_commands(&self) -> Vec<(&'static str, &'static str)> {
+        let mut commands = builtin_commands();
+        commands.push(("dump", "Returns the entries of the Memory sink as JSON"));
+        commands
+    }
 }
 
 #[macro_export]
@@ -230,8 +648,7 @@ macro_rules! log {
     ($logger:expr, $level:expr, $module:expr, $message:expr) => {
         {
             let log_entry = LogEntry::new($level, $module.to_string(), $message.to_string());
-            let input = $logger.get_input_channel::<LogEntry>("log_entry").unwrap();
-            let _ = input.send(log_entry);
+            let _ = $logger.get_log_sender().unwrap().send(log_entry);
         }
     };
 }
@@ -243,11 +660,12 @@ mod test {
     #[test]
     fn test_logger() {
         let mut logger = Logger::new(Some("TestLogger"));
-        logger.set_parameter_value("log_file_path", "./test_logs").unwrap();
-        logger.set_parameter_value("log_file_prefix", "test_log").unwrap();
-        logger.set_parameter_value("log_file_suffix", "log").unwrap();
+        logger.set_parameter_value("log_file_path", "./test_logs".to_string()).unwrap();
+        logger.set_parameter_value("log_file_prefix", "test_log".to_string()).unwrap();
+        logger.set_parameter_value("log_file_suffix", "log".to_string()).unwrap();
         logger.set_parameter_value("log_level", LogLevel::Info).unwrap();
         assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
         let log_entry = LogEntry::new(LogLevel::Info, "TestModule".to_string(), "This is a test log message.".to_string());
         let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
         let (output_test, output_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
@@ -256,7 +674,389 @@ mod test {
         input.send(log_entry).unwrap();
         assert!(logger.process().is_ok());
         output_receiver.recv().unwrap();
+        fs::remove_dir_all("./test_logs").unwrap();
     }
-}
+    #[test]
+    fn test_logger_json_format() {
+        let log_dir = "./test_logs_json";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerJson"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("log_format", LogFormat::Json).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Json \"quoted\" message".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        let file_name = logger.log_file.lock().unwrap().name.clone();
+        let content = fs::read_to_string(file_name).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        assert_eq!(lines.len(), 1);
+        let value: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(value["level"], "Error");
+        assert_eq!(value["module"], "TestModule");
+        assert_eq!(value["message"], "Json \"quoted\" message");
+        assert!(DateTime::parse_from_rfc3339(value["time"].as_str().unwrap()).is_ok());
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_redirect() {
+        let log_dir = "./test_logs_redirect";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerRedirect"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("log_level", LogLevel::Warning).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        // Without subscribers the redirect is a no-op.
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Unrouted".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Routed".to_string())).unwrap();
+        input.send(LogEntry::new(LogLevel::Debug, "TestModule".to_string(), "Filtered".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        assert!(logger.process().is_ok());
+        assert_eq!(out_receiver.try_recv().unwrap().message, "Routed");
+        assert!(out_receiver.try_recv().is_err());
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_ansi_color() {
+        let entry = LogEntry::new(LogLevel::Warning, "TestModule".to_string(), "Colored".to_string());
+        let colored = entry.format_colored();
+        assert!(colored.starts_with("\x1b[33mWarning\x1b[0m "));
+        assert!(colored.ends_with("[TestModule]: Colored\n"));
+        assert_eq!(LogLevel::Critical.ansi_color(), LogLevel::Error.ansi_color());
 
-static LOG_FILE: OnceLock<Mutex<fs::File>> = OnceLock::new();
\ No newline at end of file
+        // The file sink is never a terminal, so it keeps the plain lines.
+        let log_dir = "./test_logs_color";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerColor"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("ansi_color", true).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(entry.clone()).unwrap();
+        assert!(logger.process().is_ok());
+        let file_name = logger.log_file.lock().unwrap().name.clone();
+        assert_eq!(fs::read_to_string(file_name).unwrap(), entry.format(LogFormat::Plain));
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_syslog() {
+        let server = UdpSocket::bind("127.0.0.1:0").unwrap();
+        server.set_read_timeout(Some(std::time::Duration::from_secs(5))).unwrap();
+        let mut logger = Logger::new(Some("TestLoggerSyslog"));
+        logger.set_parameter_value("log_sinks", vec![LogSink::Syslog]).unwrap();
+        logger.set_parameter_value("syslog_address", server.local_addr().unwrap().to_string()).unwrap();
+        logger.set_parameter_value("log_format", LogFormat::Json).unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(LogEntry::new(LogLevel::Warning, "TestModule".to_string(), "Forwarded".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        let mut buf = [0u8; 1024];
+        let len = server.recv(&mut buf).unwrap();
+        let datagram = std::str::from_utf8(&buf[..len]).unwrap();
+        // User facility (1) and Warning severity (4).
+        let body = datagram.strip_prefix("<12>TestLoggerSyslog: ").unwrap();
+        let value: serde_json::Value = serde_json::from_str(body).unwrap();
+        assert_eq!(value["message"], "Forwarded");
+        // The socket, and the address it sends to, are kept until the next init.
+        logger.set_parameter_value("syslog_address", "127.0.0.1:9".to_string()).unwrap();
+        input.send(LogEntry::new(LogLevel::Warning, "TestModule".to_string(), "Kept".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        let len = server.recv(&mut buf).unwrap();
+        assert!(std::str::from_utf8(&buf[..len]).unwrap().contains("Kept"));
+        assert_eq!(8 + LogLevel::Emergency as u8, 8);
+        assert_eq!(8 + LogLevel::Debug as u8, 15);
+    }
+    #[test]
+    fn test_logger_pause() {
+        let log_dir = "./test_logs_pause";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerPause"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        assert!(logger.init().is_ok());
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        let state = logger.get_state_handle();
+        let task = thread::spawn(move || logger.run());
+        let timeout = std::time::Duration::from_secs(2);
+        let entry = |message: &str| LogEntry::new(LogLevel::Error, "TestModule".to_string(), message.to_string());
+        input.send(entry("Before")).unwrap();
+        assert_eq!(out_receiver.recv_timeout(timeout).unwrap().message, "Before");
+        *state.lock().unwrap() = StreamingState::Paused;
+        input.send(entry("First")).unwrap();
+        input.send(entry("Second")).unwrap();
+        thread::sleep(std::time::Duration::from_millis(100));
+        // At most the pass already waiting for its input completes while paused.
+        let mut received: Vec<String> = out_receiver.try_iter().map(|e| e.message).collect();
+        assert!(received.len() <= 1);
+        *state.lock().unwrap() = StreamingState::Running;
+        while received.len() < 2 {
+            received.push(out_receiver.recv_timeout(timeout).unwrap().message);
+        }
+        assert_eq!(received, vec!["First", "Second"]);
+        *state.lock().unwrap() = StreamingState::Stopped;
+        assert!(task.join().unwrap().is_ok());
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_memory_sink() {
+        let mut logger = Logger::new(Some("TestLoggerMemory"));
+        logger.set_parameter_value("log_sinks", vec![LogSink::Memory { capacity: 2 }]).unwrap();
+        assert_eq!(logger.execute_command("dump", vec![]), Ok("[]".to_string()));
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        for message in ["First", "Second", "Third"] {
+            input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), message.to_string())).unwrap();
+            assert!(logger.process().is_ok());
+        }
+        let dump: serde_json::Value = serde_json::from_str(&logger.execute_command("dump", vec![]).unwrap()).unwrap();
+        let messages: Vec<&str> = dump.as_array().unwrap().iter().map(|e| e["message"].as_str().unwrap()).collect();
+        assert_eq!(messages, vec!["Second", "Third"]);
+        assert_eq!(dump[0]["level"], "Error");
+        assert!(logger.list_commands().iter().any(|(command, _)| *command == "dump"));
+        assert_eq!(logger.execute_command("dump", vec!["extra"]), Err(StreamErrCode::InvalidOperation));
+
+        // A smaller capacity drops the oldest entries on the next line.
+        logger.set_parameter_value("log_sinks", vec![LogSink::Memory { capacity: 1 }]).unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Fourth".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        let dump: serde_json::Value = serde_json::from_str(&logger.dump()).unwrap();
+        assert_eq!(dump.as_array().unwrap().len(), 1);
+        assert_eq!(dump[0]["message"], "Fourth");
+    }
+    #[test]
+    fn test_logger_module_levels() {
+        let mut logger = Logger::new(Some("TestLoggerModules"));
+        logger.set_parameter_value("log_sinks", vec![LogSink::Stdout]).unwrap();
+        logger.set_parameter_value("module_levels", r#"{"EkfProcess": "Debug", "Noisy": "Error"}"#.to_string()).unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        for (level, module) in [(LogLevel::Debug, "EkfProcess"), (LogLevel::Debug, "Other"), (LogLevel::Warning, "Other"),
+                                (LogLevel::Warning, "Noisy"), (LogLevel::Error, "Noisy")] {
+            input.send(LogEntry::new(level, module.to_string(), format!("{:?}", level))).unwrap();
+            assert!(logger.process().is_ok());
+        }
+        let routed: Vec<(String, String)> = out_receiver.try_iter().map(|entry| (entry.module, entry.message)).collect();
+        assert_eq!(routed, vec![("EkfProcess".to_string(), "Debug".to_string()), ("Other".to_string(), "Warning".to_string()),
+                                ("Noisy".to_string(), "Error".to_string())]);
+
+        // Invalid overrides are ignored.
+        logger.set_parameter_value("module_levels", "not json".to_string()).unwrap();
+        input.send(LogEntry::new(LogLevel::Debug, "EkfProcess".to_string(), "Filtered".to_string())).unwrap();
+        input.send(LogEntry::new(LogLevel::Warning, "EkfProcess".to_string(), "Kept".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        assert!(logger.process().is_ok());
+        assert_eq!(out_receiver.try_recv().unwrap().message, "Kept");
+        assert!(out_receiver.try_recv().is_err());
+    }
+    #[test]
+    fn test_logger_sinks() {
+        let mut logger = Logger::new(Some("TestLoggerSinks"));
+        // The log file is never opened: the file sink fails on every line.
+        logger.set_parameter_value("log_sinks", vec![LogSink::File, LogSink::Stdout]).unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Mirrored".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        assert_eq!(out_receiver.try_recv().unwrap().message, "Mirrored");
+
+        logger.set_parameter_value("log_sinks", vec![LogSink::File]).unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Lost".to_string())).unwrap();
+        assert_eq!(logger.process(), Err(StreamErrCode::WriteError));
+        assert!(out_receiver.try_recv().is_err());
+    }
+    #[test]
+    fn test_logger_overflow() {
+        let log_dir = "./test_logs_overflow";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerOverflow"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(100);
+        logger.connect("log_redirect", out_sender).unwrap();
+        let sender = logger.get_log_sender().unwrap();
+        // The logger is not processing: the producer must not block on the full queue.
+        let (done_sender, done_receiver) = std::sync::mpsc::channel();
+        thread::spawn(move || {
+            for i in 0..200 {
+                log!(sender, LogLevel::Error, "TestModule", format!("Flood {}", i));
+            }
+            done_sender.send(()).unwrap();
+        });
+        assert!(done_receiver.recv_timeout(std::time::Duration::from_secs(5)).is_ok());
+        for _ in 0..50 {
+            assert!(logger.process().is_ok());
+        }
+        let messages: Vec<String> = out_receiver.try_iter().map(|entry| entry.message).collect();
+        assert_eq!(messages.len(), 51);
+        assert_eq!(messages[49], "Flood 49");
+        assert_eq!(messages[50], "dropped 150 log entries");
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_log_level_threshold() {
+        let levels = [LogLevel::Emergency, LogLevel::Alert, LogLevel::Critical, LogLevel::Error,
+                      LogLevel::Warning, LogLevel::Notice, LogLevel::Info, LogLevel::Debug];
+        assert!(LogLevel::Emergency.is_enabled(LogLevel::Warning));
+        assert!(LogLevel::Error.is_enabled(LogLevel::Warning));
+        assert!(LogLevel::Warning.is_enabled(LogLevel::Warning));
+        assert!(!LogLevel::Notice.is_enabled(LogLevel::Warning));
+        assert!(!LogLevel::Debug.is_enabled(LogLevel::Warning));
+        for (t, threshold) in levels.iter().enumerate() {
+            for (l, level) in levels.iter().enumerate() {
+                assert_eq!(level.is_enabled(*threshold), l <= t, "{:?} against {:?}", level, threshold);
+            }
+        }
+    }
+    #[test]
+    fn test_logger_init_creates_directory() {
+        let log_dir = "./test_logs_init";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerInit"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        assert!(logger.init().is_ok());
+        assert!(Path::new(log_dir).is_dir());
+        assert!(logger.check_state(StreamingState::Initial));
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_rotation_by_size() {
+        let log_dir = "./test_logs_rotation";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerRotation"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("log_file_prefix", "rotation".to_string()).unwrap();
+        logger.set_parameter_value("log_file_suffix", "log".to_string()).unwrap();
+        logger.set_parameter_value("log_level", LogLevel::Debug).unwrap();
+        logger.set_parameter_value("log_rotate", true).unwrap();
+        logger.set_parameter_value("size_rotate_MB", 0.0001).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        assert!(!logger.rotate_log_file().unwrap());
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        for _ in 0..5 {
+            let log_entry = LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Message filling the log file.".to_string());
+            input.send(log_entry).unwrap();
+            assert!(logger.process().is_ok());
+        }
+        assert!(logger.rotate_log_file().unwrap());
+        assert_eq!(fs::read_dir(log_dir).unwrap().count(), 2);
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_compression() {
+        let log_dir = "./test_logs_compression";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerCompression"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("log_file_prefix", "compression".to_string()).unwrap();
+        logger.set_parameter_value("log_file_suffix", "log".to_string()).unwrap();
+        logger.set_parameter_value("log_level", LogLevel::Info).unwrap();
+        logger.set_parameter_value("log_rotate", true).unwrap();
+        logger.set_parameter_value("log_compress", true).unwrap();
+        logger.set_parameter_value("size_rotate_MB", 0.00001).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let rotated = logger.log_file.lock().unwrap().name.clone();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Message filling the log file.".to_string())).unwrap();
+        assert!(logger.process().is_ok());
+        assert!(logger.rotate_log_file().unwrap());
+        let compressions: Vec<(String, JoinHandle<()>)> = logger.log_file.lock().unwrap().compressions.drain(..).collect();
+        for (_, handle) in compressions {
+            handle.join().unwrap();
+        }
+        assert!(!Path::new(&rotated).exists());
+        assert!(Path::new(&format!("{}.xz", rotated)).exists());
+
+        // The outcome is logged in the new file.
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        assert!(logger.process().is_ok());
+        let log_entry = out_receiver.try_recv().unwrap();
+        assert_eq!(log_entry.level, LogLevel::Info);
+        assert_eq!(log_entry.message, format!("compressed {}", rotated));
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_retention() {
+        let log_dir = "./test_logs_retention";
+        let _ = fs::remove_dir_all(log_dir);
+        let mut logger = Logger::new(Some("TestLoggerRetention"));
+        logger.set_parameter_value("log_file_path", log_dir.to_string()).unwrap();
+        logger.set_parameter_value("log_file_prefix", "retention".to_string()).unwrap();
+        logger.set_parameter_value("log_file_suffix", "log".to_string()).unwrap();
+        logger.set_parameter_value("log_rotate", true).unwrap();
+        logger.set_parameter_value("size_rotate_MB", 0.00001).unwrap();
+        logger.set_parameter_value("max_log_files", 2usize).unwrap();
+        assert!(logger.init().is_ok());
+        logger.start_log_file().unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        let mut names = vec![logger.log_file.lock().unwrap().name.clone()];
+        for _ in 0..5 {
+            input.send(LogEntry::new(LogLevel::Error, "TestModule".to_string(), "Message filling the log file.".to_string())).unwrap();
+            assert!(logger.process().is_ok());
+            assert!(logger.rotate_log_file().unwrap());
+            names.push(logger.log_file.lock().unwrap().name.clone());
+        }
+        // The current file and the two most recent rotated ones are kept.
+        assert_eq!(fs::read_dir(log_dir).unwrap().count(), 3);
+        for (index, name) in names.iter().enumerate() {
+            assert_eq!(Path::new(name).exists(), index >= names.len() - 3);
+        }
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+    #[test]
+    fn test_logger_retention_compressing() {
+        let log_dir = "./test_logs_retention_compressing";
+        let _ = fs::remove_dir_all(log_dir);
+        fs::create_dir(log_dir).unwrap();
+        let (report, _reports) = std::sync::mpsc::sync_channel::<LogEntry>(1);
+        let config = LogFileConfig {
+            path: log_dir.to_string(),
+            prefix: "retention".to_string(),
+            suffix: "log".to_string(),
+            compress: true,
+            rotate: true,
+            size_rotate: 0,
+            time_rotate: 0.0,
+            max_files: 1,
+            max_total_size: 0,
+            report,
+            module: "TestLoggerRetention",
+        };
+        let names: Vec<String> = ["2024-01-01T00:00:00+00:00", "2024-01-02T00:00:00+00:00", "2024-01-03T00:00:00+00:00"].iter()
+            .map(|stamp| format!("{}/retention_{}.log", log_dir, stamp))
+            .collect();
+        for name in names.iter() {
+            fs::write(name, "rotated").unwrap();
+        }
+        // The oldest file is still being compressed: xz has written part of its output.
+        fs::write(format!("{}.xz", names[0]), "partial").unwrap();
+        let (release, blocked) = std::sync::mpsc::channel::<()>();
+        let mut log_file = LogFile::new();
+        log_file.compressions.push((names[0].clone(), thread::spawn(move || { let _ = blocked.recv(); })));
+        log_file.remove_old_files(&config).unwrap();
+        assert!(Path::new(&names[0]).exists());
+        assert!(Path::new(&format!("{}.xz", names[0])).exists());
+        assert!(!Path::new(&names[1]).exists());
+        assert!(Path::new(&names[2]).exists());
+
+        // Once the compression is over, the file is subject to retention again.
+        release.send(()).unwrap();
+        let (_, handle) = log_file.compressions.pop().unwrap();
+        handle.join().unwrap();
+        log_file.remove_old_files(&config).unwrap();
+        assert!(!Path::new(&names[0]).exists());
+        assert!(Path::new(&names[2]).exists());
+        fs::remove_dir_all(log_dir).unwrap();
+    }
+}
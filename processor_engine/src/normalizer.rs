@@ -0,0 +1,95 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::{Deserialize, Serialize};
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum NormalizeMode {
+    /// Maps the minimum to 0 and the maximum to 1; a constant vector becomes 0.
+    MinMax,
+    /// Divides by the Euclidean norm; a zero vector is left unchanged.
+    UnitNorm,
+}
+
+fn normalize(samples: &mut [f64], mode: NormalizeMode) {
+    match mode {
+        NormalizeMode::MinMax => {
+            let min = samples.iter().copied().fold(f64::INFINITY, f64::min);
+            let max = samples.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+            let range = max - min;
+            samples.iter_mut().for_each(|x| *x = if range > 0.0 { (*x - min) / range } else { 0.0 });
+        }
+        NormalizeMode::UnitNorm => {
+            let norm = samples.iter().map(|x| x * x).sum::<f64>().sqrt();
+            if norm > 0.0 {
+                samples.iter_mut().for_each(|x| *x /= norm);
+            }
+        }
+    }
+}
+
+/// Rescales each input vector on its own, as chosen by the `mode` parameter.
+#[derive(StreamBlockMacro)]
+pub struct Normalizer {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl Normalizer {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<NormalizeMode>("mode", NormalizeMode::MinMax, None).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for Normalizer {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        normalize(&mut samples, self.get_parameter_value::<NormalizeMode>("mode")?);
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    #[test]
+    fn test_normalizer() {
+        let mut normalizer = Normalizer::new("normalizer_test");
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        normalizer.connect("output", sender).unwrap();
+        assert_eq!(process_once(&mut normalizer, &receiver, vec![2.0, 4.0, 3.0]), vec![0.0, 1.0, 0.5]);
+        assert_eq!(process_once(&mut normalizer, &receiver, vec![7.0, 7.0]), vec![0.0, 0.0]);
+        assert!(process_once(&mut normalizer, &receiver, Vec::new()).is_empty());
+        normalizer.set_parameter_value::<NormalizeMode>("mode", NormalizeMode::UnitNorm).unwrap();
+        assert_eq!(process_once(&mut normalizer, &receiver, vec![3.0, -4.0]), vec![0.6, -0.8]);
+        assert_eq!(process_once(&mut normalizer, &receiver, vec![0.0, 0.0]), vec![0.0, 0.0]);
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor, builtin_commands, execute_builtin_command};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Discards its input, measuring the rate at which it arrives: the
+/// `messages_per_second` state is refreshed every `window_ms`.
+#[derive(StreamBlockMacro)]
+pub struct NullSink<T: 'static + Send + Clone> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    window_count: u64,
+    window_start: Instant,
+}
+
+impl<T> NullSink<T> where T: 'static + Send + Clone {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            window_count: 0,
+            window_start: Instant::now(),
+        };
+        ret.new_input::<T>("input").unwrap();
+        ret.new_parameter::<u64>("window_ms", 1000, Some([1, 60000])).unwrap();
+        ret.new_state::<u64>("messages_per_second", 0).unwrap();
+        ret.new_state::<u64>("total_messages", 0).unwrap();
+        ret
+    }
+}
+
+impl<T> StreamProcessor for NullSink<T> where T: 'static + Send + Clone {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        self.recv_input::<T>("input")?;
+        self.window_count += 1;
+        let total = self.get_state_value::<u64>("total_messages")? + 1;
+        self.set_state_value::<u64>("total_messages", total)?;
+        let elapsed = self.window_start.elapsed();
+        if elapsed.as_millis() >= self.get_parameter_value::<u64>("window_ms")? as u128 {
+            let rate = (self.window_count as f64 / elapsed.as_secs_f64()).round() as u64;
+            self.set_state_value::<u64>("messages_per_second", rate)?;
+            self.window_count = 0;
+            self.window_start = Instant::now();
+        }
+        Ok(())
+    }
+    fn execute_command(&mut self, command: &str, args: Vec<&str>) -> Result<String, StreamErrCode> {
+        match command {
+            "reset" if args.is_empty() => {
+                self.set_state_value::<u64>("total_messages", 0)?;
+                self.set_state_value::<u64>("messages_per_second", 0)?;
+                self.window_count = 0;
+                self.window_start = Instant::now();
+                Ok(String::new())
+            }
+            _ => execute_builtin_command(self, command, args),
+        }
+    }
+    fn list_commands(&self) -> Vec<(&'static str, &'static str)> {
+        let mut commands = builtin_commands();
+        commands.push(("reset", "Clears the message counters"));
+        commands
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_null_sink() {
+        let mut sink = NullSink::<Vec<f64>>::new("null_sink_test");
+        sink.set_parameter_value::<u64>("window_ms", 50).unwrap();
+        let input = sink.get_input_channel::<Vec<f64>>("input").unwrap();
+        for _ in 0..10 {
+            input.send(vec![0.0; 4]).unwrap();
+            sink.process().unwrap();
+        }
+        assert_eq!(sink.get_state_value::<u64>("total_messages").unwrap(), 10);
+        assert_eq!(sink.get_state_value::<u64>("messages_per_second").unwrap(), 0);
+
+        std::thread::sleep(std::time::Duration::from_millis(60));
+        input.send(vec![0.0; 4]).unwrap();
+        sink.process().unwrap();
+        assert_eq!(sink.get_state_value::<u64>("total_messages").unwrap(), 11);
+        // 11 messages in a bit more than 60 ms.
+        let rate = sink.get_state_value::<u64>("messages_per_second").unwrap();
+        assert!(rate > 0 && rate <= 184, "rate {}", rate);
+
+        assert!(sink.list_commands().contains(&("reset", "Clears the message counters")));
+        assert_eq!(sink.execute_command("reset", vec![]), Ok(String::new()));
+        assert_eq!(sink.get_state_value::<u64>("total_messages").unwrap(), 0);
+        assert_eq!(sink.get_state_value::<u64>("messages_per_second").unwrap(), 0);
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Adds the `bias` parameter to each sample.
+#[derive(StreamBlockMacro)]
+pub struct Offset {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl Offset {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<f64>("bias", 0.0, None).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for Offset {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        let bias = self.get_parameter_value::<f64>("bias")?;
+        samples.iter_mut().for_each(|x| *x += bias);
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    #[test]
+    fn test_offset() {
+        let mut offset = Offset::new("offset_test");
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        offset.connect("output", sender).unwrap();
+        offset.set_parameter_value::<f64>("bias", -0.5).unwrap();
+        assert_eq!(process_once(&mut offset, &receiver, vec![1.0, 0.5, -1.0]), vec![0.5, 0.0, -1.5]);
+    }
+}
@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+use utils::math::fir_design::{lowpass, WindowType};
+use utils::math::numbers::gcd;
+
+const TAPS_PER_PHASE: usize = 16;
+
+/// Polyphase interpolation by `up` and decimation by `down`: the input is
+/// virtually upsampled by `up`, filtered by a lowpass prototype and only every
+/// `down`-th sample is computed.
+struct Polyphase {
+    up: usize,
+    down: usize,
+    // Prototype of TAPS_PER_PHASE * up coefficients, scaled by `up`.
+    coefficients: Vec<f64>,
+    // Last TAPS_PER_PHASE - 1 input samples.
+    history: Vec<f64>,
+    // Upsampled index of the next output, from the start of the next chunk.
+    next_index: usize,
+}
+
+impl Polyphase {
+    fn new(up: usize, down: usize) -> Self {
+        let coefficients = if up == down {
+            Vec::new()
+        } else {
+            // Cutoff at the lowest of the two Nyquist frequencies, with some room for the transition.
+            let cutoff = 0.45 / up.max(down) as f64;
+            lowpass(TAPS_PER_PHASE * up, cutoff, WindowType::Hamming).iter().map(|c| c * up as f64).collect()
+        };
+        Self { up, down, coefficients, history: vec![0.0; TAPS_PER_PHASE - 1], next_index: 0 }
+    }
+    fn process(&mut self, input: &[f64]) -> Vec<f64> {
+        if self.up == self.down {
+            return input.to_vec();
+        }
+        let mut buffer = std::mem::take(&mut self.history);
+        buffer.extend_from_slice(input);
+        let end = input.len() * self.up;
+        let mut output = Vec::with_capacity(end / self.down + 1);
+        let mut index = self.next_index;
+        while index < end {
+            let (sample, phase) = (index / self.up + TAPS_PER_PHASE - 1, index % self.up);
+            output.push((0..TAPS_PER_PHASE)
+                .map(|tap| self.coefficients[phase + tap * self.up] * buffer[sample - tap])
+                .sum());
+            index += self.down;
+        }
+        self.next_index = index - end;
+        self.history = buffer.split_off(buffer.len() - (TAPS_PER_PHASE - 1));
+        output
+    }
+}
+
+/// Changes the sample rate of a `Vec<f64>` stream by `up / down`, the ratio
+/// being reduced first. The filter state is kept across the input vectors, and
+/// reset when the ratio changes.
+#[derive(StreamBlockMacro)]
+pub struct Resampler {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    polyphase:  Polyphase,
+}
+
+impl Resampler {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            polyphase: Polyphase::new(1, 1),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<usize>("up", 1, Some([1, 1024])).unwrap();
+        ret.new_parameter::<usize>("down", 1, Some([1, 1024])).unwrap();
+        ret
+    }
+    /// Current ratio, reduced to lowest terms.
+    pub fn ratio(&self) -> Result<(usize, usize), StreamErrCode> {
+        let up = self.get_parameter_value::<usize>("up")?;
+        let down = self.get_parameter_value::<usize>("down")?;
+        let divisor = gcd(up as u64, down as u64) as usize;
+        Ok((up / divisor, down / divisor))
+    }
+}
+
+impl StreamProcessor for Resampler {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let input = self.recv_input::<Vec<f64>>("input")?;
+        let (up, down) = self.ratio()?;
+        if (up, down) != (self.polyphase.up, self.polyphase.down) {
+            self.polyphase = Polyphase::new(up, down);
+        }
+        let output = self.polyphase.process(&input);
+        self.send_output::<Vec<f64>>("output", output)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn resampler(name: &'static str, up: usize, down: usize) -> Resampler {
+        let mut resampler = Resampler::new(name);
+        resampler.set_parameter_value::<usize>("up", up).unwrap();
+        resampler.set_parameter_value::<usize>("down", down).unwrap();
+        resampler
+    }
+
+    fn resample(resampler: &mut Resampler, chunks: &[Vec<f64>]) -> Vec<f64> {
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(chunks.len());
+        resampler.connect("output", sender).unwrap();
+        let input = resampler.get_input_channel::<Vec<f64>>("input").unwrap();
+        for chunk in chunks {
+            input.send(chunk.clone()).unwrap();
+            resampler.process().unwrap();
+        }
+        receiver.try_iter().flatten().collect()
+    }
+
+    #[test]
+    fn test_resampler_ratio() {
+        let mut reduced = resampler("resampler_reduced", 6, 4);
+        assert_eq!(reduced.ratio(), Ok((3, 2)));
+        let signal: Vec<f64> = (0..400).map(|n| (n as f64 * 0.05).sin()).collect();
+        let chunks: Vec<Vec<f64>> = signal.chunks(37).map(|c| c.to_vec()).collect();
+        let output = resample(&mut reduced, &chunks);
+        assert_eq!(output.len(), 600);
+        // Chunk boundaries do not change the output.
+        assert_eq!(resample(&mut resampler("resampler_whole", 3, 2), &[signal.clone()]), output);
+        // Equal factors leave the signal untouched.
+        assert_eq!(resample(&mut resampler("resampler_unit", 5, 5), &chunks), signal);
+    }
+
+    #[test]
+    fn test_resampler_tone() {
+        let frequency = 0.02;
+        let signal: Vec<f64> = (0..900).map(|n| (2.0 * std::f64::consts::PI * frequency * n as f64).sin()).collect();
+        let chunks: Vec<Vec<f64>> = signal.chunks(100).map(|c| c.to_vec()).collect();
+        let output = resample(&mut resampler("resampler_tone", 2, 3), &chunks);
+        assert_eq!(output.len(), 600);
+        // The same tone at 2/3 of the rate, delayed by the prototype.
+        let delay = (TAPS_PER_PHASE * 2 - 1) as f64 / 2.0 / 3.0;
+        let resampled_frequency = frequency * 3.0 / 2.0;
+        for (k, value) in output.iter().enumerate().skip(TAPS_PER_PHASE) {
+            let expected = (2.0 * std::f64::consts::PI * resampled_frequency * (k as f64 - delay)).sin();
+            assert!((value - expected).abs() < 0.01, "sample {}: {} != {}", k, value, expected);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Multiplies each sample by the `gain` parameter.
+#[derive(StreamBlockMacro)]
+pub struct Scaler {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl Scaler {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<f64>("gain", 1.0, None).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for Scaler {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let mut samples = self.recv_input::<Vec<f64>>("input")?;
+        let gain = self.get_parameter_value::<f64>("gain")?;
+        samples.iter_mut().for_each(|x| *x *= gain);
+        self.send_output::<Vec<f64>>("output", samples)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test::process_once;
+
+    #[test]
+    fn test_scaler() {
+        let mut scaler = Scaler::new("scaler_test");
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(1);
+        scaler.connect("output", sender).unwrap();
+        assert_eq!(process_once(&mut scaler, &receiver, vec![1.0, -2.0]), vec![1.0, -2.0]);
+        scaler.set_parameter_value::<f64>("gain", 2.5).unwrap();
+        assert_eq!(process_once(&mut scaler, &receiver, vec![1.0, -2.0, 0.0]), vec![2.5, -5.0, 0.0]);
+    }
+}
@@ -0,0 +1,108 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Re-blocks a continuous `Vec<f64>` stream into frames of `frame_size`
+/// samples, starting every `hop` samples: frames overlap when `hop` is below
+/// `frame_size` and samples are skipped when it is above. The samples of an
+/// incomplete frame wait for the next input vectors.
+#[derive(StreamBlockMacro)]
+pub struct SlidingWindow {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    buffer:     Vec<f64>,
+    // Samples still to be dropped before the next frame starts.
+    skip:       usize,
+}
+
+impl SlidingWindow {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            buffer: Vec::new(),
+            skip: 0,
+        };
+        ret.new_input::<Vec<f64>>("input").unwrap();
+        ret.new_output::<Vec<f64>>("output").unwrap();
+        ret.new_parameter::<usize>("frame_size", 256, Some([1, usize::MAX])).unwrap();
+        ret.new_parameter::<usize>("hop", 128, Some([1, usize::MAX])).unwrap();
+        ret
+    }
+}
+
+impl StreamProcessor for SlidingWindow {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let input = self.recv_input::<Vec<f64>>("input")?;
+        let frame_size = self.get_parameter_value::<usize>("frame_size")?;
+        let hop = self.get_parameter_value::<usize>("hop")?;
+        let skipped = self.skip.min(input.len());
+        self.skip -= skipped;
+        self.buffer.extend_from_slice(&input[skipped..]);
+        let mut start = 0;
+        while self.buffer.len() - start >= frame_size {
+            self.send_output::<Vec<f64>>("output", self.buffer[start..start + frame_size].to_vec())?;
+            start += hop;
+            if start > self.buffer.len() {
+                self.skip = start - self.buffer.len();
+                start = self.buffer.len();
+            }
+        }
+        self.buffer.drain(..start);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn frames(name: &'static str, frame_size: usize, hop: usize, chunks: &[Vec<f64>]) -> Vec<Vec<f64>> {
+        let mut window = SlidingWindow::new(name);
+        window.set_parameter_value::<usize>("frame_size", frame_size).unwrap();
+        window.set_parameter_value::<usize>("hop", hop).unwrap();
+        let (sender, receiver) = std::sync::mpsc::sync_channel::<Vec<f64>>(100);
+        window.connect("output", sender).unwrap();
+        let input = window.get_input_channel::<Vec<f64>>("input").unwrap();
+        for chunk in chunks {
+            input.send(chunk.clone()).unwrap();
+            window.process().unwrap();
+        }
+        receiver.try_iter().collect()
+    }
+
+    #[test]
+    fn test_sliding_window() {
+        let signal: Vec<f64> = (0..10).map(|n| n as f64).collect();
+        let overlapping = frames("sliding_window_overlap", 4, 2, &[signal.clone()]);
+        assert_eq!(overlapping, vec![vec![0.0, 1.0, 2.0, 3.0], vec![2.0, 3.0, 4.0, 5.0],
+                                     vec![4.0, 5.0, 6.0, 7.0], vec![6.0, 7.0, 8.0, 9.0]]);
+        // Partial frames wait for the next vectors.
+        let chunks: Vec<Vec<f64>> = signal.chunks(3).map(|c| c.to_vec()).collect();
+        assert_eq!(frames("sliding_window_chunks", 4, 2, &chunks), overlapping);
+
+        // A hop above the frame size skips samples, across vectors too.
+        assert_eq!(frames("sliding_window_gap", 2, 5, &chunks), vec![vec![0.0, 1.0], vec![5.0, 6.0]]);
+        assert_eq!(frames("sliding_window_partial", 3, 3, &[vec![0.0], vec![1.0, 2.0, 3.0, 4.0]]), vec![vec![0.0, 1.0, 2.0]]);
+    }
+}
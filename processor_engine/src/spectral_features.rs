@@ -0,0 +1,130 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+use utils::math::statistics::mean;
+
+/// Frame-level features of a one-sided magnitude spectrum. The bins are taken
+/// as evenly spaced from 0 to `sample_rate / 2`.
+#[derive(StreamBlockMacro)]
+pub struct SpectralFeaturesProcess {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+}
+
+impl SpectralFeaturesProcess {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+        };
+        ret.new_input::<Vec<f64>>("spectrum").unwrap();
+        ret.new_output::<f64>("centroid").unwrap();
+        ret.new_output::<f64>("spread").unwrap();
+        ret.new_output::<f64>("flatness").unwrap();
+        ret.new_output::<f64>("rolloff").unwrap();
+        ret.new_parameter::<f64>("sample_rate", 1.0, None).unwrap();
+        ret.new_parameter::<f64>("rolloff_ratio", 0.85, Some([0.0, 1.0])).unwrap();
+        ret
+    }
+}
+
+/// Centroid, spread, flatness and rolloff of `spectrum`. A silent or empty
+/// spectrum yields all zeros.
+fn spectral_features(spectrum: &[f64], sample_rate: f64, rolloff_ratio: f64) -> (f64, f64, f64, f64) {
+    let total: f64 = spectrum.iter().sum();
+    if spectrum.is_empty() || total <= 0.0 {
+        return (0.0, 0.0, 0.0, 0.0);
+    }
+    let bin_width = if spectrum.len() > 1 { sample_rate / 2.0 / (spectrum.len() - 1) as f64 } else { 0.0 };
+    let frequency = |bin: usize| bin as f64 * bin_width;
+
+    let centroid = spectrum.iter().enumerate().map(|(bin, m)| frequency(bin) * m).sum::<f64>() / total;
+    let spread = (spectrum.iter().enumerate()
+        .map(|(bin, m)| (frequency(bin) - centroid).powi(2) * m)
+        .sum::<f64>() / total).sqrt();
+    // Geometric mean in the log domain: the plain product underflows on long spectra.
+    let geometric = mean(spectrum.iter().map(|m| m.ln()).collect()).exp();
+    let flatness = geometric / mean(spectrum.to_vec());
+
+    let threshold = rolloff_ratio * total;
+    let mut cumulated = 0.0;
+    let mut rolloff_bin = spectrum.len() - 1;
+    for (bin, m) in spectrum.iter().enumerate() {
+        cumulated += m;
+        if cumulated >= threshold {
+            rolloff_bin = bin;
+            break;
+        }
+    }
+    (centroid, spread, flatness, frequency(rolloff_bin))
+}
+
+impl StreamProcessor for SpectralFeaturesProcess {
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let spectrum = self.recv_input::<Vec<f64>>("spectrum")?;
+        let sample_rate = self.get_parameter_value::<f64>("sample_rate")?;
+        let rolloff_ratio = self.get_parameter_value::<f64>("rolloff_ratio")?;
+        let (centroid, spread, flatness, rolloff) = spectral_features(&spectrum, sample_rate, rolloff_ratio);
+        self.send_output::<f64>("centroid", centroid)?;
+        self.send_output::<f64>("spread", spread)?;
+        self.send_output::<f64>("flatness", flatness)?;
+        self.send_output::<f64>("rolloff", rolloff)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_spectral_features() {
+        let mut block = SpectralFeaturesProcess::new("spectral_features");
+        block.set_parameter_value("sample_rate", 1000.0).unwrap();
+        let mut receivers = Vec::new();
+        for key in ["centroid", "spread", "flatness", "rolloff"] {
+            let (sender, receiver) = std::sync::mpsc::sync_channel::<f64>(1);
+            block.connect(key, sender).unwrap();
+            receivers.push(receiver);
+        }
+        let input = block.get_input_channel::<Vec<f64>>("spectrum").unwrap();
+
+        // White spectrum: flat, centred on the middle of the band.
+        input.send(vec![1.0; 101]).unwrap();
+        block.process().unwrap();
+        let features: Vec<f64> = receivers.iter().map(|r| r.try_recv().unwrap()).collect();
+        assert!((features[0] - 250.0).abs() < 1e-9);
+        assert!((features[2] - 1.0).abs() < 1e-9);
+        assert!((features[3] - 425.0).abs() < 1e-9);
+
+        // Single tone at bin 20: all the energy at 100 Hz.
+        let mut tone = vec![0.0; 101];
+        tone[20] = 1.0;
+        input.send(tone).unwrap();
+        block.process().unwrap();
+        let features: Vec<f64> = receivers.iter().map(|r| r.try_recv().unwrap()).collect();
+        assert!((features[0] - 100.0).abs() < 1e-9);
+        assert!(features[1].abs() < 1e-9);
+        assert!(features[2].abs() < 1e-9);
+        assert!((features[3] - 100.0).abs() < 1e-9);
+    }
+}
@@ -35,13 +35,28 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
     if !fields_names {
         return syn::Error::new_spanned(
             name,
-            "Struct must have 'inputs', 'outputs', 'parameters', 'statics', 'state', 'name', 'prooc_state' and 'lock' fields to derive StreamBlockMacro.",
+            "Struct must have 'inputs', 'outputs', 'parameters', 'statics', 'state', 'name', 'proc_state' and 'lock' fields to derive StreamBlockMacro.",
         )
         .to_compile_error()
         .into();
     }
 
     let code_gen = quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            /// Keeps the `bypass` parameter defined exactly while the block has one input and one output.
+            fn sync_bypass_parameter(&mut self) -> Result<(), StreamErrCode> {
+                let single = self.inputs.len() == 1 && self.outputs.len() == 1;
+                let qualified_name: &'static str = Self::get_qualified_name(self, "bypass");
+                if single && !self.parameters.contains_key(qualified_name) {
+                    self.new_parameter::<bool>("bypass", false, None)?;
+                } else if !single && self.parameters.remove(qualified_name).is_some() {
+                    if let Some(mode) = data_model::memory_manager::MemoryManager::get_memory_manager()?.get_memory_current_mode() {
+                        mode.remove_parameters(qualified_name);
+                    }
+                }
+                Ok(())
+            }
+        }
         impl #impl_generics StreamBlockDyn for #name #ty_generics #where_clause {
             fn as_any(&self) -> &dyn Any {
                 self
@@ -56,6 +71,9 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
             fn set_state(&mut self, state: StreamingState){
                 *self.proc_state.lock().unwrap() = state;
             }
+            fn get_state_handle(&self) -> std::sync::Arc<std::sync::Mutex<StreamingState>> {
+                self.proc_state.clone()
+            }
             fn get_input_list(&self) -> Vec<&str>{
                 self.inputs.keys().copied().collect()
             }
@@ -68,6 +86,38 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
             fn get_statics_list(&self) -> Vec<&str>{
                 self.statics.keys().copied().collect()
             }
+            fn get_input_type(&self, key: &str) -> Option<&'static str> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                self.inputs.get(qualified_name).map(|input| input.get_header().type_name)
+            }
+            fn get_output_type(&self, key: &str) -> Option<&'static str> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                self.outputs.get(qualified_name).map(|output| output.get_header().type_name)
+            }
+            fn get_parameter_type(&self, key: &str) -> Option<&'static str> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                self.parameters.get(qualified_name).map(|param| param.get_header().type_name)
+            }
+            fn get_parameters_json(&self) -> serde_json::Value {
+                self.parameters.iter()
+                    .map(|(key, param)| (key.to_string(), param.to_json()))
+                    .collect::<serde_json::Map<String, serde_json::Value>>()
+                    .into()
+            }
+            fn set_parameter_json(&mut self, key: &str, value: &serde_json::Value) -> Result<(), StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                match self.parameters.get_mut(qualified_name) {
+                    Some(param) => param.load_json(value)?,
+                    None => return Err(StreamErrCode::InvalidParameter),
+                }
+                // Keeps the copy of the MemoryManager in step, as Parameter::set_value does.
+                let document = serde_json::json!({"memory_mapped": {"parameters": {qualified_name: value}}});
+                let mut mgr = data_model::memory_manager::MemoryManager::get_memory_manager()?;
+                if let Some(mode) = mgr.get_memory_current_mode() {
+                    mode.load_from_json(&document.to_string())?;
+                }
+                Ok(())
+            }
             fn is_initialized(&self) -> bool {
                 let keys = self.get_statics_list();
                 for k in keys {
@@ -81,6 +131,37 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
             fn get_qualified_name(&self, name: &str) -> &'static str {
                 Box::leak(format!("{}.{}", self.name, name).into_boxed_str())
             }
+            fn get_input_connector(&self, key: &str) -> Result<&dyn ConnectorTrait, StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                match self.inputs.get(qualified_name) {
+                    Some(input) => Ok(input.as_ref()),
+                    None => Err(StreamErrCode::InvalidInput),
+                }
+            }
+            fn connect_input(&mut self, key: &str, input: &dyn ConnectorTrait) -> Result<(), StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                match self.outputs.get_mut(qualified_name) {
+                    Some(output) => output.connect_input(input),
+                    None => Err(StreamErrCode::InvalidOutput),
+                }
+            }
+            fn set_bypass(&mut self, bypass: bool) -> Result<(), StreamErrCode> {
+                if self.inputs.len() != 1 || self.outputs.len() != 1 {
+                    return Err(StreamErrCode::InvalidOperation);
+                }
+                Ok(self.set_parameter_value::<bool>("bypass", bypass)?)
+            }
+            fn is_bypassed(&self) -> bool {
+                self.get_parameter_value::<bool>("bypass").unwrap_or(false)
+            }
+            fn bypass(&mut self) -> Result<(), StreamErrCode> {
+                if self.inputs.len() != 1 || self.outputs.len() != 1 {
+                    return Err(StreamErrCode::InvalidOperation);
+                }
+                let output = self.outputs.values().next().unwrap();
+                let input = self.inputs.values_mut().next().unwrap();
+                input.forward(output.as_ref())
+            }
         }
         impl #impl_generics StreamBlock for #name #ty_generics #where_clause
         {
@@ -90,7 +171,7 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     return Err(StreamErrCode::AlreadyDefined);
                 }
                 self.inputs.insert(qualified_name, Box::new(Input::<V>::new(qualified_name)));
-                Ok(())
+                self.sync_bypass_parameter()
             }
             fn new_output<V: 'static + Send + Clone> (&mut self, key: &'static str) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
@@ -98,30 +179,30 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     return Err(StreamErrCode::AlreadyDefined);
                 }
                 self.outputs.insert(qualified_name, Box::new(Output::<V>::new(qualified_name)));
-                Ok(())
+                self.sync_bypass_parameter()
             }
-            fn new_state<V: 'static + Send + Sync + Clone + Serialize + PartialOrd + Debug> (&mut self, key: &'static str, value: V,) -> Result<(), StreamErrCode> {
+            fn new_state<V: 'static + Send + Sync + Clone + Serialize + serde::de::DeserializeOwned + PartialOrd + Debug> (&mut self, key: &'static str, value: V,) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if self.state.contains_key(qualified_name) {
                     return Err(StreamErrCode::AlreadyDefined);
                 }
-                self.state.insert(qualified_name, Box::new(State::<V>::new(qualified_name, value)));
+                self.state.insert(qualified_name, Box::new(State::<V>::try_new(qualified_name, value)?));
                 Ok(())
             }
-            fn new_parameter<V: 'static + Send + Sync + Clone + Serialize + PartialOrd + Debug> (&mut self, key: &'static str, value: V, limits: Option<[V;2]>) -> Result<(), StreamErrCode> {
+            fn new_parameter<V: 'static + Send + Sync + Clone + Serialize + serde::de::DeserializeOwned + PartialOrd + Debug> (&mut self, key: &'static str, value: V, limits: Option<[V;2]>) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if self.parameters.contains_key(qualified_name) {
                     return Err(StreamErrCode::AlreadyDefined);
                 }
-                self.parameters.insert(qualified_name, Box::new(Parameter::<V>::new(qualified_name, value, limits)));
+                self.parameters.insert(qualified_name, Box::new(Parameter::<V>::try_new(qualified_name, value, limits)?));
                 Ok(())
             }
-            fn new_statics<V: 'static + Send + Sync + Clone + Serialize + PartialOrd + PartialEq+Debug> (&mut self, key: &'static str, value: V, limits: Option<[V;2]>) -> Result<(), StreamErrCode> {
+            fn new_statics<V: 'static + Send + Sync + Clone + Serialize + serde::de::DeserializeOwned + PartialOrd + PartialEq+Debug> (&mut self, key: &'static str, value: V, limits: Option<[V;2]>) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if self.statics.contains_key(qualified_name) {
                     return Err(StreamErrCode::AlreadyDefined);
                 }
-                self.statics.insert(qualified_name, Box::new(Statics::<V>::new(qualified_name, value, limits)));
+                self.statics.insert(qualified_name, Box::new(Statics::<V>::try_new(qualified_name, value, limits)?));
                 Ok(())
             }
             fn get_input<V: Send+Clone> (&self, key: &str) -> Result<&Input<V>, StreamErrCode> {
@@ -204,7 +285,7 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidOutput)
                 }
             }
-            fn get_parameter_value<V:'static + Send + PartialOrd + Clone + Serialize + Sync + Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
+            fn get_parameter_value<V:'static + Send + PartialOrd + Clone + Serialize + serde::de::DeserializeOwned + Sync + Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.parameters.get(qualified_name) {
                     let any_ref: &dyn Any = container.as_ref().as_any();
@@ -217,20 +298,23 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidParameter)
                 }
             }
-            fn set_parameter_value<V:'static + Send + PartialOrd + Clone + Serialize + Sync + Debug>(&mut self, key: &str, value: V) -> Result<(), StreamErrCode> {
+            fn set_parameter_value<V:'static + Send + PartialOrd + Clone + Serialize + serde::de::DeserializeOwned + Sync + Debug>(&mut self, key: &str, value: V) -> Result<(), data_model::streaming_data::StreamingError> {
+                use data_model::streaming_data::StreamingError;
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.parameters.get_mut(qualified_name) {
+                    let type_name = container.get_header().type_name;
                     let any_mut: &mut dyn Any = container.as_mut().as_any_mut();
                     if let Some(param) = any_mut.downcast_mut::<Parameter<V>>() {
                         param.set_value(value)
                     } else {
-                        Err(StreamErrCode::WrongType)
+                        Err(StreamingError::new(StreamErrCode::WrongType, &format!("key {} holds {}, not {}",
+                            qualified_name, type_name, std::any::type_name::<V>())))
                     }
                 } else {
-                    Err(StreamErrCode::InvalidParameter)
+                    Err(StreamingError::new(StreamErrCode::InvalidParameter, &format!("no parameter {}", qualified_name)))
                 }
             }
-            fn set_statics_value<V:'static + Send + Clone + Serialize + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: V) -> Result<(), StreamErrCode> {
+            fn set_statics_value<V:'static + Send + Clone + Serialize + serde::de::DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: V) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.statics.get_mut(qualified_name) {
                     let any_mut: &mut dyn Any = container.as_mut().as_any_mut();
@@ -243,7 +327,7 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidParameter)
                 }
             }
-            fn get_statics_value<V:'static + Send + PartialOrd + Clone + Serialize + Sync + Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
+            fn get_statics_value<V:'static + Send + PartialOrd + Clone + Serialize + serde::de::DeserializeOwned + Sync + Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.statics.get(qualified_name) {
                     let any_ref: &dyn Any = container.as_ref().as_any();
@@ -256,12 +340,13 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidParameter)
                 }
             }
-            fn set_state_value<V:'static + Send + Clone + Serialize + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: V) -> Result<(), StreamErrCode> {
+            fn set_state_value<V:'static + Send + Clone + Serialize + serde::de::DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: V) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.state.get_mut(qualified_name) {
                     let any_mut: &mut dyn Any = container.as_mut().as_any_mut();
                     if let Some(state) = any_mut.downcast_mut::<State<V>>() {
-                        state.set_value(value);
+                        state.set_value(value)?;
+                        state.send();
                         Ok(())
                     } else {
                         Err(StreamErrCode::WrongType)
@@ -270,7 +355,7 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidParameter)
                 }
             }
-            fn get_state_value<V:'static + Send + Clone + Serialize + Sync + PartialOrd + PartialEq+Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
+            fn get_state_value<V:'static + Send + Clone + Serialize + serde::de::DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&self, key: &str) -> Result<V, StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.state.get(qualified_name) {
                     let any_ref: &dyn Any = container.as_ref().as_any();
@@ -283,12 +368,34 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode::InvalidParameter)
                 }
             }
+            fn connect_state<V:'static + Send + Clone + Serialize + serde::de::DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, sender: SyncSender<V>) -> Result<(), StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                if let Some(container) = self.state.get_mut(qualified_name) {
+                    let any_mut: &mut dyn Any = container.as_mut().as_any_mut();
+                    if let Some(state) = any_mut.downcast_mut::<State<V>>() {
+                        state.connect(sender);
+                        Ok(())
+                    } else {
+                        Err(StreamErrCode::WrongType)
+                    }
+                } else {
+                    Err(StreamErrCode::InvalidParameter)
+                }
+            }
             fn recv_input<V: 'static + Send+Clone> (&mut self, key: &str) -> Result<V , StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.inputs.get_mut(qualified_name) {
                     let any_ref : &mut dyn Any = container.as_mut().as_any_mut(); 
                     if let Some(input_container) = any_ref.downcast_mut :: < Input < V >> () { 
-                        input_container.recv() 
+                        // Wait in slices, so that a stop request is seen without another value arriving.
+                        loop {
+                            if let Some(value) = input_container.recv_timeout(std::time::Duration::from_millis(100))? {
+                                return Ok(value);
+                            }
+                            if *self.proc_state.lock().unwrap() == StreamingState::Stopped {
+                                return Err(StreamErrCode::ReceiveDataError);
+                            }
+                        }
                     } else { 
                         Err(StreamErrCode :: WrongType) 
                     }
@@ -296,6 +403,49 @@ pub fn stream_processor_macro_derive(input: TokenStream) -> TokenStream {
                     Err(StreamErrCode :: InvalidInput) 
                 }
             }
+            fn unlock_statics(&mut self, key: &str) -> Result<(), StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                if let Some(statics) = self.statics.get_mut(qualified_name) {
+                    statics.unlock();
+                    Ok(())
+                } else {
+                    Err(StreamErrCode::InvalidStatics)
+                }
+            }
+            fn reset_statics(&mut self, key: &str) -> Result<(), StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                if let Some(statics) = self.statics.get_mut(qualified_name) {
+                    statics.reset_to_default()
+                } else {
+                    Err(StreamErrCode::InvalidStatics)
+                }
+            }
+            fn recv_input_latest<V: 'static + Send+Clone> (&mut self, key: &str) -> Result<Option<V>, StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                if let Some(container) = self.inputs.get_mut(qualified_name) {
+                    let any_ref : &mut dyn Any = container.as_mut().as_any_mut();
+                    if let Some(input_container) = any_ref.downcast_mut::<Input<V>>() {
+                        Ok(input_container.recv_latest())
+                    } else {
+                        Err(StreamErrCode::WrongType)
+                    }
+                } else {
+                    Err(StreamErrCode::InvalidInput)
+                }
+            }
+            fn recv_input_timeout<V: 'static + Send+Clone> (&mut self, key: &str, timeout: std::time::Duration) -> Result<Option<V>, StreamErrCode> {
+                let qualified_name: &'static str = Self::get_qualified_name(self, key);
+                if let Some(container) = self.inputs.get_mut(qualified_name) {
+                    let any_ref : &mut dyn Any = container.as_mut().as_any_mut();
+                    if let Some(input_container) = any_ref.downcast_mut::<Input<V>>() {
+                        input_container.recv_timeout(timeout)
+                    } else {
+                        Err(StreamErrCode::WrongType)
+                    }
+                } else {
+                    Err(StreamErrCode::InvalidInput)
+                }
+            }
             fn send_output<V:'static + Send+Clone> (&self, key: &str, value: V) -> Result<(), StreamErrCode> {
                 let qualified_name: &'static str = Self::get_qualified_name(self, key);
                 if let Some(container) = self.outputs.get(qualified_name) {
@@ -5,32 +5,42 @@ use std::sync::mpsc::SyncSender;
 use std::thread;
 use std::time::Duration;
 
-use data_model::connectors::{Input, Output};
+use data_model::connectors::{ConnectorTrait, Input, Output};
 use data_model::memory_manager::Statics;
 use data_model::memory_manager::Parameter;
-use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::streaming_data::{StreamErrCode, StreamingError, StreamingState};
 
 use serde::Serialize;
+use serde::de::DeserializeOwned;
 
 pub trait StreamBlock {
     fn new_input<T: 'static + Send + Clone> (&mut self, key: &'static str) -> Result<(), StreamErrCode>;
     fn new_output<T: 'static + Send + Clone> (&mut self, key: &'static str) -> Result<(), StreamErrCode>;
-    fn new_state<T: 'static + Send + Send + Sync + Clone + Serialize + PartialOrd + Debug> (&mut self, key: &'static str, value: T) -> Result<(), StreamErrCode>;
-    fn new_parameter<T: 'static + Send + Sync + Clone + Serialize + PartialOrd + Debug> (&mut self, key: &'static str, value: T, limits: Option<[T; 2]>) -> Result<(), StreamErrCode>;
-    fn new_statics<T: 'static + Send + Sync + Clone + Serialize + PartialEq + PartialOrd+ Debug> (&mut self, key: &'static str, value: T, limits: Option<[T; 2]>) -> Result<(), StreamErrCode>;
+    fn new_state<T: 'static + Send + Send + Sync + Clone + Serialize + DeserializeOwned + PartialOrd + Debug> (&mut self, key: &'static str, value: T) -> Result<(), StreamErrCode>;
+    fn new_parameter<T: 'static + Send + Sync + Clone + Serialize + DeserializeOwned + PartialOrd + Debug> (&mut self, key: &'static str, value: T, limits: Option<[T; 2]>) -> Result<(), StreamErrCode>;
+    fn new_statics<T: 'static + Send + Sync + Clone + Serialize + DeserializeOwned + PartialEq + PartialOrd+ Debug> (&mut self, key: &'static str, value: T, limits: Option<[T; 2]>) -> Result<(), StreamErrCode>;
     fn get_input<T: 'static + Send + Clone> (&self, key: &str) -> Result<&Input<T>, StreamErrCode>;
     fn get_output<T: 'static + Send + Clone> (&self, key: &str) -> Result<&Output<T>, StreamErrCode>;
     fn get_parameter<T: 'static + Send + Sync + Clone + Debug> (&self, key: &str) -> Result<&Parameter<T>, StreamErrCode>;
     fn get_statics<T: 'static + Send + Sync + Debug> (&self, key: &str) -> Result<&Statics<T>, StreamErrCode>;
     fn get_input_channel<T: 'static + Send + Any + Clone>(&self, key: &str) -> Result<SyncSender<T>, StreamErrCode>;
     fn connect<T: 'static + Send + Any + Clone>(&mut self, key: &str, sender: SyncSender<T>) -> Result<(), StreamErrCode>;
-    fn set_parameter_value<T: 'static + Send + Clone + PartialOrd + Clone + Serialize + Sync+ Debug>(&mut self, key: &str, value: T) -> Result<(), StreamErrCode>;
-    fn get_parameter_value<T: 'static + Send + Clone + PartialOrd + Clone + Serialize + Sync+Debug>(&self, key: &str) -> Result<T, StreamErrCode>;
-    fn set_statics_value<T: 'static + Send + Clone + Serialize + Sync + Debug + PartialOrd + PartialEq>(&mut self, key: &str, value: T) -> Result<(), StreamErrCode>;
-    fn get_statics_value<T: 'static + Send + Clone + Serialize + Sync + Debug + PartialOrd + PartialEq>(&self, key: &str) -> Result<T, StreamErrCode>;
-    fn set_state_value<T: 'static + Send + Clone + Serialize + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: T) -> Result<(), StreamErrCode>;
-    fn get_state_value<T: 'static + Send + Clone + Serialize + Sync + PartialOrd + PartialEq+Debug>(&self, key: &str) -> Result<T, StreamErrCode>;
+    /// Sets a parameter, with a message telling what is wrong on failure.
+    fn set_parameter_value<T: 'static + Send + Clone + PartialOrd + Clone + Serialize + DeserializeOwned + Sync+ Debug>(&mut self, key: &str, value: T) -> Result<(), StreamingError>;
+    fn get_parameter_value<T: 'static + Send + Clone + PartialOrd + Clone + Serialize + DeserializeOwned + Sync+Debug>(&self, key: &str) -> Result<T, StreamErrCode>;
+    fn set_statics_value<T: 'static + Send + Clone + Serialize + DeserializeOwned + Sync + Debug + PartialOrd + PartialEq>(&mut self, key: &str, value: T) -> Result<(), StreamErrCode>;
+    fn get_statics_value<T: 'static + Send + Clone + Serialize + DeserializeOwned + Sync + Debug + PartialOrd + PartialEq>(&self, key: &str) -> Result<T, StreamErrCode>;
+    fn set_state_value<T: 'static + Send + Clone + Serialize + DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, value: T) -> Result<(), StreamErrCode>;
+    fn get_state_value<T: 'static + Send + Clone + Serialize + DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&self, key: &str) -> Result<T, StreamErrCode>;
+    /// Registers a sender notified with the new value on every `set_state_value`.
+    fn connect_state<T: 'static + Send + Clone + Serialize + DeserializeOwned + Sync + PartialOrd + PartialEq+Debug>(&mut self, key: &str, sender: SyncSender<T>) -> Result<(), StreamErrCode>;
+    fn unlock_statics(&mut self, key: &str) -> Result<(), StreamErrCode>;
+    fn reset_statics(&mut self, key: &str) -> Result<(), StreamErrCode>;
     fn recv_input<T: 'static + Send+Clone> (&mut self, key: &str) -> Result<T, StreamErrCode>;
+    /// Discards the backlog of an input and returns its most recent value, None if empty.
+    fn recv_input_latest<T: 'static + Send+Clone> (&mut self, key: &str) -> Result<Option<T>, StreamErrCode>;
+    /// Waits at most `timeout` for a value of an input, None if none arrived in time.
+    fn recv_input_timeout<T: 'static + Send+Clone> (&mut self, key: &str, timeout: std::time::Duration) -> Result<Option<T>, StreamErrCode>;
     fn send_output<T: 'static +  Send+Clone> (&self, key: &str, value: T) -> Result<(), StreamErrCode>;
 }
 
@@ -39,12 +49,35 @@ pub trait StreamBlockDyn : Send {
     fn as_any_mut(&mut self) -> &mut dyn Any;
     fn check_state(&self, state: StreamingState) -> bool;
     fn set_state(&mut self, state: StreamingState);
+    /// Returns the shared processing state, to control the block from other threads.
+    fn get_state_handle(&self) -> std::sync::Arc<std::sync::Mutex<StreamingState>>;
     fn get_input_list(&self) -> Vec<&str>;
     fn get_output_list(&self) -> Vec<&str>;
     fn get_parameter_list(&self) -> Vec<&str>;
     fn get_statics_list(&self) -> Vec<&str>;
+    /// Returns the type carried by an input, None if there is no such input.
+    fn get_input_type(&self, key: &str) -> Option<&'static str>;
+    /// Returns the type carried by an output, None if there is no such output.
+    fn get_output_type(&self, key: &str) -> Option<&'static str>;
+    /// Returns the type of a parameter, None if there is no such parameter.
+    fn get_parameter_type(&self, key: &str) -> Option<&'static str>;
+    /// Returns the value of every parameter, keyed by qualified name.
+    fn get_parameters_json(&self) -> serde_json::Value;
+    /// Sets a parameter from a JSON value, without naming its type.
+    fn set_parameter_json(&mut self, key: &str, value: &serde_json::Value) -> Result<(), StreamErrCode>;
     fn is_initialized(&self) -> bool;
     fn get_qualified_name(&self, name: &str) -> &'static str;
+    /// Returns an input as a type-erased connector, to wire blocks whose type is unknown.
+    fn get_input_connector(&self, key: &str) -> Result<&dyn ConnectorTrait, StreamErrCode>;
+    /// Connects an output to the input connector of another block. Both must carry the same type.
+    fn connect_input(&mut self, key: &str, input: &dyn ConnectorTrait) -> Result<(), StreamErrCode>;
+    /// Enables or disables the pass-through of the block. Bypass is only
+    /// defined for blocks with exactly one input and one output, which get
+    /// a `bypass` parameter as soon as their connectors are created.
+    fn set_bypass(&mut self, bypass: bool) -> Result<(), StreamErrCode>;
+    fn is_bypassed(&self) -> bool;
+    /// Forwards one value from the input to the output unchanged.
+    fn bypass(&mut self) -> Result<(), StreamErrCode>;
 }
 
 pub trait StreamProcessor: StreamBlockDyn {
@@ -65,10 +98,42 @@ pub trait StreamProcessor: StreamBlockDyn {
         self.set_state(StreamingState::Running);
 
         while !self.check_state(StreamingState::Stopped) {
-            self.process()?;
+            if self.check_state(StreamingState::Paused) {
+                thread::sleep(Duration::from_millis(10));
+                continue;
+            }
+            if let Err(e) = self.process_step() {
+                // A stop request interrupts a pending receive with an error.
+                if self.check_state(StreamingState::Stopped) {
+                    break;
+                }
+                return Err(e);
+            }
         }
         Ok(())
     }
+    /// Suspends the processing of a running block, keeping its channels open.
+    fn pause(&mut self) -> Result<(), StreamErrCode> {
+        if !self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition);
+        }
+        self.set_state(StreamingState::Paused);
+        Ok(())
+    }
+    fn resume(&mut self) -> Result<(), StreamErrCode> {
+        if !self.check_state(StreamingState::Paused) {
+            return Err(StreamErrCode::InvalidStateTransition);
+        }
+        self.set_state(StreamingState::Running);
+        Ok(())
+    }
+    fn process_step(&mut self) -> Result<(), StreamErrCode >{
+        if self.is_bypassed() {
+            self.bypass()
+        } else {
+            self.process()
+        }
+    }
     fn process(&mut self) -> Result<(), StreamErrCode >{
         thread::sleep(Duration::from_millis(100));
         Ok(())
@@ -77,11 +142,47 @@ pub trait StreamProcessor: StreamBlockDyn {
         self.set_state(StreamingState::Stopped);
         Ok(())
     }
+    /// Runs a named command on the block. Blocks adding commands fall back
+    /// to `execute_builtin_command` for the ones they do not handle.
     fn execute_command(&mut self, command: &str, args: Vec<&str>) -> Result<String, StreamErrCode> {
-        Err(StreamErrCode::InvalidOperation)
+        execute_builtin_command(self, command, args)
+    }
+    /// Returns the name and help of each command accepted by `execute_command`.
+    fn list_commands(&self) -> Vec<(&'static str, &'static str)> {
+        builtin_commands()
     }
 }
 
+/// Commands that every block accepts.
+pub fn builtin_commands() -> Vec<(&'static str, &'static str)> {
+    vec![
+        ("pause", "Suspends the processing, keeping the channels open"),
+        ("resume", "Resumes the processing of a paused block"),
+        ("set_parameter", "Sets the parameter <key> to the JSON <value>"),
+    ]
+}
+
+pub fn execute_builtin_command<P: StreamProcessor + ?Sized>(processor: &mut P, command: &str, args: Vec<&str>) -> Result<String, StreamErrCode> {
+    match (command, args.as_slice()) {
+        ("pause", []) => processor.pause().map(|_| String::new()),
+        ("resume", []) => processor.resume().map(|_| String::new()),
+        ("set_parameter", [key, value]) => {
+            let value: serde_json::Value = serde_json::from_str(value).map_err(|_| StreamErrCode::InvalidInput)?;
+            processor.set_parameter_json(key, &value).map(|_| String::new())
+        }
+        ("pause" | "resume" | "set_parameter", _) => Err(StreamErrCode::InvalidInput),
+        _ => Err(StreamErrCode::InvalidOperation),
+    }
+}
+
+/// Connects the output `out_key` of `src` to the input `in_key` of `dst`,
+/// without naming the data type. Fails with `WrongType` if the connectors
+/// carry different types.
+pub fn connect_blocks(src: &mut dyn StreamProcessor, out_key: &str, dst: &dyn StreamProcessor, in_key: &str) -> Result<(), StreamErrCode> {
+    let input = dst.get_input_connector(in_key)?;
+    src.connect_input(out_key, input)
+}
+
 #[repr(C)]
 pub struct StreamProcessorStruct {
     pub name: *const c_char,
@@ -127,4 +228,167 @@ mod test {
         test_block.process();
         assert_eq!(out_receiver.recv().unwrap(), -6.0);
     }
+    #[test]
+    fn test_port_types() {
+        let test_block: Box<dyn StreamProcessor> = Box::new(TestBlock::new("test_types"));
+        assert_eq!(test_block.get_input_type("test_input"), Some("i32"));
+        assert_eq!(test_block.get_output_type("test_output"), Some("f32"));
+        assert_eq!(test_block.get_parameter_type("change_sign"), Some("bool"));
+        assert_eq!(test_block.get_input_type("test_output"), None);
+        assert_eq!(test_block.get_parameter_type("missing"), None);
+    }
+    #[test]
+    fn test_parameter_errors() {
+        let mut test_block = TestBlock::new("test_errors");
+        let error = test_block.set_parameter_value::<i32>("change_sign", 1).unwrap_err();
+        assert_eq!(error.code, StreamErrCode::WrongType);
+        assert_eq!(error.message, "key test_errors.change_sign holds bool, not i32");
+        let error = test_block.set_parameter_value("missing", true).unwrap_err();
+        assert_eq!(error, StreamingError::new(StreamErrCode::InvalidParameter, "no parameter test_errors.missing"));
+    }
+    #[test]
+    fn test_parameters_json() {
+        let mut test_block = TestBlock::new("test_json");
+        assert_eq!(test_block.get_parameters_json(), serde_json::json!({"test_json.change_sign": false, "test_json.bypass": false}));
+        test_block.set_parameter_json("change_sign", &serde_json::json!(true)).unwrap();
+        assert!(test_block.get_parameter_value::<bool>("change_sign").unwrap());
+        assert_eq!(test_block.set_parameter_json("change_sign", &serde_json::json!(1.5)), Err(StreamErrCode::WrongType));
+        assert_eq!(test_block.set_parameter_json("missing", &serde_json::json!(true)), Err(StreamErrCode::InvalidParameter));
+        assert_eq!(test_block.execute_command("set_parameter", vec!["change_sign", "false"]), Ok(String::new()));
+        assert!(!test_block.get_parameter_value::<bool>("change_sign").unwrap());
+        assert_eq!(test_block.execute_command("set_parameter", vec!["change_sign"]), Err(StreamErrCode::InvalidInput));
+    }
+    #[test]
+    fn test_pause_resume() {
+        let mut test_block = TestBlock::new("test_pause");
+        assert_eq!(test_block.pause(), Err(StreamErrCode::InvalidStateTransition));
+        assert_eq!(test_block.resume(), Err(StreamErrCode::InvalidStateTransition));
+        test_block.set_statics_value("sum_value", 0).unwrap();
+        let input = test_block.get_input_channel::<i32>("test_input").unwrap();
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(10);
+        test_block.connect("test_output", out_sender).unwrap();
+        let state = test_block.get_state_handle();
+        let task = thread::spawn(move || {
+            let ret = test_block.run();
+            (test_block, ret)
+        });
+        input.send(1).unwrap();
+        assert_eq!(out_receiver.recv().unwrap(), 1.0);
+        *state.lock().unwrap() = StreamingState::Paused;
+        input.send(2).unwrap();
+        input.send(3).unwrap();
+        thread::sleep(Duration::from_millis(100));
+        // At most the pass already waiting for its input completes while paused.
+        let mut received: Vec<f32> = out_receiver.try_iter().collect();
+        assert!(received.is_empty() || received == vec![2.0]);
+        *state.lock().unwrap() = StreamingState::Running;
+        while received.len() < 2 {
+            received.push(out_receiver.recv().unwrap());
+        }
+        assert_eq!(received, vec![2.0, 3.0]);
+        *state.lock().unwrap() = StreamingState::Stopped;
+        input.send(4).unwrap();
+        let (mut test_block, ret) = task.join().unwrap();
+        assert!(ret.is_ok());
+        test_block.set_state(StreamingState::Running);
+        test_block.pause().unwrap();
+        assert!(test_block.check_state(StreamingState::Paused));
+        test_block.resume().unwrap();
+        assert!(test_block.check_state(StreamingState::Running));
+    }
+    #[test]
+    fn test_stop_interrupts_recv() {
+        let mut test_block = TestBlock::new("test_stop_recv");
+        test_block.set_statics_value("sum_value", 0).unwrap();
+        let state = test_block.get_state_handle();
+        let (done_sender, done_receiver) = std::sync::mpsc::channel();
+        let task = thread::spawn(move || {
+            let ret = test_block.run();
+            done_sender.send(()).unwrap();
+            ret
+        });
+        // The block is parked waiting for an input that never comes.
+        thread::sleep(Duration::from_millis(50));
+        *state.lock().unwrap() = StreamingState::Stopped;
+        assert!(done_receiver.recv_timeout(Duration::from_secs(2)).is_ok());
+        assert!(task.join().unwrap().is_ok());
+    }
+    #[test]
+    fn test_bypass() {
+        use crate::logger::{Logger, LogEntry, LogLevel};
+        // Without an opened log file process() would fail: the entry must go straight to the output.
+        let mut logger = Logger::new(Some("TestBypassLogger"));
+        assert!(!logger.is_bypassed());
+        logger.set_bypass(true).unwrap();
+        assert!(logger.is_bypassed());
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<LogEntry>(10);
+        logger.connect("log_redirect", out_sender).unwrap();
+        let input = logger.get_input_channel::<LogEntry>("log_entry").unwrap();
+        input.send(LogEntry::new(LogLevel::Emergency, "TestModule".to_string(), "bypassed".to_string())).unwrap();
+        assert!(logger.process_step().is_ok());
+        assert!(out_receiver.try_recv().is_ok());
+        logger.set_parameter_value("bypass", false).unwrap();
+        assert!(!logger.is_bypassed());
+
+        let mut test_block = TestBlock::new("test_bypass");
+        test_block.set_bypass(true).unwrap();
+        test_block.get_input::<i32>("test_input").unwrap().sender.send(1).unwrap();
+        assert_eq!(test_block.process_step(), Err(StreamErrCode::WrongType));
+
+        // The parameter is listed before any set_bypass, and only for single input and output blocks.
+        let mut block = TestBlock::new("test_bypass_listed");
+        assert_eq!(block.get_parameters_json()["test_bypass_listed.bypass"], false);
+        block.new_output::<f32>("second_output").unwrap();
+        assert!(block.get_parameters_json().get("test_bypass_listed.bypass").is_none());
+        assert_eq!(block.set_bypass(true), Err(StreamErrCode::InvalidOperation));
+    }
+    #[test]
+    fn test_state_value() {
+        let mut test_block = TestBlock::new("test_state");
+        test_block.new_state::<u32>("counter", 0).unwrap();
+        assert_eq!(test_block.new_state::<u32>("counter", 0), Err(StreamErrCode::AlreadyDefined));
+        let (state_sender, state_receiver) = std::sync::mpsc::sync_channel::<u32>(10);
+        test_block.connect_state("counter", state_sender).unwrap();
+        test_block.set_state_value::<u32>("counter", 3).unwrap();
+        assert_eq!(test_block.get_state_value::<u32>("counter"), Ok(3));
+        assert_eq!(state_receiver.try_recv(), Ok(3));
+        assert_eq!(test_block.get_state_value::<i32>("counter"), Err(StreamErrCode::WrongType));
+        let (wrong_sender, _wrong_receiver) = std::sync::mpsc::sync_channel::<i32>(10);
+        assert_eq!(test_block.connect_state("counter", wrong_sender), Err(StreamErrCode::WrongType));
+    }
+    #[test]
+    fn test_unlock_statics() {
+        let mut test_block = TestBlock::new("test_unlock");
+        test_block.set_statics_value("sum_value", 10).unwrap();
+        assert!(test_block.is_initialized());
+        assert!(test_block.set_statics_value("sum_value", 20).is_err());
+        test_block.unlock_statics("sum_value").unwrap();
+        assert!(!test_block.is_initialized());
+        test_block.set_statics_value("sum_value", 20).unwrap();
+        assert_eq!(test_block.get_statics_value::<i32>("sum_value"), Ok(20));
+        test_block.reset_statics("sum_value").unwrap();
+        assert_eq!(test_block.get_statics_value::<i32>("sum_value"), Ok(0));
+        assert_eq!(test_block.unlock_statics("missing"), Err(StreamErrCode::InvalidStatics));
+    }
+    #[test]
+    fn test_recv_input_latest() {
+        let mut test_block = TestBlock::new("test_latest");
+        assert_eq!(test_block.recv_input_latest::<i32>("test_input"), Ok(None));
+        let input = test_block.get_input_channel::<i32>("test_input").unwrap();
+        for i in 1..=5 {
+            input.send(i).unwrap();
+        }
+        assert_eq!(test_block.recv_input_latest::<i32>("test_input"), Ok(Some(5)));
+        assert_eq!(test_block.recv_input_latest::<i32>("test_input"), Ok(None));
+        assert_eq!(test_block.recv_input_latest::<f32>("test_input"), Err(StreamErrCode::WrongType));
+    }
+    #[test]
+    fn test_recv_input_timeout() {
+        let mut test_block = TestBlock::new("test_timeout");
+        let timeout = std::time::Duration::from_millis(10);
+        assert_eq!(test_block.recv_input_timeout::<i32>("test_input", timeout), Ok(None));
+        test_block.get_input_channel::<i32>("test_input").unwrap().send(3).unwrap();
+        assert_eq!(test_block.recv_input_timeout::<i32>("test_input", timeout), Ok(Some(3)));
+        assert_eq!(test_block.recv_input_timeout::<i32>("missing", timeout), Err(StreamErrCode::InvalidInput));
+    }
 }
\ No newline at end of file
@@ -1,10 +1,10 @@
 use std::collections::{HashMap, VecDeque};
 use std::sync::{Mutex, OnceLock, Arc};
+use std::sync::mpsc::{SyncSender, TrySendError};
 use std::thread::{self, JoinHandle};
 use std::fmt;
 use chrono::{DateTime, Utc};
 use data_model::streaming_data::StreamErrCode;
-use libc::{clock_gettime, clockid_t, pthread_getcpuclockid, pthread_self, pthread_t, timespec};
 use utils::math::statistics::{mean, std_deviation, percentile};
 
 #[repr(C)]
@@ -18,68 +18,130 @@ pub struct TaskStatistics {
     pub p50: f64,
     pub p90: f64,
     pub p99: f64,
+    /// Resident memory of the whole process, as threads share their memory;
+    /// appended last to keep the layout of the previous fields.
+    pub rss_bytes: f64,
+}
+
+/// Source of the CPU time consumed by a thread.
+trait CpuClock: Send + Sync {
+    /// CPU time in seconds, None if it cannot be read.
+    fn cpu_time(&self) -> Option<f64>;
+}
+
+/// Resident memory of the process in bytes, None where it is not available.
+#[cfg(target_os = "linux")]
+pub fn process_rss_bytes() -> Option<f64> {
+    // statm reports sizes in pages, the resident set being the second field.
+    let statm = std::fs::read_to_string("/proc/self/statm").ok()?;
+    let pages: f64 = statm.split_whitespace().nth(1)?.parse().ok()?;
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) };
+    Some(pages * page_size as f64)
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn process_rss_bytes() -> Option<f64> {
+    None
+}
+
+/// CPU time clock of a thread, read through its pthread CPU clock.
+#[cfg(target_os = "linux")]
+struct ThreadCpuClock {
+    clock_id: libc::clockid_t,
+}
+
+#[cfg(target_os = "linux")]
+impl ThreadCpuClock {
+    /// Clock of the calling thread.
+    fn current() -> Self {
+        let mut clock_id: libc::clockid_t = 0;
+        unsafe {
+            libc::pthread_getcpuclockid(libc::pthread_self(), &mut clock_id);
+        }
+        Self { clock_id }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl CpuClock for ThreadCpuClock {
+    fn cpu_time(&self) -> Option<f64> {
+        let mut ts = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+        if unsafe { libc::clock_gettime(self.clock_id, &mut ts) } != 0 {
+            return None;
+        }
+        Some(utils::time::timespec_to_f64(&ts))
+    }
+}
+
+/// Fallback without per-thread CPU clocks: the wall-clock time since the
+/// clock was created, so the occupancy reads as a busy thread.
+#[cfg(not(target_os = "linux"))]
+struct ThreadCpuClock {
+    start: std::time::Instant,
+}
+
+#[cfg(not(target_os = "linux"))]
+impl ThreadCpuClock {
+    fn current() -> Self {
+        Self { start: std::time::Instant::now() }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl CpuClock for ThreadCpuClock {
+    fn cpu_time(&self) -> Option<f64> {
+        Some(self.start.elapsed().as_secs_f64())
+    }
 }
 
-#[repr(C)]
 #[derive(Clone)]
 struct Task {
     pub name: &'static str,
     pub occupacy: VecDeque<f64>,
-    thread_id: pthread_t,
-    cpu_clock_id: clockid_t,
+    /// Published by the task thread once it runs, as the clock must be read on the measured thread.
+    clock: Arc<OnceLock<Box<dyn CpuClock>>>,
     last_cpu_time: f64,
     last_update: DateTime<Utc>
     
 }
 
-unsafe impl Send for Task {}
-unsafe impl Sync for Task {}
-
 impl Task {
-    pub fn new(name: &'static str, thread_id: pthread_t) -> Self {
-        
-        let mut cpu_clock_id: clockid_t = 0;
-        unsafe {
-            pthread_getcpuclockid(thread_id, &mut cpu_clock_id);
-        }
+    pub fn new(name: &'static str, clock: Arc<OnceLock<Box<dyn CpuClock>>>) -> Self {
         Task {
             name,
             occupacy: VecDeque::with_capacity(100),
-            thread_id,
-            cpu_clock_id,
+            clock,
             last_cpu_time: 0.0,
             last_update: Utc::now(),
         }
     }
     pub fn update(&mut self) -> Result<(), StreamErrCode> {
         let mut occupacy: f64 = 0.0;
-        unsafe {
-            let mut ts: timespec = timespec { tv_sec: 0, tv_nsec: 0 };
-            let ts_ptr: *mut timespec = &mut ts as *mut timespec;
-            if clock_gettime(self.cpu_clock_id, ts_ptr) != 0 {
-                return Err(StreamErrCode::TaskError);
+        let Some(clock) = self.clock.get() else {
+            // The thread has not started yet.
+            return Ok(());
+        };
+        let current_cpu_time = clock.cpu_time().ok_or(StreamErrCode::TaskError)?;
+        if self.last_cpu_time == 0.0 {
+            self.last_cpu_time = current_cpu_time;
+            self.last_update = Utc::now();
+        } else {
+            let current_time = Utc::now();
+            let cpu_time_diff = current_cpu_time - self.last_cpu_time;
+            let wall_time_diff = (current_time - self.last_update).num_nanoseconds().unwrap() as f64 * 1e-9;
+            if wall_time_diff > 0.0 {
+                occupacy = cpu_time_diff / wall_time_diff;
             }
-            if self.last_cpu_time == 0.0 {
-                self.last_cpu_time = utils::time::timespec_to_f64(&ts);
-                self.last_update = Utc::now();
-            } else {
-                let current_cpu_time = utils::time::timespec_to_f64(&ts);
-                let current_time = Utc::now();
-                let cpu_time_diff = current_cpu_time - self.last_cpu_time;
-                let wall_time_diff = (current_time - self.last_update).num_nanoseconds().unwrap() as f64 * 1e-9;
-                if wall_time_diff > 0.0 {
-                    occupacy = cpu_time_diff / wall_time_diff;
-                }
-                self.occupacy.push_back(occupacy);
-                if self.occupacy.len() > 100 {
-                    self.occupacy.pop_front();
-                }
-                self.last_cpu_time = current_cpu_time;
-                self.last_update = current_time;
+            self.occupacy.push_back(occupacy);
+            if self.occupacy.len() > 100 {
+                self.occupacy.pop_front();
             }
+            self.last_cpu_time = current_cpu_time;
+            self.last_update = current_time;
         }
         Ok(())
     }
+    /// Statistics of the task; `rss_bytes` is left to the caller, being process-wide.
     pub fn get_stats(&self) -> TaskStatistics {
         let timestamp = Utc::now();
         let mut data: Vec<f64> = self.occupacy.iter().cloned().collect();
@@ -100,6 +162,7 @@ impl Task {
             p50,
             p90,
             p99,
+            rss_bytes: 0.0,
         }
     }
 }
@@ -110,6 +173,7 @@ pub struct TaskManager {
     interval_update: f64,
     interval_statistics: usize,
     send_statistics: bool,
+    statistics_sink: Option<SyncSender<(&'static str, TaskStatistics)>>,
     count_updates: usize,
 }
 
@@ -121,6 +185,7 @@ impl TaskManager {
             interval_update: 0.1,
             interval_statistics: 10,
             send_statistics: false,
+            statistics_sink: None,
             count_updates: 0,
         }
     }
@@ -133,6 +198,21 @@ impl TaskManager {
     pub fn enable_statistics_sending(&mut self, enable: bool) {
         self.send_statistics = enable;
     }
+    /// Sets the channel receiving the statistics of each task when sending is enabled.
+    pub fn set_statistics_sink(&mut self, sink: SyncSender<(&'static str, TaskStatistics)>) {
+        self.statistics_sink = Some(sink);
+    }
+    // Never blocks the monitor: a full sink misses this round, a closed one is dropped.
+    fn push_statistics(&mut self, stats: &HashMap<&'static str, TaskStatistics>) {
+        if let Some(sink) = self.statistics_sink.as_ref() {
+            for (name, task_stats) in stats.iter() {
+                if let Err(TrySendError::Disconnected(_)) = sink.try_send((name, *task_stats)) {
+                    self.statistics_sink = None;
+                    return;
+                }
+            }
+        }
+    }
     pub fn set_statistics_interval(&mut self, interval_statistics: f64) {
         self.interval_statistics = (interval_statistics/self.interval_update) as usize;
     }
@@ -143,9 +223,9 @@ impl TaskManager {
         S: Into<String> + fmt::Display, 
     {
         let builder = thread::Builder::new().name(name.clone().into());  
-        let thread_id = unsafe { pthread_self() };
         let name: &'static str = Box::leak(Box::new(name.to_string().clone()));
-        let task = Task::new(name, thread_id);
+        let clock = Arc::new(OnceLock::new());
+        let task = Task::new(name, clock.clone());
         self.tasks.insert(name, task); 
         self.thread_statics.insert(name, TaskStatistics {
             timestamp: Utc::now().timestamp_millis() as f64 * 1e-3,
@@ -156,8 +236,40 @@ impl TaskManager {
             p50: 0.0,
             p90: 0.0,
             p99: 0.0,
+            rss_bytes: 0.0,
         });
-        builder.spawn(f)
+        builder.spawn(move || {
+            let _ = clock.set(Box::new(ThreadCpuClock::current()) as Box<dyn CpuClock>);
+            f()
+        })
+    }
+    /// Like `create_task`, but a panic of `f` is caught and logged, and `f`
+    /// is run again after `delay`, up to `max_restarts` times. The task
+    /// returns `TaskError` when the last attempt panics too.
+    pub fn create_supervised_task<F, T, S>(&mut self, name: S, max_restarts: usize, delay: std::time::Duration, mut f: F) -> std::io::Result<JoinHandle<Result<T, StreamErrCode>>>
+    where
+        F: FnMut() -> T + Send + 'static,
+        T: Send + 'static,
+        S: Clone + Into<String> + fmt::Display,
+    {
+        let task_name = name.to_string();
+        self.create_task(name, move || {
+            let mut restarts = 0;
+            loop {
+                match std::panic::catch_unwind(std::panic::AssertUnwindSafe(&mut f)) {
+                    Ok(ret) => return Ok(ret),
+                    Err(_) if restarts < max_restarts => {
+                        restarts += 1;
+                        eprintln!("Task '{}' panicked, restart {} of {}", task_name, restarts, max_restarts);
+                        thread::sleep(delay);
+                    }
+                    Err(_) => {
+                        eprintln!("Task '{}' panicked, no restarts left", task_name);
+                        return Err(StreamErrCode::TaskError);
+                    }
+                }
+            }
+        })
     }
 }
 
@@ -176,13 +288,16 @@ pub fn start_task_monitoring() -> JoinHandle<()> {
                 task_manager.count_updates = 0;
             }
             let mut stats_temp: HashMap<&'static str, TaskStatistics> = HashMap::new();
+            // Read once per round: the memory is shared by all the tasks.
+            let rss_bytes = if update_statistics { process_rss_bytes().unwrap_or(0.0) } else { 0.0 };
             for (name, task) in task_manager.tasks.iter_mut() {
                 match task.update() {
                     Ok(_) => {
                         if !update_statistics {
                             continue;
                         }
-                        let stats = task.get_stats();
+                        let mut stats = task.get_stats();
+                        stats.rss_bytes = rss_bytes;
                         stats_temp.insert(&name, stats);
                     }
                     Err(e) => {
@@ -194,8 +309,8 @@ pub fn start_task_monitoring() -> JoinHandle<()> {
                 task_manager.thread_statics.insert(&name, *stats);
             }
             if task_manager.send_statistics && update_statistics {
-                todo!(); // Send statistics to monitoring system
-            }   
+                task_manager.push_statistics(&stats_temp);
+            }
         }
     })
 }
@@ -225,4 +340,67 @@ mod test {
             stats.mean, stats.max, stats.min, stats.std_dev, stats.p50, stats.p90, stats.p99);
         assert!(stats.mean >= 0.0);
     }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_process_rss() {
+        assert!(process_rss_bytes().unwrap() > 0.0);
+    }
+    #[test]
+    #[cfg(target_os = "linux")]
+    fn test_task_clock_thread() {
+        let (busy_sender, busy_receiver) = std::sync::mpsc::sync_channel::<()>(1);
+        let (done_sender, done_receiver) = std::sync::mpsc::sync_channel::<()>(1);
+        let handle = TaskManager::get().lock().unwrap().create_task("clock_thread_task", move || {
+            // Spins on the thread's own clock, whatever the load of the machine.
+            let clock = ThreadCpuClock::current();
+            while clock.cpu_time().unwrap() < 0.2 {}
+            busy_sender.send(()).unwrap();
+            // Keep the thread, and so its CPU clock, alive while it is read.
+            done_receiver.recv().unwrap();
+        }).unwrap();
+        busy_receiver.recv().unwrap();
+        // The clock measures the spawned thread, not the idle creator.
+        let cpu_time = TaskManager::get().lock().unwrap().tasks["clock_thread_task"].clock.get().unwrap().cpu_time().unwrap();
+        assert!(cpu_time >= 0.2);
+        done_sender.send(()).unwrap();
+        handle.join().unwrap();
+    }
+    #[test]
+    fn test_supervised_task() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let handle = TaskManager::get().lock().unwrap().create_supervised_task("supervised_task", 3, std::time::Duration::from_millis(1), move || {
+            if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                panic!("transient failure");
+            }
+            42
+        }).unwrap();
+        assert_eq!(handle.join().unwrap(), Ok(42));
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+
+        let handle = TaskManager::get().lock().unwrap().create_supervised_task("failing_task", 1, std::time::Duration::from_millis(1), || -> i32 {
+            panic!("permanent failure");
+        }).unwrap();
+        assert_eq!(handle.join().unwrap(), Err(StreamErrCode::TaskError));
+    }
+    #[test]
+    fn test_statistics_sink() {
+        let mut task_manager = TaskManager::new();
+        let stats = TaskStatistics { timestamp: 1.0, mean: 0.5, max: 0.9, min: 0.1, std_dev: 0.2, p50: 0.5, p90: 0.8, p99: 0.9, rss_bytes: 0.0 };
+        let batch: HashMap<&'static str, TaskStatistics> = [("sink_task", stats)].into_iter().collect();
+        task_manager.push_statistics(&batch);
+        let (sink, receiver) = std::sync::mpsc::sync_channel(1);
+        task_manager.set_statistics_sink(sink);
+        task_manager.push_statistics(&batch);
+        // The sink is full: this round is skipped without blocking.
+        task_manager.push_statistics(&batch);
+        let (name, received) = receiver.try_recv().unwrap();
+        assert_eq!(name, "sink_task");
+        assert_eq!(received.mean, 0.5);
+        assert!(receiver.try_recv().is_err());
+        drop(receiver);
+        task_manager.push_statistics(&batch);
+        assert!(task_manager.statistics_sink.is_none());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,118 @@
+use std::collections::HashMap;
+use std::any::Any;
+use std::fmt::Debug;
+use std::marker::PhantomData;
+use std::sync::mpsc::SyncSender;
+use std::sync::{Arc, Mutex};
+use serde::Serialize;
+use stream_proc_macro::{StreamBlockMacro};
+use data_model::streaming_data::{StreamErrCode, StreamingState};
+use data_model::memory_manager::{DataTrait, StaticsTrait, State, Parameter, Statics};
+use crate::stream_processor::{StreamBlock, StreamBlockDyn, StreamProcessor};
+use data_model::connectors::{ConnectorTrait, Input, Output};
+
+/// Copies each input message to the outputs `out_0` to `out_{fanout - 1}`,
+/// which are created by `init` from the `fanout` statics. A full branch blocks
+/// the tee, and so all the other branches, until it has room again.
+#[derive(StreamBlockMacro)]
+pub struct Tee<T: 'static + Send + Clone> {
+    name:       &'static str,
+    inputs:     HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    outputs:    HashMap<&'static str, Box<dyn ConnectorTrait>>,
+    parameters: HashMap<&'static str, Box<dyn DataTrait>>,
+    statics:    HashMap<&'static str, Box<dyn StaticsTrait>>,
+    state:      HashMap<&'static str, Box<dyn DataTrait>>,
+    lock:       Arc<Mutex<()>>,
+    proc_state: Arc<Mutex<StreamingState>>,
+    phantom:    PhantomData<T>,
+    branches:   Vec<&'static str>,
+}
+
+impl<T> Tee<T> where T: 'static + Send + Clone {
+    pub fn new(name: &'static str) -> Self {
+        let mut ret = Self {
+            name,
+            inputs: HashMap::new(),
+            outputs: HashMap::new(),
+            parameters: HashMap::new(),
+            statics: HashMap::new(),
+            state: HashMap::new(),
+            lock: Arc::new(Mutex::new(())),
+            proc_state: Arc::new(Mutex::new(StreamingState::Null)),
+            phantom: PhantomData,
+            branches: Vec::new(),
+        };
+        ret.new_input::<T>("input").unwrap();
+        ret.new_statics::<usize>("fanout", 2, Some([1, 256])).unwrap();
+        ret
+    }
+}
+
+impl<T> StreamProcessor for Tee<T> where T: 'static + Send + Clone {
+    fn init(&mut self) -> Result<(), StreamErrCode> {
+        if self.check_state(StreamingState::Running) {
+            return Err(StreamErrCode::InvalidStateTransition)
+        }
+        if !self.is_initialized() {
+            return Err(StreamErrCode::InvalidStatics)
+        }
+        let fanout = self.get_statics_value::<usize>("fanout")?;
+        for branch in self.branches.len()..fanout {
+            let key: &'static str = Box::leak(format!("out_{}", branch).into_boxed_str());
+            self.new_output::<T>(key)?;
+            self.branches.push(key);
+        }
+        self.set_state(StreamingState::Initial);
+        Ok(())
+    }
+    fn process(&mut self) -> Result<(), StreamErrCode> {
+        let input = self.recv_input::<T>("input")?;
+        for branch in self.branches.clone() {
+            self.send_output::<T>(branch, input.clone())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tee() {
+        let mut tee = Tee::<u32>::new("tee_test");
+        tee.set_statics_value::<usize>("fanout", 3).unwrap();
+        assert!(tee.init().is_ok());
+        assert_eq!(tee.connect("out_3", std::sync::mpsc::sync_channel::<u32>(1).0), Err(StreamErrCode::InvalidOutput));
+        let mut receivers = Vec::new();
+        for branch in ["out_0", "out_1", "out_2"] {
+            let (sender, receiver) = std::sync::mpsc::sync_channel::<u32>(1);
+            tee.connect(branch, sender).unwrap();
+            receivers.push(receiver);
+        }
+        let input = tee.get_input_channel::<u32>("input").unwrap();
+        input.send(7).unwrap();
+        tee.process().unwrap();
+        for receiver in &receivers {
+            assert_eq!(receiver.try_recv().unwrap(), 7);
+        }
+
+        // The tee waits for the slowest branch.
+        input.send(8).unwrap();
+        input.send(9).unwrap();
+        let handle = std::thread::spawn(move || {
+            tee.process().unwrap();
+            tee.process().unwrap();
+        });
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        // 9 waits on the full out_0, so it has not reached out_1 yet.
+        assert_eq!(receivers[1].try_recv().unwrap(), 8);
+        assert!(receivers[1].try_recv().is_err());
+        assert_eq!(receivers[0].recv().unwrap(), 8);
+        assert_eq!(receivers[0].recv().unwrap(), 9);
+        assert_eq!(receivers[1].recv().unwrap(), 9);
+        assert_eq!(receivers[2].recv().unwrap(), 8);
+        assert_eq!(receivers[2].recv().unwrap(), 9);
+        handle.join().unwrap();
+    }
+}
@@ -1,7 +1,7 @@
 use std::collections::HashMap;
 use std::any::Any;
 use std::fmt::Debug;
-use std::sync::mpsc::SyncSender;
+use std::sync::mpsc::{Receiver, SyncSender};
 use std::sync::{Arc, Mutex};
 use serde::Serialize;
 use stream_proc_macro::{StreamBlockMacro};
@@ -46,7 +46,7 @@ impl StreamProcessor for TestBlock {
     fn process(&mut self) -> Result<(), StreamErrCode >{
         let change_sign = self.get_parameter_value::<bool>("change_sign").unwrap();
         let sum_value = self.get_statics_value::<i32>("sum_value").unwrap();
-        let value = self.recv_input::<i32>("test_input").unwrap();
+        let value = self.recv_input::<i32>("test_input")?;
         let out_value: f32;
         if !change_sign {
             out_value = (value + sum_value) as f32;
@@ -56,4 +56,16 @@ impl StreamProcessor for TestBlock {
         self.send_output::<f32>("test_output", out_value)
 
     }
-}
\ No newline at end of file
+}
+
+/// Sends `input` to the `input` connector of `block`, runs one pass and
+/// returns what the block sent to `receiver`.
+pub fn process_once<B, T>(block: &mut B, receiver: &Receiver<T>, input: T) -> T
+where
+    B: StreamBlock + StreamProcessor,
+    T: 'static + Send + Any + Clone,
+{
+    block.get_input_channel::<T>("input").unwrap().send(input).unwrap();
+    block.process().unwrap();
+    receiver.try_recv().unwrap()
+}
@@ -0,0 +1,176 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+use chrono::Utc;
+use data_model::streaming_data::StreamErrCode;
+use crate::engine::{ChainHandle, ProcessorChain};
+use crate::logger::{LogEntry, LogLevel, LogSender};
+use crate::task_monitor::TaskManager;
+
+const MODULE: &str = "Watchdog";
+
+/// Builds a fresh chain replacing a stalled one.
+pub type ChainFactory = Box<dyn Fn() -> ProcessorChain + Send>;
+
+struct WatchedChain {
+    handle: ChainHandle,
+    factory: Option<ChainFactory>,
+}
+
+/// Runs chains and stops the ones not completing a pass within the deadline.
+/// A stalled chain is restarted from its factory, if any. The thread of the
+/// stalled chain cannot be interrupted and is left detached.
+pub struct Watchdog {
+    deadline: Duration,
+    chains: Vec<WatchedChain>,
+    log_sender: LogSender,
+}
+
+/// Stops the supervision task started by `Watchdog::start`.
+pub struct WatchdogHandle {
+    exit: Arc<AtomicBool>,
+    task: JoinHandle<Watchdog>,
+}
+
+impl WatchdogHandle {
+    /// Stops checking the chains, which keep running, and gives the watchdog back.
+    pub fn stop(self) -> Result<Watchdog, StreamErrCode> {
+        self.exit.store(true, Ordering::SeqCst);
+        self.task.join().map_err(|_| StreamErrCode::TaskError)
+    }
+}
+
+impl Watchdog {
+    pub fn new(deadline: Duration, log_sender: LogSender) -> Self {
+        Watchdog {
+            deadline,
+            chains: Vec::new(),
+            log_sender,
+        }
+    }
+    fn log(log_sender: &LogSender, level: LogLevel, message: String) {
+        let _ = log_sender.send(LogEntry::new(level, MODULE.to_string(), message));
+    }
+    fn spawn_chain(mut chain: ProcessorChain, log_sender: LogSender) -> Result<ChainHandle, StreamErrCode> {
+        chain.set_running();
+        let handle = chain.handle();
+        *chain.last_output.lock().unwrap() = Utc::now();
+        let mut tm = TaskManager::get().lock().unwrap();
+        tm.create_task(chain.name.clone(), move || {
+            if let Err(e) = chain.run() {
+                Self::log(&log_sender, LogLevel::Error, format!("Chain '{}' error: {}", chain.name, e));
+            }
+        }).map_err(|_| StreamErrCode::TaskError)?;
+        Ok(handle)
+    }
+    /// Starts the chain and puts it under supervision.
+    pub fn add_chain(&mut self, chain: ProcessorChain, factory: Option<ChainFactory>) -> Result<(), StreamErrCode> {
+        let handle = Self::spawn_chain(chain, self.log_sender.clone())?;
+        self.chains.push(WatchedChain { handle, factory });
+        Ok(())
+    }
+    /// Checks every chain once and returns the names of the stalled ones.
+    pub fn check(&mut self) -> Result<Vec<String>, StreamErrCode> {
+        let mut stalled = Vec::new();
+        let mut index = 0;
+        while index < self.chains.len() {
+            let watched = &mut self.chains[index];
+            let elapsed = (Utc::now() - *watched.handle.last_output.lock().unwrap()).to_std().unwrap_or_default();
+            if elapsed <= self.deadline {
+                index += 1;
+                continue;
+            }
+            Self::log(&self.log_sender, LogLevel::Warning,
+                      format!("Chain '{}' stalled for {} ms, stopping it", watched.handle.name, elapsed.as_millis()));
+            watched.handle.stop();
+            stalled.push(watched.handle.name.clone());
+            match &watched.factory {
+                Some(factory) => {
+                    watched.handle = Self::spawn_chain(factory(), self.log_sender.clone())?;
+                    index += 1;
+                }
+                None => {
+                    self.chains.remove(index);
+                }
+            }
+        }
+        Ok(stalled)
+    }
+    /// Spawns the task checking the chains every half deadline, until the
+    /// returned handle is stopped.
+    pub fn start(mut self) -> Result<WatchdogHandle, StreamErrCode> {
+        let exit = Arc::new(AtomicBool::new(false));
+        let task_exit = exit.clone();
+        let mut tm = TaskManager::get().lock().unwrap();
+        let task = tm.create_task("watchdog", move || {
+            while !task_exit.load(Ordering::SeqCst) {
+                thread::sleep(self.deadline / 2);
+                if let Err(e) = self.check() {
+                    Self::log(&self.log_sender, LogLevel::Error, format!("Check failed: {}", e));
+                }
+            }
+            self
+        }).map_err(|_| StreamErrCode::TaskError)?;
+        Ok(WatchdogHandle { exit, task })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::mpsc::SyncSender;
+    use crate::logger::Logger;
+    use crate::test::TestBlock;
+    use crate::stream_processor::StreamBlock;
+
+    fn feeding_chain(name: &'static str, output: SyncSender<f32>) -> ProcessorChain {
+        let mut block = TestBlock::new(name);
+        block.connect("test_output", output).unwrap();
+        let input = block.get_input_channel::<i32>("test_input").unwrap();
+        input.send(1).unwrap();
+        let mut chain = ProcessorChain::new("watched_chain".to_string());
+        chain.add_processor(Box::new(block));
+        chain
+    }
+
+    #[test]
+    fn test_watchdog_restarts_stalled_chain() {
+        let (out_sender, out_receiver) = std::sync::mpsc::sync_channel::<f32>(10);
+        let restarts = Arc::new(AtomicUsize::new(0));
+        let factory_restarts = restarts.clone();
+        let factory_sender = out_sender.clone();
+        let factory: ChainFactory = Box::new(move || {
+            factory_restarts.fetch_add(1, Ordering::SeqCst);
+            feeding_chain("watchdog_restarted", factory_sender.clone())
+        });
+        // The chain processes the single queued value, then blocks on its empty input.
+        let chain = feeding_chain("watchdog_stalled", out_sender);
+        let stalled_chain = chain.handle();
+        let mut logger = Logger::new(Some("watchdog_test_logger"));
+        let mut watchdog = Watchdog::new(Duration::from_millis(100), logger.get_log_sender().unwrap());
+        watchdog.add_chain(chain, Some(factory)).unwrap();
+        assert_eq!(out_receiver.recv_timeout(Duration::from_secs(1)), Ok(1.0));
+        assert!(watchdog.check().unwrap().is_empty());
+
+        thread::sleep(Duration::from_millis(200));
+        assert_eq!(watchdog.check().unwrap(), vec!["watched_chain".to_string()]);
+        assert!(stalled_chain.is_stopped());
+        let entry = logger.recv_input_timeout::<LogEntry>("log_entry", Duration::from_secs(1)).unwrap().unwrap();
+        assert!(entry.to_json().contains("stalled"));
+        assert_eq!(restarts.load(Ordering::SeqCst), 1);
+        // The restarted chain produces again.
+        assert_eq!(out_receiver.recv_timeout(Duration::from_secs(1)), Ok(1.0));
+    }
+
+    #[test]
+    fn test_watchdog_stop() {
+        let logger = Logger::new(Some("watchdog_stop_logger"));
+        let watchdog = Watchdog::new(Duration::from_millis(20), logger.get_log_sender().unwrap());
+        let handle = watchdog.start().unwrap();
+        thread::sleep(Duration::from_millis(30));
+        let watchdog = handle.stop().unwrap();
+        assert!(watchdog.chains.is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use std::borrow::Cow;
+use std::mem;
+
+/// Plain old data: types without padding for which every bit pattern is a
+/// valid value, so they can be read from and written to raw bytes.
+///
+/// # Safety
+/// Implementors must be `Copy`, contain no padding, no pointers and accept
+/// any bit pattern.
+pub unsafe trait Pod: Copy + 'static {}
+
+macro_rules! impl_pod {
+    ($($t:ty),*) => {
+        $(unsafe impl Pod for $t {})*
+    };
+}
+impl_pod!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize, f32, f64);
+
+unsafe impl<T: Pod, const N: usize> Pod for [T; N] {}
+
+/// Views a slice of values as raw bytes, in native endianness.
+pub fn bytes_from_pod_slice<T: Pod>(data: &[T]) -> &[u8] {
+    // SAFETY: T has no padding, so every byte of the slice is initialized.
+    unsafe { std::slice::from_raw_parts(data.as_ptr() as *const u8, mem::size_of_val(data)) }
+}
+
+/// Reads a slice of values from raw bytes. The bytes are borrowed when they are
+/// suitably aligned for `T` and copied otherwise.
+pub fn pod_slice_from_bytes<T: Pod>(data: &[u8]) -> Result<Cow<'_, [T]>, &'static str> {
+    let size = mem::size_of::<T>();
+    if size == 0 || !data.len().is_multiple_of(size) {
+        return Err("Byte length is not a multiple of the element size");
+    }
+    let len = data.len() / size;
+    if data.as_ptr().cast::<T>().is_aligned() {
+        // SAFETY: pointer aligned, length checked and any bit pattern is a valid T.
+        Ok(Cow::Borrowed(unsafe { std::slice::from_raw_parts(data.as_ptr() as *const T, len) }))
+    } else {
+        let values = data
+            .chunks_exact(size)
+            // SAFETY: each chunk holds exactly size_of::<T>() bytes.
+            .map(|chunk| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) })
+            .collect();
+        Ok(Cow::Owned(values))
+    }
+}
+
+/// Views a single value as raw bytes, in native endianness.
+pub fn bytes_from_pod<T: Pod>(value: &T) -> &[u8] {
+    bytes_from_pod_slice(std::slice::from_ref(value))
+}
+
+/// Reads a single value from exactly `size_of::<T>()` raw bytes, whatever
+/// their alignment.
+pub fn pod_from_bytes<T: Pod>(data: &[u8]) -> Result<T, &'static str> {
+    if data.len() != mem::size_of::<T>() {
+        return Err("Byte length does not match the value size");
+    }
+    // SAFETY: length checked and any bit pattern is a valid T.
+    Ok(unsafe { std::ptr::read_unaligned(data.as_ptr() as *const T) })
+}
+
+pub fn bytes_from_f64_slice(data: &[f64]) -> &[u8] {
+    bytes_from_pod_slice(data)
+}
+
+pub fn f64_slice_from_bytes(data: &[u8]) -> Result<Cow<'_, [f64]>, &'static str> {
+    pod_slice_from_bytes(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_f64_round_trip() {
+        let values: Vec<f64> = vec![0.0, -1.5, std::f64::consts::PI, f64::MAX, f64::MIN_POSITIVE];
+        let bytes = bytes_from_f64_slice(&values);
+        assert_eq!(bytes.len(), values.len() * 8);
+        let decoded = f64_slice_from_bytes(bytes).unwrap();
+        assert!(matches!(decoded, Cow::Borrowed(_)));
+        assert_eq!(decoded.as_ref(), values.as_slice());
+
+        // Shift the data by one byte to force the unaligned copy.
+        let mut shifted = vec![0u8];
+        shifted.extend_from_slice(bytes);
+        let decoded = f64_slice_from_bytes(&shifted[1..]).unwrap();
+        assert!(matches!(decoded, Cow::Owned(_)));
+        assert_eq!(decoded.into_owned(), values);
+
+        assert!(f64_slice_from_bytes(&bytes[1..]).is_err());
+        let pairs: Vec<[i16; 2]> = vec![[1, -2], [3, -4]];
+        assert_eq!(pod_slice_from_bytes::<[i16; 2]>(bytes_from_pod_slice(&pairs)).unwrap().as_ref(), pairs.as_slice());
+
+        let value = 0x0102_0304u32;
+        let mut shifted = vec![0u8];
+        shifted.extend_from_slice(bytes_from_pod(&value));
+        assert_eq!(pod_from_bytes::<u32>(&shifted[1..]), Ok(value));
+        assert!(pod_from_bytes::<u32>(&shifted).is_err());
+    }
+}
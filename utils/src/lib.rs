@@ -1,2 +1,3 @@
+pub mod bytes;
 pub mod math;
-pub mod time;
\ No newline at end of file
+pub mod time;
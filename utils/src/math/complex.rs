@@ -1,7 +1,8 @@
 use num_traits::{Float, Zero, One};
+use serde::{Deserialize, Serialize};
 
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Complex<T> {
     pub real: T,
     pub imag: T,
@@ -105,20 +106,20 @@ where T:
         Complex::new(self.real.sin() * self.imag.cosh(), self.real.cos() * self.imag.sinh())
     }
 
+    /// Principal value, `-i ln(iz + sqrt(1 - z^2))`.
     pub fn asin(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sin() / r, theta.cos() / r)
+        let z = *self;
+        let i = Complex::i();
+        -i * (i * z + (Complex::one() - z * z).sqrt()).ln()
     }
 
     pub fn cos(&self) -> Self {
         Complex::new(self.real.cos() * self.imag.cosh(), -self.real.sin() * self.imag.sinh())
     }
 
+    /// Principal value, `pi/2 - asin(z)`.
     pub fn acos(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.cos() / r, -theta.sin() / r)
+        Complex::from(T::from(std::f64::consts::FRAC_PI_2).unwrap()) - self.asin()
     }
 
     pub fn tan(&self) -> Self {
@@ -129,36 +130,38 @@ where T:
         self.sin() / denom
     }
 
+    /// Principal value, `(i/2) (ln(1 - iz) - ln(1 + iz))`.
     pub fn atan(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sin() / r, theta.cos() / r)
+        let z = *self;
+        let i = Complex::i();
+        let half_i = Complex::new(T::zero(), T::from(0.5).unwrap());
+        half_i * ((Complex::one() - i * z).ln() - (Complex::one() + i * z).ln())
     }
 
+    /// The angle of the point `(x, y)`, as a real complex number: the phase of
+    /// `x + iy`.
     pub fn atan2(y: T, x: T) -> Self {
-        let r = (x * x + y * y).sqrt();
-        let theta = y.atan2(x);
-        Complex::new(r, theta)
+        Complex::from(y.atan2(x))
     }
 
     pub fn sinh(&self) -> Self {
         Complex::new(self.real.sinh() * self.imag.cos(), self.real.cosh() * self.imag.sin())
     }
 
+    /// Principal value, `ln(z + sqrt(z^2 + 1))`.
     pub fn asinh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sinh() / r, theta.cosh() / r)
+        let z = *self;
+        (z + (z * z + Complex::one()).sqrt()).ln()
     }
 
     pub fn cosh(&self) -> Self {
         Complex::new(self.real.cosh() * self.imag.cos(), self.real.sinh() * self.imag.sin())
     }
 
+    /// Principal value, `ln(z + sqrt(z + 1) sqrt(z - 1))`.
     pub fn acosh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.cosh() / r, theta.sinh() / r)
+        let z = *self;
+        (z + (z + Complex::one()).sqrt() * (z - Complex::one()).sqrt()).ln()
     }
 
     pub fn tanh(&self) -> Self {
@@ -169,10 +172,11 @@ where T:
         self.sinh() / denom
     }
 
+    /// Principal value, `(ln(1 + z) - ln(1 - z)) / 2`.
     pub fn atanh(&self) -> Self {
-        let r = self.magnitude();
-        let theta = self.phase();
-        Complex::new(theta.sinh() / r, theta.cosh() / r)
+        let z = *self;
+        let half = Complex::from(T::from(0.5).unwrap());
+        half * ((Complex::one() + z).ln() - (Complex::one() - z).ln())
     }
     
 }
@@ -303,3 +307,114 @@ impl<T: Float + std::fmt::Display + std::fmt::Debug> From<T> for Complex<T> {
 
 impl<T: Copy> Copy for Complex<T> { }
 
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> Complex<T> {
+    // Writes "<real><sign><imag>i" with the formatter precision applied to both
+    // components and the width applied to the whole number.
+    fn fmt_with(&self, f: &mut std::fmt::Formatter<'_>, component: fn(&T, Option<usize>) -> String) -> std::fmt::Result {
+        let sign = if self.imag.is_sign_negative() { '-' } else { '+' };
+        let text = format!("{}{}{}i", component(&self.real, f.precision()), sign, component(&self.imag.abs(), f.precision()));
+        let padding = f.width().unwrap_or(0).saturating_sub(text.chars().count());
+        let (before, after) = match f.align() {
+            Some(std::fmt::Alignment::Left) => (0, padding),
+            Some(std::fmt::Alignment::Center) => (padding / 2, padding - padding / 2),
+            _ => (padding, 0),
+        };
+        let fill = f.fill().to_string();
+        write!(f, "{}{}{}", fill.repeat(before), text, fill.repeat(after))
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug> std::fmt::Display for Complex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, |value, precision| match precision {
+            Some(p) => format!("{:.*}", p, value),
+            None => format!("{}", value),
+        })
+    }
+}
+
+impl<T: Float + std::fmt::Display + std::fmt::Debug + std::fmt::LowerExp> std::fmt::LowerExp for Complex<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_with(f, |value, precision| match precision {
+            Some(p) => format!("{:.*e}", p, value),
+            None => format!("{:e}", value),
+        })
+    }
+}
+
+/// Parses the `Display` format back: `"<real><sign><imag>i"`, as in
+/// `"1.5-2e-3i"`.
+impl<T: Float + std::fmt::Display + std::fmt::Debug + std::str::FromStr> std::str::FromStr for Complex<T> {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let body = s.trim().strip_suffix('i').ok_or("Missing imaginary unit")?;
+        // The imaginary sign is the last one that neither leads the number
+        // nor belongs to an exponent.
+        let split = body.char_indices()
+            .filter(|&(i, c)| (c == '+' || c == '-') && i > 0 && !body[..i].ends_with(['e', 'E']))
+            .map(|(i, _)| i)
+            .next_back()
+            .ok_or("Missing imaginary part")?;
+        let real = body[..split].parse::<T>().map_err(|_| "Invalid real part")?;
+        let imag = body[split..].strip_prefix('+').unwrap_or(&body[split..])
+            .parse::<T>().map_err(|_| "Invalid imaginary part")?;
+        Ok(Complex::new(real, imag))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_complex_format() {
+        let c = Complex::new(1.23456, -2500.5);
+        assert_eq!(format!("{}", c), "1.23456-2500.5i");
+        assert_eq!(format!("{:.2}", c), "1.23-2500.50i");
+        assert_eq!(format!("{:e}", c), "1.23456e0-2.5005e3i");
+        assert_eq!(format!("{:.1e}", c), "1.2e0-2.5e3i");
+        assert_eq!(format!("{:>16.1}", c), "     1.2-2500.5i");
+        assert_eq!(format!("{:*<16.1}", c), "1.2-2500.5i*****");
+        assert_eq!(format!("{:.1}", Complex::new(0.5f32, 0.25)), "0.5+0.2i");
+    }
+
+    #[test]
+    fn test_inverse_trig() {
+        let close = |a: Complex<f64>, b: Complex<f64>| (a - b).magnitude() < 1e-12;
+        for z in [Complex::new(0.3, 0.4), Complex::new(-1.5, 0.7), Complex::new(2.0, -3.0), Complex::new(0.5, 0.0)] {
+            assert!(close(z.asin().sin(), z), "asin {:?}", z);
+            assert!(close(z.acos().cos(), z), "acos {:?}", z);
+            assert!(close(z.atan().tan(), z), "atan {:?}", z);
+            assert!(close(z.asinh().sinh(), z), "asinh {:?}", z);
+            assert!(close(z.acosh().cosh(), z), "acosh {:?}", z);
+            assert!(close(z.atanh().tanh(), z), "atanh {:?}", z);
+        }
+        // Real arguments inside the real domain give the real functions.
+        assert!(close(Complex::new(0.5, 0.0).asin(), Complex::new(0.5f64.asin(), 0.0)));
+        assert!(close(Complex::new(0.5, 0.0).acos(), Complex::new(0.5f64.acos(), 0.0)));
+        assert!(close(Complex::new(2.0, 0.0).atan(), Complex::new(2.0f64.atan(), 0.0)));
+        assert!(close(Complex::atan2(1.0, -1.0), Complex::new(3.0 * std::f64::consts::FRAC_PI_4, 0.0)));
+    }
+
+    #[test]
+    fn test_complex_serde() {
+        let c = Complex::new(1.5, -2.0);
+        let json = serde_json::to_string(&c).unwrap();
+        assert_eq!(json, r#"{"real":1.5,"imag":-2.0}"#);
+        assert_eq!(serde_json::from_str::<Complex<f64>>(&json).unwrap(), c);
+    }
+
+    #[test]
+    fn test_complex_parse() {
+        for c in [Complex::new(1.23456, -2500.5), Complex::new(-0.5, 0.25), Complex::new(3.0, 0.0), Complex::new(-1e-30, 4e200)] {
+            assert_eq!(format!("{}", c).parse::<Complex<f64>>(), Ok(c));
+            assert_eq!(format!("{:e}", c).parse::<Complex<f64>>(), Ok(c));
+        }
+        assert_eq!(" -1.5e-3+2E+2i ".parse::<Complex<f32>>(), Ok(Complex::new(-1.5e-3, 200.0)));
+        assert!("1+2".parse::<Complex<f64>>().is_err());
+        assert!("2i".parse::<Complex<f64>>().is_err());
+        assert!("x+2i".parse::<Complex<f64>>().is_err());
+    }
+}
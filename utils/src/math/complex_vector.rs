@@ -1,6 +1,8 @@
 use crate::math::complex::Complex;
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, PartialEq)]
+/// Stored as parallel `real`/`imag` arrays, which is also how it serializes.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ComplexVector<T> {
     pub real: Vec<T>,
     pub imag: Vec<T>,
@@ -258,6 +260,27 @@ impl<T: Clone + num_traits::Zero> ComplexVector<T> {
         }
         sum.sqrt()
     }
+
+    /// Hermitian inner product `sum(self[k] * conj(other[k]))`: linear in
+    /// `self`, conjugate-linear in `other`, so `v.dot(&v)` is `|v|^2`.
+    /// Panics if the lengths differ.
+    pub fn dot(&self, other: &ComplexVector<T>) -> Complex<T>
+    where
+        T: Copy + num_traits::Float + std::fmt::Display + std::fmt::Debug,
+    {
+        assert_eq!(self.len(), other.len(), "ComplexVector length mismatch in dot");
+        self.iter().zip(other.iter())
+            .fold(Complex::new(T::zero(), T::zero()), |acc, (a, b)| acc + a * b.conjugate())
+    }
+
+    /// Element-wise product, without conjugation. Panics if the lengths differ.
+    pub fn hadamard(&self, other: &ComplexVector<T>) -> Self
+    where
+        T: Copy + num_traits::Float + std::fmt::Display + std::fmt::Debug,
+    {
+        assert_eq!(self.len(), other.len(), "ComplexVector length mismatch in hadamard");
+        Self::from_complex_numbers(self.iter().zip(other.iter()).map(|(a, b)| a * b).collect())
+    }
 }
 
 impl<T> std::ops::Add for ComplexVector<T>
@@ -325,3 +348,34 @@ impl<T> std::ops::Mul for ComplexVector<T>
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dot_hadamard() {
+        let a = ComplexVector::new(vec![1.0f64, 0.0], Some(vec![1.0, 2.0]));
+        let b = ComplexVector::new(vec![2.0, 1.0], Some(vec![0.0, -1.0]));
+        // (1+i)(2) + (2i)(1+i) = 2+2i - 2+2i
+        assert_eq!(a.dot(&b), Complex::new(0.0, 4.0));
+        assert_eq!(b.dot(&a), Complex::new(0.0, -4.0));
+        assert_eq!(a.dot(&a), Complex::new(6.0, 0.0));
+        // (1+i)(2) and (2i)(1-i)
+        assert_eq!(a.hadamard(&b), ComplexVector::new(vec![2.0, 2.0], Some(vec![2.0, 2.0])));
+        assert_eq!(a.hadamard(&b), a.clone() * b.clone());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_dot_length_mismatch() {
+        ComplexVector::<f64>::zeroed(2).dot(&ComplexVector::zeroed(3));
+    }
+
+    #[test]
+    fn test_complex_vector_serde() {
+        let v = ComplexVector::new(vec![1.0, 0.5], Some(vec![-1.0, 0.0]));
+        let json = serde_json::to_string(&v).unwrap();
+        assert_eq!(json, r#"{"real":[1.0,0.5],"imag":[-1.0,0.0]}"#);
+        assert_eq!(serde_json::from_str::<ComplexVector<f64>>(&json).unwrap(), v);
+    }
+}
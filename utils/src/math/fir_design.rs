@@ -0,0 +1,129 @@
+use std::f64::consts::PI;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialOrd, PartialEq, Copy, Serialize, Deserialize)]
+pub enum WindowType {
+    Rectangular,
+    Hann,
+    Hamming,
+    Blackman,
+}
+
+/// Symmetric window of `len` points.
+pub fn window(window_type: WindowType, len: usize) -> Vec<f64> {
+    if len == 1 {
+        return vec![1.0];
+    }
+    let span = (len - 1) as f64;
+    (0..len).map(|n| {
+        let phase = 2.0 * PI * n as f64 / span;
+        match window_type {
+            WindowType::Rectangular => 1.0,
+            WindowType::Hann => 0.5 - 0.5 * phase.cos(),
+            WindowType::Hamming => 0.54 - 0.46 * phase.cos(),
+            WindowType::Blackman => 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos(),
+        }
+    }).collect()
+}
+
+fn sinc(x: f64) -> f64 {
+    if x == 0.0 { 1.0 } else { (PI * x).sin() / (PI * x) }
+}
+
+/// Subtracts `coefficients` from a unit impulse at their centre, turning a
+/// filter into its complement.
+fn spectral_inversion(mut coefficients: Vec<f64>) -> Vec<f64> {
+    assert!(coefficients.len() % 2 == 1, "Spectral inversion needs an odd number of taps");
+    coefficients.iter_mut().for_each(|c| *c = -*c);
+    let centre = coefficients.len() / 2;
+    coefficients[centre] += 1.0;
+    coefficients
+}
+
+/// Windowed-sinc lowpass of `num_taps` coefficients with unit gain at DC.
+/// `cutoff_norm` is the cutoff frequency over the sample rate, in (0, 0.5).
+pub fn lowpass(num_taps: usize, cutoff_norm: f64, window_type: WindowType) -> Vec<f64> {
+    assert!(num_taps > 0, "A filter needs at least one tap");
+    assert!(cutoff_norm > 0.0 && cutoff_norm < 0.5, "The cutoff must be in (0, 0.5)");
+    let centre = (num_taps - 1) as f64 / 2.0;
+    let coefficients: Vec<f64> = window(window_type, num_taps).iter().enumerate()
+        .map(|(n, w)| 2.0 * cutoff_norm * sinc(2.0 * cutoff_norm * (n as f64 - centre)) * w)
+        .collect();
+    let gain: f64 = coefficients.iter().sum();
+    coefficients.iter().map(|c| c / gain).collect()
+}
+
+/// Highpass complement of `lowpass`. `num_taps` must be odd.
+pub fn highpass(num_taps: usize, cutoff_norm: f64, window_type: WindowType) -> Vec<f64> {
+    spectral_inversion(lowpass(num_taps, cutoff_norm, window_type))
+}
+
+/// Passes the band between `low_norm` and `high_norm`, as the difference of
+/// two lowpass filters.
+pub fn bandpass(num_taps: usize, low_norm: f64, high_norm: f64, window_type: WindowType) -> Vec<f64> {
+    assert!(low_norm < high_norm, "The low edge must be below the high edge");
+    let low = lowpass(num_taps, low_norm, window_type);
+    lowpass(num_taps, high_norm, window_type).iter().zip(low).map(|(h, l)| h - l).collect()
+}
+
+/// Rejects the band between `low_norm` and `high_norm`. `num_taps` must be odd.
+pub fn bandstop(num_taps: usize, low_norm: f64, high_norm: f64, window_type: WindowType) -> Vec<f64> {
+    spectral_inversion(bandpass(num_taps, low_norm, high_norm, window_type))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Amplitude of a tone of `frequency_norm` after the filter.
+    fn tone_gain(coefficients: &[f64], frequency_norm: f64) -> f64 {
+        let (re, im) = coefficients.iter().enumerate().fold((0.0, 0.0), |(re, im), (n, c)| {
+            let phase = 2.0 * PI * frequency_norm * n as f64;
+            (re + c * phase.cos(), im - c * phase.sin())
+        });
+        (re * re + im * im).sqrt()
+    }
+
+    #[test]
+    fn test_window() {
+        assert_eq!(window(WindowType::Rectangular, 3), vec![1.0, 1.0, 1.0]);
+        let hann = window(WindowType::Hann, 5);
+        assert!(hann.iter().zip([0.0, 0.5, 1.0, 0.5, 0.0]).all(|(w, e)| (w - e).abs() < 1e-12));
+        let blackman = window(WindowType::Blackman, 4);
+        assert!((blackman[0]).abs() < 1e-12 && (blackman[1] - blackman[2]).abs() < 1e-12);
+        assert_eq!(window(WindowType::Hamming, 1), vec![1.0]);
+    }
+
+    #[test]
+    fn test_lowpass() {
+        let coefficients = lowpass(51, 0.1, WindowType::Hamming);
+        assert_eq!(coefficients.len(), 51);
+        assert!((coefficients.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+        assert!((coefficients[0] - coefficients[50]).abs() < 1e-15);
+        assert!((tone_gain(&coefficients, 0.02) - 1.0).abs() < 0.01);
+        assert!(tone_gain(&coefficients, 0.4) < 0.01);
+    }
+
+    #[test]
+    fn test_highpass_bandpass_bandstop() {
+        let coefficients = highpass(51, 0.1, WindowType::Hamming);
+        assert!(tone_gain(&coefficients, 0.02) < 0.01);
+        assert!((tone_gain(&coefficients, 0.4) - 1.0).abs() < 0.01);
+
+        let coefficients = bandpass(101, 0.1, 0.3, WindowType::Blackman);
+        assert!(tone_gain(&coefficients, 0.02) < 0.01);
+        assert!((tone_gain(&coefficients, 0.2) - 1.0).abs() < 0.01);
+        assert!(tone_gain(&coefficients, 0.45) < 0.01);
+
+        let coefficients = bandstop(101, 0.1, 0.3, WindowType::Blackman);
+        assert!((tone_gain(&coefficients, 0.02) - 1.0).abs() < 0.01);
+        assert!(tone_gain(&coefficients, 0.2) < 0.01);
+        assert!((tone_gain(&coefficients, 0.45) - 1.0).abs() < 0.01);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_highpass_even_taps() {
+        highpass(50, 0.1, WindowType::Hann);
+    }
+}
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// `(L, U, perm, sign)` as returned by `Matrix::lu_decompose`.
+pub type LuDecomposition<T> = (Matrix<T>, Matrix<T>, Vec<usize>, T);
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, PartialOrd)]
 pub struct Matrix<T> {
     pub data: Vec<Vec<T>>,
@@ -115,45 +118,149 @@ where
         Some(trace)
     }
 
+    pub fn mul_vec(&self, vector: &[T]) -> Vec<T>
+    where
+        T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        if vector.len() != self.cols {
+            panic!("Matrix dimensions must agree for multiplication");
+        }
+        self.data
+            .iter()
+            .map(|row| {
+                row.iter().zip(vector).fold(T::default(), |acc, (a, b)| acc + a.clone() * b.clone())
+            })
+            .collect()
+    }
+
+    pub fn scale(&self, scalar: T) -> Matrix<T>
+    where
+        T: std::ops::Mul<Output = T>,
+    {
+        let mut scaled = self.clone();
+        scaled.scale_inplace(scalar);
+        scaled
+    }
+
+    pub fn scale_inplace(&mut self, scalar: T)
+    where
+        T: std::ops::Mul<Output = T>,
+    {
+        for value in self.data.iter_mut().flatten() {
+            *value = value.clone() * scalar.clone();
+        }
+    }
+
+    /// LU decomposition with partial pivoting: returns `(L, U, perm, sign)`
+    /// with `L` unit lower triangular, `U` upper triangular and row `i` of
+    /// `L * U` equal to row `perm[i]` of the matrix. `sign` is the parity of
+    /// the permutation. A singular matrix leaves a zero on the diagonal of `U`.
+    pub fn lu_decompose(&self) -> Option<LuDecomposition<T>>
+    where
+        T: num_traits::Float,
+    {
+        if !self.is_square() {
+            return None;
+        }
+        let n = self.rows;
+        let zero = T::zero();
+        let mut lower = Matrix::<T>::new(n, n);
+        for i in 0..n {
+            lower.data[i][i] = T::one();
+        }
+        let mut upper = self.clone();
+        let mut perm: Vec<usize> = (0..n).collect();
+        let mut sign = T::one();
+        for i in 0..n {
+            let mut pivot_row = i;
+            for k in i + 1..n {
+                if upper.data[k][i].abs() > upper.data[pivot_row][i].abs() {
+                    pivot_row = k;
+                }
+            }
+            if pivot_row != i {
+                upper.data.swap(i, pivot_row);
+                perm.swap(i, pivot_row);
+                // The multipliers already computed follow their rows.
+                for j in 0..i {
+                    let tmp = lower.data[i][j];
+                    lower.data[i][j] = lower.data[pivot_row][j];
+                    lower.data[pivot_row][j] = tmp;
+                }
+                sign = -sign;
+            }
+            let pivot = upper.data[i][i];
+            if pivot == zero {
+                continue;
+            }
+            for k in i + 1..n {
+                let factor = upper.data[k][i] / pivot;
+                for j in i..n {
+                    upper.data[k][j] = upper.data[k][j] - factor * upper.data[i][j];
+                }
+                lower.data[k][i] = factor;
+            }
+        }
+        Some((lower, upper, perm, sign))
+    }
+
+    /// Determinant from the LU decomposition, in O(n³).
+    pub fn lu_determinant(&self) -> Option<T>
+    where
+        T: num_traits::Float,
+    {
+        let (_, upper, _, sign) = self.lu_decompose()?;
+        let mut det = sign;
+        for i in 0..upper.rows {
+            det = det * upper.data[i][i];
+        }
+        Some(det)
+    }
+
+    /// Determinant by fraction-free (Bareiss) elimination, in O(n³). Every
+    /// division is exact, so integer matrices get their exact determinant.
     pub fn determinant(&self) -> Option<T> 
     where
-        T: std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8>,
+        T: std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>
+            + std::ops::Div<Output = T> + From<u8> + PartialEq,
     {
         if !self.is_square() {
             return None;
         }
         let n = self.rows;
-        if n == 1 {
-            return Some(self.data[0][0].clone());
-        }
-        if n == 2 {
-            return Some(
-                self.data[0][0].clone() * self.data[1][1].clone()
-                    - self.data[0][1].clone() * self.data[1][0].clone(),
-            );
-        }
-        let mut det = T::default();
-        for c in 0..n {
-            let mut submatrix_data = vec![];
-            for r in 1..n {
-                let mut row_data = vec![];
-                for cc in 0..n {
-                    if cc != c {
-                        row_data.push(self.data[r][cc].clone());
+        if n == 0 {
+            return Some(T::default());
+        }
+        let zero = T::from(0u8);
+        let mut data = self.data.clone();
+        let mut sign = T::from(1u8);
+        let mut previous = T::from(1u8);
+        for k in 0..n.saturating_sub(1) {
+            if data[k][k] == zero {
+                match (k + 1..n).find(|&i| data[i][k] != zero) {
+                    Some(i) => {
+                        data.swap(i, k);
+                        sign = zero.clone() - sign;
                     }
+                    None => return Some(zero),
                 }
-                submatrix_data.push(row_data);
             }
-            let submatrix = Matrix::from_vec(submatrix_data);
-            let sign = if c % 2 == 0 { T::from(1u8) } else { T::from(0u8) - T::from(1u8) };
-            det = det + sign * self.data[0][c].clone() * submatrix.determinant().unwrap();
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    data[i][j] = (data[i][j].clone() * data[k][k].clone()
+                        - data[i][k].clone() * data[k][j].clone())
+                        / previous.clone();
+                }
+            }
+            previous = data[k][k].clone();
         }
-        Some(det)
+        Some(sign * data[n - 1][n - 1].clone())
     }
 
     pub fn is_singular(&self) -> Option<bool> 
     where
-        T: std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8> + PartialEq,
+        T: std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T>
+            + std::ops::Div<Output = T> + From<u8> + PartialEq,
     {
         match self.determinant() {
             Some(det) => {
@@ -167,6 +274,97 @@ where
         }
     }
 
+    /// Solves `self * x = b` through the LU decomposition. None if the
+    /// matrix is singular or `b` does not match its size.
+    pub fn solve(&self, b: &[T]) -> Option<Vec<T>>
+    where
+        T: num_traits::Float,
+    {
+        let (lower, upper, perm, _) = self.lu_decompose()?;
+        Self::lu_solve(&lower, &upper, &perm, b)
+    }
+
+    /// Solves `self * X = b` for every column of `b`.
+    pub fn solve_matrix(&self, b: &Matrix<T>) -> Option<Matrix<T>>
+    where
+        T: num_traits::Float,
+    {
+        let (lower, upper, perm, _) = self.lu_decompose()?;
+        let mut solution = Matrix::new(self.cols, b.cols);
+        for col in 0..b.cols {
+            let column: Vec<T> = b.data.iter().map(|row| row[col]).collect();
+            let x = Self::lu_solve(&lower, &upper, &perm, &column)?;
+            for (row, value) in x.into_iter().enumerate() {
+                solution.data[row][col] = value;
+            }
+        }
+        Some(solution)
+    }
+
+    fn lu_solve(lower: &Matrix<T>, upper: &Matrix<T>, perm: &[usize], b: &[T]) -> Option<Vec<T>>
+    where
+        T: num_traits::Float,
+    {
+        let n = upper.rows;
+        if b.len() != n {
+            return None;
+        }
+        // Forward substitution on the permuted right-hand side: L * y = P * b.
+        let mut y: Vec<T> = Vec::with_capacity(n);
+        for i in 0..n {
+            let mut sum = b[perm[i]];
+            for (j, y_j) in y.iter().enumerate() {
+                sum = sum - lower.data[i][j] * *y_j;
+            }
+            y.push(sum);
+        }
+        // Back substitution: U * x = y.
+        let mut x = vec![T::default(); n];
+        for i in (0..n).rev() {
+            let pivot = upper.data[i][i];
+            if pivot == T::zero() {
+                return None;
+            }
+            let mut sum = y[i];
+            for (j, x_j) in x.iter().enumerate().skip(i + 1) {
+                sum = sum - upper.data[i][j] * *x_j;
+            }
+            x[i] = sum / pivot;
+        }
+        Some(x)
+    }
+
+    /// Cholesky factor `L` of a symmetric positive-definite matrix, such that
+    /// `self = L * Lᵀ`. Only the lower triangle is read. None if the matrix
+    /// is not square or not positive-definite.
+    pub fn cholesky(&self) -> Option<Matrix<T>>
+    where
+        T: num_traits::Float,
+    {
+        if !self.is_square() {
+            return None;
+        }
+        let n = self.rows;
+        let mut lower = Matrix::new(n, n);
+        for i in 0..n {
+            for j in 0..=i {
+                let mut sum = self.data[i][j];
+                for k in 0..j {
+                    sum = sum - lower.data[i][k] * lower.data[j][k];
+                }
+                if i == j {
+                    if sum <= T::zero() {
+                        return None;
+                    }
+                    lower.data[i][i] = sum.sqrt();
+                } else {
+                    lower.data[i][j] = sum / lower.data[j][j];
+                }
+            }
+        }
+        Some(lower)
+    }
+
     pub fn inverse(&self) -> Option<Matrix<T>> 
     where
         T: std::ops::Add<Output = T>
@@ -176,7 +374,8 @@ where
             + From<u8>
             + Default
             + Clone
-            + PartialEq,
+            + PartialEq
+            + PartialOrd,
     {
         if !self.is_square() {
             return None;
@@ -190,9 +389,19 @@ where
             augmented_data[i][i + n] = T::from(1u8);
         }
 
+        let zero = T::from(0u8);
+        let abs = |x: &T| if *x < zero { zero.clone() - x.clone() } else { x.clone() };
         for i in 0..n {
+            // Partial pivoting: bring up the row with the largest pivot candidate.
+            let mut pivot_row = i;
+            for k in i + 1..n {
+                if abs(&augmented_data[k][i]) > abs(&augmented_data[pivot_row][i]) {
+                    pivot_row = k;
+                }
+            }
+            augmented_data.swap(i, pivot_row);
             let pivot = augmented_data[i][i].clone();
-            if pivot == T::from(0u8) {
+            if pivot == zero {
                 return None;
             }
             for j in 0..2 * n {
@@ -342,7 +551,7 @@ where
 }
 impl<T> std::ops::Div for Matrix<T>
 where
-    T: Clone + std::ops::Div<Output = T> + std::default::Default + PartialEq + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8>,
+    T: Clone + std::ops::Div<Output = T> + std::default::Default + PartialEq + PartialOrd + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8>,
 {
     type Output = Matrix<T>;
 
@@ -360,7 +569,7 @@ where
 }
 impl<T> std::ops::Div for &Matrix<T>
 where
-    T: Clone + std::ops::Div<Output = T> + std::default::Default + PartialEq + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8>,
+    T: Clone + std::ops::Div<Output = T> + std::default::Default + PartialEq + PartialOrd + std::ops::Add<Output = T> + std::ops::Sub<Output = T> + std::ops::Mul<Output = T> + From<u8>,
 {
     type Output = Matrix<T>;
 
@@ -375,4 +584,320 @@ where
             panic!("Matrix is singular, cannot divide");
         }
     }
-}
\ No newline at end of file
+}
+/// Square matrix storing only the diagonals between `lower` diagonals below
+/// and `upper` diagonals above the main one. Elements outside the band are zero.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BandedMatrix<T> {
+    pub diagonals: Vec<Vec<T>>,
+    pub size: usize,
+    pub lower: usize,
+    pub upper: usize,
+}
+
+impl<T> BandedMatrix<T>
+where
+    T: Clone + Default,
+{
+    pub fn new(size: usize, lower: usize, upper: usize) -> Self {
+        let lower = lower.min(size.saturating_sub(1));
+        let upper = upper.min(size.saturating_sub(1));
+        let diagonals = (0..=lower + upper)
+            .map(|d| vec![T::default(); size - d.abs_diff(lower)])
+            .collect();
+        BandedMatrix { diagonals, size, lower, upper }
+    }
+
+    /// Builds a banded matrix from a square dense one, dropping the elements outside the band.
+    pub fn from_matrix(matrix: &Matrix<T>, lower: usize, upper: usize) -> Result<Self, &'static str> {
+        if !matrix.is_square() {
+            return Err("Matrix must be square");
+        }
+        let mut banded = BandedMatrix::new(matrix.rows, lower, upper);
+        for i in 0..banded.size {
+            for j in banded.band_columns(i) {
+                banded.set(i, j, matrix.data[i][j].clone())?;
+            }
+        }
+        Ok(banded)
+    }
+
+    pub fn to_matrix(&self) -> Matrix<T> {
+        let mut matrix = Matrix::new(self.size, self.size);
+        for i in 0..self.size {
+            for j in self.band_columns(i) {
+                matrix.data[i][j] = self.diagonals[self.lower + j - i][i.min(j)].clone();
+            }
+        }
+        matrix
+    }
+
+    fn band_columns(&self, row: usize) -> std::ops::Range<usize> {
+        row.saturating_sub(self.lower)..(row + self.upper + 1).min(self.size)
+    }
+
+    fn band_rows(&self, col: usize) -> std::ops::Range<usize> {
+        col.saturating_sub(self.upper)..(col + self.lower + 1).min(self.size)
+    }
+
+    fn in_band(&self, row: usize, col: usize) -> bool {
+        row < self.size && col < self.size && col + self.lower >= row && row + self.upper >= col
+    }
+
+    /// Returns the element at (`row`, `col`), zero outside the band and None outside the matrix.
+    pub fn get(&self, row: usize, col: usize) -> Option<T> {
+        if row >= self.size || col >= self.size {
+            return None;
+        }
+        if self.in_band(row, col) {
+            Some(self.diagonals[self.lower + col - row][row.min(col)].clone())
+        } else {
+            Some(T::default())
+        }
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: T) -> Result<(), &'static str> {
+        if !self.in_band(row, col) {
+            return Err("Index out of band");
+        }
+        self.diagonals[self.lower + col - row][row.min(col)] = value;
+        Ok(())
+    }
+
+    pub fn mul_vec(&self, vector: &[T]) -> Vec<T>
+    where
+        T: std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+    {
+        if vector.len() != self.size {
+            panic!("Matrix dimensions must agree for multiplication");
+        }
+        (0..self.size)
+            .map(|i| {
+                self.band_columns(i).fold(T::default(), |acc, k| {
+                    acc + self.diagonals[self.lower + k - i][i.min(k)].clone() * vector[k].clone()
+                })
+            })
+            .collect()
+    }
+}
+
+impl<T> std::ops::Mul<&Matrix<T>> for &BandedMatrix<T>
+where
+    T: Clone + Default + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Output = Matrix<T>;
+
+    fn mul(self, other: &Matrix<T>) -> Matrix<T> {
+        if self.size != other.rows {
+            panic!("Matrix dimensions must agree for multiplication");
+        }
+        let mut result_data = vec![vec![T::default(); other.cols]; self.size];
+        for (i, row) in result_data.iter_mut().enumerate() {
+            for k in self.band_columns(i) {
+                let value = &self.diagonals[self.lower + k - i][i.min(k)];
+                for (j, result) in row.iter_mut().enumerate() {
+                    *result = result.clone() + value.clone() * other.data[k][j].clone();
+                }
+            }
+        }
+        Matrix::from_vec(result_data)
+    }
+}
+
+impl<T> std::ops::Mul for &BandedMatrix<T>
+where
+    T: Clone + Default + std::ops::Add<Output = T> + std::ops::Mul<Output = T>,
+{
+    type Output = BandedMatrix<T>;
+
+    fn mul(self, other: &BandedMatrix<T>) -> BandedMatrix<T> {
+        if self.size != other.size {
+            panic!("Matrix dimensions must agree for multiplication");
+        }
+        let mut result = BandedMatrix::new(self.size, self.lower + other.lower, self.upper + other.upper);
+        for i in 0..self.size {
+            for j in result.band_columns(i) {
+                let first = self.band_columns(i).start.max(other.band_rows(j).start);
+                let last = self.band_columns(i).end.min(other.band_rows(j).end);
+                let value = (first..last).fold(T::default(), |acc, k| {
+                    acc + self.diagonals[self.lower + k - i][i.min(k)].clone()
+                        * other.diagonals[other.lower + j - k][k.min(j)].clone()
+                });
+                result.set(i, j, value).unwrap();
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lu_determinant() {
+        let matrix: Matrix<f64> = Matrix::from_vec(vec![
+            vec![0.0, 1.0, 4.0],
+            vec![2.0, -1.0, 0.0],
+            vec![1.0, 3.0, 2.0],
+        ]);
+        let (lower, upper, perm, sign) = matrix.lu_decompose().unwrap();
+        let product = &lower * &upper;
+        for i in 0..3 {
+            assert_eq!(lower.data[i][i], 1.0);
+            for j in 0..3 {
+                if j < i {
+                    assert_eq!(upper.data[i][j], 0.0);
+                } else if j > i {
+                    assert_eq!(lower.data[i][j], 0.0);
+                }
+                assert!((product.data[i][j] - matrix.data[perm[i]][j]).abs() < 1e-12);
+            }
+        }
+        assert!(sign == 1.0 || sign == -1.0);
+        assert!((matrix.lu_determinant().unwrap() - 24.0_f64).abs() < 1e-12);
+        assert_eq!(matrix.determinant(), Some(24.0));
+        assert_eq!(Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).is_singular(), Some(true));
+
+        let mut large = Matrix::<f64>::identity(16);
+        for i in 0..16 {
+            large.data[i][i] = 2.0;
+        }
+        large.data.swap(0, 15);
+        assert_eq!(large.lu_determinant(), Some(-65536.0));
+        assert!(Matrix::<f64>::new(2, 3).lu_determinant().is_none());
+    }
+    #[test]
+    fn test_integer_determinant() {
+        let matrix: Matrix<i64> = Matrix::from_vec(vec![vec![2, 1], vec![1, 2]]);
+        assert_eq!(matrix.determinant(), Some(3));
+        let matrix: Matrix<i64> = Matrix::from_vec(vec![
+            vec![2, 1, 0],
+            vec![1, 3, 1],
+            vec![0, 1, 4],
+        ]);
+        assert_eq!(matrix.determinant(), Some(18));
+        assert_eq!(matrix.is_singular(), Some(false));
+        assert!(Matrix::<i64>::new(2, 3).determinant().is_none());
+
+        // A zero pivot swaps the rows, flipping the sign.
+        let matrix: Matrix<i64> = Matrix::from_vec(vec![
+            vec![0, 2, 1],
+            vec![3, 1, 0],
+            vec![1, 0, 2],
+        ]);
+        assert_eq!(matrix.determinant(), Some(-13));
+        let matrix: Matrix<i64> = Matrix::from_vec(vec![
+            vec![1, 2, 3],
+            vec![2, 4, 6],
+            vec![1, 0, 1],
+        ]);
+        assert_eq!(matrix.is_singular(), Some(true));
+
+        let mut large = Matrix::<i64>::new(16, 16);
+        for i in 0..16 {
+            large.data[i][i] = 2;
+        }
+        large.data.swap(0, 15);
+        assert_eq!(large.determinant(), Some(-65536));
+    }
+    #[test]
+    fn test_solve() {
+        let matrix: Matrix<f64> = Matrix::from_vec(vec![
+            vec![0.0, 1.0, 4.0],
+            vec![2.0, -1.0, 0.0],
+            vec![1.0, 3.0, 2.0],
+        ]);
+        let b = vec![1.0, -2.0, 0.5];
+        let x = matrix.solve(&b).unwrap();
+        let inverse = matrix.inverse().unwrap();
+        for i in 0..3 {
+            let expected: f64 = (0..3).map(|j| inverse.data[i][j] * b[j]).sum();
+            assert!((x[i] - expected).abs() < 1e-12);
+        }
+        let rhs = Matrix::from_vec(vec![vec![1.0, 0.0], vec![-2.0, 1.0], vec![0.5, 0.0]]);
+        let solution = matrix.solve_matrix(&rhs).unwrap();
+        let product = &matrix * &solution;
+        for i in 0..3 {
+            for j in 0..2 {
+                assert!((product.data[i][j] - rhs.data[i][j]).abs() < 1e-12);
+            }
+        }
+        assert!(matrix.solve(&[1.0, 2.0]).is_none());
+        assert!(Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).solve(&[1.0, 1.0]).is_none());
+    }
+    #[test]
+    fn test_cholesky() {
+        let matrix: Matrix<f64> = Matrix::from_vec(vec![
+            vec![4.0, 12.0, -16.0],
+            vec![12.0, 37.0, -43.0],
+            vec![-16.0, -43.0, 98.0],
+        ]);
+        let lower = matrix.cholesky().unwrap();
+        assert_eq!(lower.data, vec![
+            vec![2.0, 0.0, 0.0],
+            vec![6.0, 1.0, 0.0],
+            vec![-8.0, 5.0, 3.0],
+        ]);
+        assert_eq!(&lower * &lower.transpose(), matrix);
+        let indefinite: Matrix<f64> = Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 1.0]]);
+        assert!(indefinite.cholesky().is_none());
+        assert!(Matrix::<f64>::new(2, 3).cholesky().is_none());
+    }
+    #[test]
+    fn test_mul_vec_scale() {
+        let matrix = Matrix::from_vec(vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(matrix.mul_vec(&[1, 0, -1]), vec![-2, -2]);
+        assert_eq!(matrix.scale(2).data, vec![vec![2, 4, 6], vec![8, 10, 12]]);
+        let mut scaled = matrix.clone();
+        scaled.scale_inplace(-1);
+        assert_eq!(scaled.data, vec![vec![-1, -2, -3], vec![-4, -5, -6]]);
+        assert!(std::panic::catch_unwind(|| matrix.mul_vec(&[1, 2])).is_err());
+    }
+    #[test]
+    fn test_inverse_pivoting() {
+        let swap = Matrix::from_vec(vec![vec![0.0, 1.0], vec![1.0, 0.0]]);
+        assert_eq!(swap.inverse().unwrap().data, swap.data);
+
+        // Without pivoting the tiny leading element would wreck the result.
+        let epsilon = 1e-17;
+        let near = Matrix::from_vec(vec![vec![epsilon, 1.0], vec![1.0, 1.0]]);
+        let product = &near * &near.inverse().unwrap();
+        for i in 0..2 {
+            for j in 0..2 {
+                let expected: f64 = if i == j { 1.0 } else { 0.0 };
+                assert!((product.data[i][j] - expected).abs() < 1e-12);
+            }
+        }
+        assert!(Matrix::from_vec(vec![vec![1.0, 2.0], vec![2.0, 4.0]]).inverse().is_none());
+    }
+    #[test]
+    fn test_banded_multiply() {
+        let n = 6;
+        let mut dense = Matrix::<f64>::new(n, n);
+        for i in 0..n {
+            dense.data[i][i] = 2.0 + i as f64;
+            if i > 0 {
+                dense.data[i][i - 1] = -1.0 - i as f64;
+            }
+            if i + 1 < n {
+                dense.data[i][i + 1] = 0.5 * i as f64 + 1.0;
+            }
+        }
+        let banded = BandedMatrix::from_matrix(&dense, 1, 1).unwrap();
+        assert_eq!(banded.to_matrix(), dense);
+        assert_eq!(banded.get(0, 2), Some(0.0));
+        assert_eq!(banded.get(0, n), None);
+        assert!(banded.clone().set(0, 2, 1.0).is_err());
+
+        let other = Matrix::from_vec((0..n).map(|i| (0..3).map(|j| (i * 3 + j) as f64).collect()).collect());
+        assert_eq!(&banded * &other, &dense * &other);
+        let vector: Vec<f64> = (0..n).map(|i| i as f64 - 2.0).collect();
+        let column = Matrix::from_vec(vector.iter().map(|v| vec![*v]).collect());
+        let expected: Vec<f64> = (&dense * &column).data.iter().map(|row| row[0]).collect();
+        assert_eq!(banded.mul_vec(&vector), expected);
+        let squared = &banded * &banded;
+        assert_eq!((squared.lower, squared.upper), (2, 2));
+        assert_eq!(squared.to_matrix(), &dense * &dense);
+    }
+}
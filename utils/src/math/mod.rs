@@ -2,4 +2,5 @@ pub mod complex;
 pub mod complex_vector;
 pub mod statistics;
 pub mod numbers;
-pub mod matrix;
\ No newline at end of file
+pub mod matrix;
+pub mod fir_design;
\ No newline at end of file
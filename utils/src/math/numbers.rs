@@ -1,39 +1,28 @@
+/// Primes up to and including `limit`, by the Sieve of Eratosthenes.
 pub fn get_primes_number( limit: u64) -> Vec<u64> {
-    let mut primes = Vec::new();
-    let mut counter = 3;
-    primes.push(2); // 2 is the first prime number
-    while counter <= limit {
-        let mut is_prime = true;
-        for val in &primes {
-            if counter % val == 0 {
-                is_prime = false;
-                break;
+    if limit < 2 {
+        return Vec::new();
+    }
+    let limit = limit as usize;
+    let mut composite = vec![false; limit + 1];
+    let mut i = 2;
+    while i * i <= limit {
+        if !composite[i] {
+            for multiple in (i * i..=limit).step_by(i) {
+                composite[multiple] = true;
             }
         }
-        if is_prime {
-            primes.push(counter);
-        }
-        counter += 2;
+        i += 1;
     }
-    primes
+    (2..=limit).filter(|&n| !composite[n]).map(|n| n as u64).collect()
 }
 
 pub fn is_prime(n: u64) -> bool {
     if n < 2 {
         return false;
     }
-    if n == 2 {
-        return true;
-    }
-    if n % 2 == 0 {
-        return false;
-    }
-    let limit = (n as f64).sqrt() as u64 + 1;
-    let prime_list = get_primes_number(limit);
-    if prime_list.contains(&n) {
-        return true;
-    }
-    false
+    let limit = (n as f64).sqrt() as u64;
+    get_primes_number(limit).iter().all(|&p| !n.is_multiple_of(p))
 }
 
 pub fn factorize(mut n: u64) -> Vec<u64> {
@@ -45,6 +34,10 @@ pub fn factorize(mut n: u64) -> Vec<u64> {
             n /= prime;
         }
     }
+    // At most one prime factor is above the square root.
+    if n > 1 {
+        factors.push(n);
+    }
     factors
 }
 
@@ -88,11 +81,27 @@ pub fn factorial(n: u64) -> u64 {
     result
 }
 
-pub fn binomial_coefficient(n: u64, k: u64) -> u64 {
+/// `n!`, or `None` when it does not fit in a `u64` (from `n = 21`).
+pub fn checked_factorial(n: u64) -> Option<u64> {
+    (1..=n).try_fold(1u64, |acc, i| acc.checked_mul(i))
+}
+
+/// `n` choose `k`, or `None` when it does not fit in a `u64`. Uses the
+/// multiplicative formula, so the factorials are never formed: after step `i`
+/// the partial result is `C(n - k + i, i)`, which never exceeds the answer.
+pub fn binomial_coefficient(n: u64, k: u64) -> Option<u64> {
     if k > n {
-        return 0;
+        return Some(0);
     }
-    factorial(n) / (factorial(k) * factorial(n - k))
+    let k = k.min(n - k);
+    let mut result: u128 = 1;
+    for i in 1..=k {
+        result = result * (n - k + i) as u128 / i as u128;
+        if result > u64::MAX as u128 {
+            return None;
+        }
+    }
+    Some(result as u64)
 }
 
 #[cfg(test)]
@@ -102,5 +111,49 @@ mod tests {
     fn test_get_primes_number() {
         let primes = get_primes_number(30);
         assert_eq!(primes, vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert!(get_primes_number(1).is_empty());
+        assert_eq!(get_primes_number(2), vec![2]);
+    }
+    #[test]
+    fn test_sieve_matches_trial_division() {
+        // The trial-division implementation the sieve replaced.
+        fn trial_division(limit: u64) -> Vec<u64> {
+            let mut primes = vec![2];
+            let mut counter = 3;
+            while counter <= limit {
+                if primes.iter().all(|p| counter % p != 0) {
+                    primes.push(counter);
+                }
+                counter += 2;
+            }
+            primes
+        }
+        for limit in 2..=1000 {
+            assert_eq!(get_primes_number(limit), trial_division(limit), "limit {}", limit);
+        }
+    }
+    #[test]
+    fn test_factorial_binomial() {
+        assert_eq!(checked_factorial(0), Some(1));
+        assert_eq!(checked_factorial(20), Some(factorial(20)));
+        assert_eq!(checked_factorial(21), None);
+        assert_eq!(binomial_coefficient(5, 2), Some(10));
+        assert_eq!(binomial_coefficient(5, 7), Some(0));
+        assert_eq!(binomial_coefficient(10, 0), Some(1));
+        // 30! overflows, but C(30, 15) is small.
+        assert_eq!(binomial_coefficient(30, 15), Some(155117520));
+        assert_eq!(binomial_coefficient(62, 31), Some(465428353255261088));
+        assert_eq!(binomial_coefficient(68, 34), None);
+        assert_eq!(binomial_coefficient(u64::MAX, 1), Some(u64::MAX));
+    }
+    #[test]
+    fn test_is_prime_factorize() {
+        let primes: Vec<u64> = (0..30).filter(|&n| is_prime(n)).collect();
+        assert_eq!(primes, get_primes_number(30));
+        assert!(is_prime(7919));
+        assert!(!is_prime(7917));
+        assert_eq!(factorize(360), vec![2, 2, 2, 3, 3, 5]);
+        assert_eq!(factorize(14), vec![2, 7]);
+        assert_eq!(factorize(7919), vec![7919]);
     }
 }
\ No newline at end of file
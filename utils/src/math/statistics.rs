@@ -47,6 +47,25 @@ where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
     variance(data, mean).sqrt()
 }
 
+/// Population skewness, the third standardised moment.
+pub fn skewness<T>(data: Vec<T>, mean: T, std: T) -> T 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    let n: T = (data.len() as f64).into();
+    let m3: T = data.iter().map(|&x| (x - mean).powi(3)).sum::<T>() / n;
+    m3 / std.powi(3)
+}
+
+/// Population excess kurtosis: the fourth standardised moment minus 3, so
+/// that a normal distribution scores 0.
+pub fn kurtosis<T>(data: Vec<T>, mean: T, std: T) -> T 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    let n: T = (data.len() as f64).into();
+    let m4: T = data.iter().map(|&x| (x - mean).powi(4)).sum::<T>() / n;
+    m4 / std.powi(4) - (3.0).into()
+}
+
 pub fn covariance<T>(data_x: Vec<T>, data_y: Vec<T>) -> T 
 where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
 {
@@ -58,6 +77,36 @@ where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
     cov_sum / (data_x.len() as f64).into()
 }
 
+/// Autocorrelation of `data` for lags `0..=max_lag`, normalised by the
+/// lag-0 value so that it lies in `[-1, 1]`. Lags past the end are dropped.
+pub fn autocorrelation<T>(data: &[T], max_lag: usize) -> Vec<T> 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    cross_correlation(data, data, max_lag)
+}
+
+/// Cross-correlation of `x` and `y` for lags `0..=max_lag`, where lag `k`
+/// pairs `x[n]` with `y[n + k]`: a `y` delayed by `k` samples peaks at `k`.
+/// Normalised by the energies of both signals so that it lies in `[-1, 1]`.
+pub fn cross_correlation<T>(x: &[T], y: &[T], max_lag: usize) -> Vec<T> 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    let len = x.len().min(y.len());
+    if len == 0 {
+        return Vec::new();
+    }
+    let (mean_x, mean_y) = (mean(x[..len].to_vec()), mean(y[..len].to_vec()));
+    let dx: Vec<T> = x[..len].iter().map(|&v| v - mean_x).collect();
+    let dy: Vec<T> = y[..len].iter().map(|&v| v - mean_y).collect();
+    let norm = (dx.iter().map(|&v| v * v).sum::<T>() * dy.iter().map(|&v| v * v).sum::<T>()).sqrt();
+    (0..=max_lag.min(len - 1)).map(|lag| {
+        if norm == T::zero() {
+            return T::zero();
+        }
+        dx.iter().zip(dy[lag..].iter()).map(|(&a, &b)| a * b).sum::<T>() / norm
+    }).collect()
+}
+
 pub fn median<T>(data: &mut Vec<T>) -> T 
 where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
 {
@@ -73,8 +122,25 @@ where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
     }
 }
 
+/// Interpolation used when a percentile falls between two order statistics,
+/// following the NumPy method names.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PercentileMethod {
+    Linear,
+    Lower,
+    Higher,
+    Nearest,
+    Midpoint,
+}
+
 pub fn percentile<T>(data: &mut Vec<T>, percentile: f64) -> T 
 where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    percentile_with(data, percentile, PercentileMethod::Linear)
+}
+
+pub fn percentile_with<T>(data: &mut [T], percentile: f64, method: PercentileMethod) -> T 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
 {
     data.sort_by(|a, b| a.partial_cmp(b).unwrap());
 
@@ -82,14 +148,51 @@ where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
     let f = k.floor() as usize;
     let c = k.ceil() as usize;
     if f == c {
-        data[f]
-    } else {
-        let d0 = data[f] * ((c as f64 - k).into());
-        let d1 = data[c] * ((k - f as f64).into());
-        d0 + d1
+        return data[f];
+    }
+    match method {
+        PercentileMethod::Linear => {
+            let d0 = data[f] * ((c as f64 - k).into());
+            let d1 = data[c] * ((k - f as f64).into());
+            d0 + d1
+        }
+        PercentileMethod::Lower => data[f],
+        PercentileMethod::Higher => data[c],
+        PercentileMethod::Nearest => data[k.round_ties_even() as usize],
+        PercentileMethod::Midpoint => (data[f] + data[c]) / (2.0).into(),
     }
 }
 
+/// Counts `data` into `bins` equal-width bins and returns the `bins + 1`
+/// edges with the counts. Without a `range` the data min/max is used. As in
+/// NumPy, the last bin includes its right edge and values outside the range
+/// are not counted. Empty data or zero bins give empty vectors.
+pub fn histogram<T>(data: &[T], bins: usize, range: Option<(T, T)>) -> (Vec<T>, Vec<usize>) 
+where T: Float + Sum + From<f64> + PartialOrd + Copy + Product
+{
+    if data.is_empty() || bins == 0 {
+        return (Vec::new(), Vec::new());
+    }
+    let (mut low, mut high) = range.unwrap_or_else(|| {
+        data.iter().fold((data[0], data[0]), |(lo, hi), &x| (lo.min(x), hi.max(x)))
+    });
+    if low == high {
+        low = low - (0.5).into();
+        high = high + (0.5).into();
+    }
+    let width = (high - low) / (bins as f64).into();
+    let edges: Vec<T> = (0..=bins).map(|i| low + width * (i as f64).into()).collect();
+    let mut counts = vec![0; bins];
+    for &x in data {
+        if x < low || x > high {
+            continue;
+        }
+        let bin = ((x - low) / width).floor().to_usize().unwrap_or(bins).min(bins - 1);
+        counts[bin] += 1;
+    }
+    (edges, counts)
+}
+
 pub fn unique<T>(data: &Vec<T>) -> Vec<T> 
 where
     T: std::cmp::Eq + std::hash::Hash + Clone,
@@ -98,4 +201,106 @@ where
 
     let mut seen = HashSet::new();
     data.iter().filter(|&x| seen.insert(x.clone())).cloned().collect()
-}
\ No newline at end of file
+}
+
+/// Most frequent value of discrete data; ties go to the value seen first.
+pub fn mode<T>(data: &[T]) -> Option<T> 
+where
+    T: std::cmp::Eq + std::hash::Hash + Clone,
+{
+    use std::collections::HashMap;
+
+    let mut counts: HashMap<&T, usize> = HashMap::new();
+    for x in data {
+        *counts.entry(x).or_insert(0) += 1;
+    }
+    let mut best: Option<(&T, usize)> = None;
+    for x in data {
+        let count = counts[x];
+        if best.is_none_or(|(_, c)| count > c) {
+            best = Some((x, count));
+        }
+    }
+    best.map(|(x, _)| x.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[test]
+    fn test_median_floats() {
+        assert_eq!(median::<f64>(&mut vec![3.0, 1.0, 2.0]), 2.0);
+        assert_eq!(median::<f64>(&mut vec![4.0, 1.0, 3.0, 2.0]), 2.5);
+    }
+    #[test]
+    fn test_higher_moments() {
+        let data = vec![2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let m = mean::<f64>(data.clone());
+        let std = std_deviation(data.clone(), m);
+        assert_eq!((m, std), (5.0, 2.0));
+        // Third and fourth central moments are 42/8 and 356/8.
+        assert!((skewness(data.clone(), m, std) - 0.65625).abs() < 1e-12);
+        assert!((kurtosis(data.clone(), m, std) + 0.21875).abs() < 1e-12);
+        // A symmetric sample has no skew.
+        assert_eq!(skewness::<f64>(vec![1.0, 2.0, 3.0], 2.0, (2.0f64 / 3.0).sqrt()), 0.0);
+    }
+    #[test]
+    fn test_correlation() {
+        let x: Vec<f64> = (0..64).map(|n| ((n * n * 7 + 3 * n) % 11) as f64).collect();
+        let mut y = vec![0.0; 5];
+        y.extend_from_slice(&x[..59]);
+        let r = cross_correlation(&x, &y, 10);
+        assert_eq!(r.len(), 11);
+        let peak = r.iter().enumerate().max_by(|a, b| a.1.partial_cmp(b.1).unwrap()).unwrap().0;
+        assert_eq!(peak, 5);
+        assert!(r.iter().all(|v| v.abs() <= 1.0));
+
+        let auto = autocorrelation(&x, 100);
+        assert_eq!(auto.len(), 64);
+        assert!((auto[0] - 1.0).abs() < 1e-12);
+        assert!(auto[1..].iter().all(|&v| v < 1.0));
+    }
+    #[test]
+    fn test_histogram() {
+        let data = [0.0, 0.5, 1.0, 1.5, 2.0, 3.9, 4.0];
+        let (edges, counts) = histogram(&data, 4, None);
+        assert_eq!(edges, vec![0.0, 1.0, 2.0, 3.0, 4.0]);
+        assert_eq!(counts, vec![2, 2, 1, 2]);
+
+        let (edges, counts) = histogram(&data, 2, Some((1.0, 3.0)));
+        assert_eq!(edges, vec![1.0, 2.0, 3.0]);
+        assert_eq!(counts, vec![2, 1]);
+
+        let (edges, counts) = histogram(&[2.0; 3], 2, None);
+        assert_eq!(edges, vec![1.5, 2.0, 2.5]);
+        assert_eq!(counts, vec![0, 3]);
+
+        assert_eq!(histogram::<f64>(&[], 4, None), (vec![], vec![]));
+    }
+    #[test]
+    fn test_mode() {
+        assert_eq!(mode(&[1, 3, 3, 2, 2, 3]), Some(3));
+        assert_eq!(mode(&[2, 1, 1, 2]), Some(2));
+        assert_eq!(mode::<i32>(&[]), None);
+    }
+    #[test]
+    fn test_percentile_methods() {
+        let data = vec![40.0, 10.0, 30.0, 20.0];
+        let expected = [
+            (PercentileMethod::Linear, 22.0),
+            (PercentileMethod::Lower, 20.0),
+            (PercentileMethod::Higher, 30.0),
+            (PercentileMethod::Nearest, 20.0),
+            (PercentileMethod::Midpoint, 25.0),
+        ];
+        for (method, value) in expected {
+            let result = percentile_with::<f64>(&mut data.clone(), 40.0, method);
+            assert!((result - value).abs() < 1e-9, "{:?}: {} != {}", method, result, value);
+        }
+        assert!((percentile::<f64>(&mut data.clone(), 40.0) - 22.0).abs() < 1e-9);
+        // Exact order statistics are returned unchanged by every method.
+        assert_eq!(percentile_with::<f64>(&mut data.clone(), 100.0, PercentileMethod::Midpoint), 40.0);
+        // Nearest rounds half-way positions to the even index, as NumPy does.
+        assert_eq!(percentile_with::<f64>(&mut data.clone(), 50.0, PercentileMethod::Nearest), 30.0);
+    }
+}